@@ -0,0 +1,366 @@
+use bevy::asset::io::{AssetReaderError, AssetSourceId, Reader};
+use bevy::prelude::*;
+use bevy::tasks::futures::check_ready;
+use bevy::tasks::{IoTaskPool, Task};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use ui::{KeyBindings, UiInputCapture};
+
+use crate::game::UpdateSet;
+use crate::game::camera::{CameraFollow, CameraTween, TopDownCameraSettings, Viewer};
+
+/// A saved viewpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraBookmark {
+    pub focus_xz: Vec2,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+}
+
+/// Nine viewpoint slots: Ctrl+1..9 stores the current view, and 1..9 flies back to it.
+///
+/// Number keys that are bound to something else (by default 1..6 pick tools) only recall with
+/// Alt held; Alt+1..9 always works.
+///
+/// Kept in [`BOOKMARKS_PATH`] between sessions, as there's no world save file to keep them in
+/// yet.
+#[derive(Resource, Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraBookmarks {
+    slots: [Option<CameraBookmark>; 9],
+}
+
+impl CameraBookmarks {
+    /// Stores `bookmark` in `slot` (0-based); out of range slots are ignored.
+    pub fn store(&mut self, slot: usize, bookmark: CameraBookmark) {
+        if let Some(s) = self.slots.get_mut(slot) {
+            *s = Some(bookmark);
+        }
+    }
+
+    pub fn get(&self, slot: usize) -> Option<CameraBookmark> {
+        self.slots.get(slot).copied().flatten()
+    }
+}
+
+/// Where bookmarks are saved, in the default asset source (the `assets` folder on desktop).
+pub const BOOKMARKS_PATH: &str = "saves/camera_bookmarks.ron";
+
+/// The saved bookmarks being read in the background.
+#[derive(Resource)]
+struct BookmarksLoad(Task<Result<Option<CameraBookmarks>, BevyError>>);
+
+/// Time taken to fly to a recalled bookmark.
+const RECALL_SECS: f32 = 0.6;
+
+const SLOT_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+pub struct CameraBookmarksPlugin;
+
+impl Plugin for CameraBookmarksPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraBookmarks>()
+            .add_systems(Startup, load_bookmarks)
+            .add_systems(
+                Update,
+                (
+                    finish_loading_bookmarks.before(handle_bookmark_keys),
+                    handle_bookmark_keys.in_set(UpdateSet::CameraInput),
+                    save_bookmarks.after(handle_bookmark_keys),
+                ),
+            );
+    }
+}
+
+/// Starts reading the bookmarks saved by an earlier session.
+fn load_bookmarks(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let asset_server = asset_server.clone();
+    let task = IoTaskPool::get().spawn(async move { read_bookmarks(&asset_server).await });
+    commands.insert_resource(BookmarksLoad(task));
+}
+
+/// The saved bookmarks, or `None` if nothing has been saved yet.
+async fn read_bookmarks(asset_server: &AssetServer) -> Result<Option<CameraBookmarks>, BevyError> {
+    let source = asset_server.get_source(AssetSourceId::Default)?;
+    let mut reader = match source.reader().read(Path::new(BOOKMARKS_PATH)).await {
+        Ok(reader) => reader,
+        Err(AssetReaderError::NotFound(_)) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    Ok(Some(ron::de::from_bytes(&bytes)?))
+}
+
+/// Puts the saved bookmarks in once they've been read. Slots stored in the meantime are kept.
+fn finish_loading_bookmarks(
+    mut commands: Commands,
+    load: Option<ResMut<BookmarksLoad>>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+) {
+    let Some(mut load) = load else {
+        return;
+    };
+    let Some(result) = check_ready(&mut load.0) else {
+        return;
+    };
+    commands.remove_resource::<BookmarksLoad>();
+
+    let loaded = match result {
+        Ok(Some(loaded)) => loaded,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("ignoring saved camera bookmarks in {BOOKMARKS_PATH}: {e}");
+            return;
+        }
+    };
+    if bookmarks.slots.iter().all(Option::is_none) {
+        // Nothing new to write back.
+        *bookmarks.bypass_change_detection() = loaded;
+        return;
+    }
+    for (slot, saved) in bookmarks.slots.iter_mut().zip(loaded.slots) {
+        if slot.is_none() {
+            *slot = saved;
+        }
+    }
+}
+
+fn save_bookmarks(
+    bookmarks: Res<CameraBookmarks>,
+    load: Option<Res<BookmarksLoad>>,
+    asset_server: Res<AssetServer>,
+) {
+    // Saving before the old bookmarks are in would lose them; `finish_loading_bookmarks` changes
+    // the bookmarks again if there's anything to save by then.
+    if !bookmarks.is_changed() || bookmarks.is_added() || load.is_some() {
+        return;
+    }
+    let text = match ron::ser::to_string_pretty(&*bookmarks, ron::ser::PrettyConfig::default()) {
+        Ok(text) => text,
+        Err(e) => {
+            error!("camera bookmarks couldn't be serialized: {e}");
+            return;
+        }
+    };
+    let asset_server = asset_server.clone();
+    IoTaskPool::get()
+        .spawn(async move {
+            if let Err(e) = write_bookmarks(&asset_server, &text).await {
+                error!("saving camera bookmarks to {BOOKMARKS_PATH} failed: {e}");
+            }
+        })
+        .detach();
+}
+
+async fn write_bookmarks(asset_server: &AssetServer, text: &str) -> Result<(), BevyError> {
+    let writer = asset_server.get_source(AssetSourceId::Default)?.writer()?;
+    writer
+        .write_bytes(Path::new(BOOKMARKS_PATH), text.as_bytes())
+        .await?;
+    Ok(())
+}
+
+fn handle_bookmark_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ui_capture: Res<UiInputCapture>,
+    settings: Res<TopDownCameraSettings>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut tween: ResMut<CameraTween>,
+    mut follow: ResMut<CameraFollow>,
+    q_focus: Query<&Transform, With<Viewer>>,
+) {
+    if ui_capture.keyboard {
+        return;
+    }
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    if ctrl && alt {
+        return;
+    }
+    let Some(slot) = SLOT_KEYS.iter().position(|k| keys.just_pressed(*k)) else {
+        return;
+    };
+    // A plain press of a number that's bound elsewhere belongs to that binding.
+    if !ctrl && !alt && bindings.is_bound(SLOT_KEYS[slot]) {
+        return;
+    }
+
+    if ctrl {
+        let Ok(focus) = q_focus.single() else {
+            return;
+        };
+        bookmarks.store(
+            slot,
+            CameraBookmark {
+                focus_xz: Vec2::new(focus.translation.x, focus.translation.z),
+                yaw: settings.yaw,
+                pitch: settings.pitch,
+                distance: settings.distance,
+            },
+        );
+        info!("camera bookmark {} stored", slot + 1);
+    } else if let Some(bookmark) = bookmarks.get(slot) {
+        if follow.0.is_some() {
+            follow.clear();
+        }
+        tween.fly_to_view(
+            bookmark.focus_xz,
+            bookmark.distance,
+            bookmark.yaw,
+            bookmark.pitch,
+            RECALL_SECS,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use terrain::{TerrainConfig, TerrainWorld};
+
+    use crate::game::camera::update_camera_tween;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<UiInputCapture>()
+            .init_resource::<TopDownCameraSettings>()
+            .init_resource::<CameraBookmarks>()
+            .init_resource::<CameraTween>()
+            .init_resource::<CameraFollow>()
+            .init_resource::<Time>()
+            .insert_resource(TerrainWorld::new(TerrainConfig::default()))
+            .add_systems(Update, (handle_bookmark_keys, update_camera_tween).chain());
+        app.world_mut()
+            .spawn((Viewer, Transform::from_xyz(12.0, 0.0, -7.0)));
+        app
+    }
+
+    fn press(app: &mut App, keys: &[KeyCode]) {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        input.reset_all();
+        for key in keys {
+            input.press(*key);
+        }
+        app.update();
+    }
+
+    fn view(app: &mut App) -> CameraBookmark {
+        let focus = app
+            .world_mut()
+            .query_filtered::<&Transform, With<Viewer>>()
+            .single(app.world())
+            .unwrap()
+            .translation;
+        let settings = app.world().resource::<TopDownCameraSettings>();
+        CameraBookmark {
+            focus_xz: Vec2::new(focus.x, focus.z),
+            yaw: settings.yaw,
+            pitch: settings.pitch,
+            distance: settings.distance,
+        }
+    }
+
+    #[test]
+    fn store_then_recall_restores_the_view() {
+        let mut app = app();
+        {
+            let mut settings = app.world_mut().resource_mut::<TopDownCameraSettings>();
+            settings.yaw = 0.4;
+            settings.pitch = (settings.min_pitch + settings.max_pitch) * 0.5;
+            settings.distance = (settings.min_distance + settings.max_distance) * 0.5;
+        }
+        let stored = view(&mut app);
+        press(&mut app, &[KeyCode::ControlLeft, KeyCode::Digit8]);
+        assert_eq!(
+            app.world().resource::<CameraBookmarks>().get(7),
+            Some(stored)
+        );
+
+        // Look somewhere else, then recall with the plain (unbound) number key.
+        {
+            let mut settings = app.world_mut().resource_mut::<TopDownCameraSettings>();
+            settings.yaw = -2.0;
+            settings.pitch = settings.min_pitch;
+            settings.distance = settings.max_distance;
+        }
+        app.world_mut()
+            .query_filtered::<&mut Transform, With<Viewer>>()
+            .single_mut(app.world_mut())
+            .unwrap()
+            .translation = Vec3::new(-40.0, 0.0, 90.0);
+        press(&mut app, &[KeyCode::Digit8]);
+        assert!(app.world().resource::<CameraTween>().is_active());
+
+        // Let the flight finish.
+        press(&mut app, &[]);
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(RECALL_SECS * 2.0));
+        press(&mut app, &[]);
+        assert!(!app.world().resource::<CameraTween>().is_active());
+
+        let recalled = view(&mut app);
+        assert!(recalled.focus_xz.abs_diff_eq(stored.focus_xz, 1e-4));
+        assert!((recalled.yaw - stored.yaw).abs() < 1e-4);
+        assert!((recalled.pitch - stored.pitch).abs() < 1e-4);
+        assert!((recalled.distance - stored.distance).abs() < 1e-4);
+    }
+
+    #[test]
+    fn bound_numbers_need_alt_to_recall() {
+        let mut app = app();
+        press(&mut app, &[KeyCode::ControlLeft, KeyCode::Digit1]);
+        assert!(app.world().resource::<CameraBookmarks>().get(0).is_some());
+
+        // 1 picks a tool by default, so a plain press leaves the camera alone.
+        press(&mut app, &[KeyCode::Digit1]);
+        assert!(!app.world().resource::<CameraTween>().is_active());
+
+        press(&mut app, &[KeyCode::AltLeft, KeyCode::Digit1]);
+        assert!(app.world().resource::<CameraTween>().is_active());
+    }
+
+    #[test]
+    fn bookmarks_round_trip_through_ron() {
+        let mut bookmarks = CameraBookmarks::default();
+        bookmarks.store(
+            0,
+            CameraBookmark {
+                focus_xz: Vec2::new(-3.5, 120.25),
+                yaw: 1.25,
+                pitch: 0.8,
+                distance: 42.0,
+            },
+        );
+        bookmarks.store(
+            6,
+            CameraBookmark {
+                focus_xz: Vec2::new(1e4, -0.125),
+                yaw: -3.0,
+                pitch: 0.3,
+                distance: 7.5,
+            },
+        );
+
+        let text =
+            ron::ser::to_string_pretty(&bookmarks, ron::ser::PrettyConfig::default()).unwrap();
+        let loaded: CameraBookmarks = ron::from_str(&text).unwrap();
+        assert_eq!(loaded, bookmarks);
+    }
+}
@@ -1,6 +1,9 @@
+use bevy::camera::ScalingMode;
 use bevy::input::mouse::{MouseMotion, MouseWheel};
 use bevy::prelude::*;
-use ui::UiInputCapture;
+use objects::system::{CursorHit, HoveredObject};
+use terrain::TerrainWorld;
+use ui::{InputAction, KeyBindings, UiInputCapture};
 
 use super::{StartupSet, UpdateSet};
 
@@ -9,11 +12,24 @@ pub struct CameraPlugin;
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(TopDownCameraSettings::default())
+            .init_resource::<CameraTween>()
+            .init_resource::<CameraFollow>()
+            .init_resource::<ScrollCaptured>()
             .add_systems(Startup, setup_viewer.in_set(StartupSet::Camera))
-            .add_systems(Update, top_down_camera_input.in_set(UpdateSet::CameraInput))
             .add_systems(
                 Update,
-                update_top_down_camera.in_set(UpdateSet::CameraUpdate),
+                (
+                    top_down_camera_input,
+                    focus_hovered_object,
+                    toggle_projection_mode,
+                )
+                    .in_set(UpdateSet::CameraInput),
+            )
+            .add_systems(
+                Update,
+                (update_camera_tween, follow_entity, update_top_down_camera)
+                    .chain()
+                    .in_set(UpdateSet::CameraUpdate),
             );
     }
 }
@@ -21,13 +37,38 @@ impl Plugin for CameraPlugin {
 #[derive(Component)]
 pub struct Viewer;
 
+/// Set by tools that use the scroll wheel themselves (before [`UpdateSet::CameraInput`]), so
+/// scrolling doesn't zoom as well.
+#[derive(Resource, Default)]
+pub struct ScrollCaptured(pub bool);
+
 #[derive(Component)]
 pub struct TopDownCamera;
 
+/// The camera the player drives and picks through. Further cameras (picture-in-picture, a
+/// render-to-texture minimap) may also be `TopDownCamera`s, but only this one follows the
+/// viewer and is used for cursor rays.
+#[derive(Component)]
+pub struct PrimaryCamera;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CameraProjectionMode {
+    #[default]
+    Perspective,
+    /// Near and far things draw the same size. `distance` still orbits the camera and sets the
+    /// zoom, matching what perspective shows at the focus.
+    Orthographic,
+}
+
 #[derive(Resource, Clone)]
 pub struct TopDownCameraSettings {
     pub yaw: f32,
+    /// Tilt below the horizontal, in radians; kept within `min_pitch..=max_pitch`.
     pub pitch: f32,
+    /// Flattest allowed tilt. Keep above 0 so the camera never looks up from below the ground.
+    pub min_pitch: f32,
+    /// Steepest allowed tilt. Keep below a right angle so the view never flips over.
+    pub max_pitch: f32,
     pub distance: f32,
     pub min_distance: f32,
     pub max_distance: f32,
@@ -36,6 +77,13 @@ pub struct TopDownCameraSettings {
     pub rotate_speed: f32,
     pub zoom_speed: f32,
     pub mouse_pan_sensitivity: f32,
+    /// Zoom used when flying to a hovered object; a closer current zoom is kept.
+    pub focus_distance: f32,
+    pub projection: CameraProjectionMode,
+    /// Scroll zoom towards the point under the cursor rather than the focus.
+    pub zoom_to_cursor: bool,
+    /// How quickly the focus catches up with a followed entity, per second; 0 snaps to it.
+    pub follow_smoothing: f32,
 }
 
 impl Default for TopDownCameraSettings {
@@ -43,6 +91,8 @@ impl Default for TopDownCameraSettings {
         Self {
             yaw: 0.8,
             pitch: 1.05,
+            min_pitch: 0.35,
+            max_pitch: 1.5,
             distance: 80.0,
             min_distance: 10.0,
             max_distance: 400.0,
@@ -51,25 +101,111 @@ impl Default for TopDownCameraSettings {
             rotate_speed: 1.8,
             zoom_speed: 0.12,
             mouse_pan_sensitivity: 0.12,
+            focus_distance: 40.0,
+            projection: CameraProjectionMode::Perspective,
+            zoom_to_cursor: false,
+            follow_smoothing: 8.0,
         }
     }
 }
 
+struct Tween {
+    /// Focus XZ and distance when the tween first ran; captured lazily so `fly_to` doesn't
+    /// need to know where the camera is.
+    from: Option<(Vec2, f32)>,
+    to_xz: Vec2,
+    to_distance: f32,
+    /// Yaw and pitch to turn to, if the tween changes the angle too.
+    to_angles: Option<Vec2>,
+    /// Yaw and pitch when the tween first ran, if it turns.
+    from_angles: Option<Vec2>,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// An in-progress smooth move of the camera focus and zoom.
+#[derive(Resource, Default)]
+pub struct CameraTween {
+    active: Option<Tween>,
+}
+
+impl CameraTween {
+    /// Smoothly move the focus to `target_xz` and the zoom to `distance` over `secs` seconds.
+    ///
+    /// Replaces any tween already in progress. Manual camera input cancels it.
+    pub fn fly_to(&mut self, target_xz: Vec2, distance: f32, secs: f32) {
+        self.active = Some(Tween {
+            from: None,
+            to_xz: target_xz,
+            to_distance: distance,
+            to_angles: None,
+            from_angles: None,
+            duration: secs.max(0.0),
+            elapsed: 0.0,
+        });
+    }
+
+    /// Like [`fly_to`](Self::fly_to), also turning the camera to `yaw` and `pitch`. Yaw turns
+    /// the short way round.
+    pub fn fly_to_view(&mut self, target_xz: Vec2, distance: f32, yaw: f32, pitch: f32, secs: f32) {
+        self.fly_to(target_xz, distance, secs);
+        if let Some(active) = self.active.as_mut() {
+            active.to_angles = Some(Vec2::new(yaw, pitch));
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.active = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+}
+
+/// Keeps the camera focus on an entity while set. Rotate and zoom still work; panning stops
+/// following, as does the entity going away.
+///
+/// A fly-to [`CameraTween`] takes priority while it runs and following resumes when it ends, so
+/// flying to the entity and then following it gives a smooth start.
+#[derive(Resource, Default)]
+pub struct CameraFollow(pub Option<Entity>);
+
+impl CameraFollow {
+    pub fn set(&mut self, entity: Entity) {
+        self.0 = Some(entity);
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+}
+
 pub fn setup_viewer(mut commands: Commands) {
     commands.spawn((Viewer, Transform::from_xyz(0.0, 0.0, 0.0)));
 
-    commands.spawn((TopDownCamera, Camera3d::default(), Transform::default()));
+    commands.spawn((
+        TopDownCamera,
+        PrimaryCamera,
+        Camera3d::default(),
+        Transform::default(),
+    ));
 }
 
 pub fn top_down_camera_input(
     time: Res<Time>,
     keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     mut mouse_wheel: MessageReader<MouseWheel>,
     mut mouse_motion: MessageReader<MouseMotion>,
     mut settings: ResMut<TopDownCameraSettings>,
     mut q_focus: Query<&mut Transform, With<Viewer>>,
+    mut tween: ResMut<CameraTween>,
     ui_capture: Res<UiInputCapture>,
+    scroll_captured: Res<ScrollCaptured>,
+    hit: Res<CursorHit>,
+    mut follow: ResMut<CameraFollow>,
 ) {
     let mut focus = match q_focus.single_mut() {
         Ok(t) => t,
@@ -79,12 +215,36 @@ pub fn top_down_camera_input(
     // Keyboard input: ignore while egui is actively consuming keyboard input (e.g. text field).
     if !ui_capture.keyboard {
         // Rotate around focus
-        if keys.pressed(KeyCode::KeyQ) {
+        if bindings.pressed(&keys, InputAction::OrbitLeft) {
             settings.yaw += settings.rotate_speed * time.delta_secs();
         }
-        if keys.pressed(KeyCode::KeyE) {
+        if bindings.pressed(&keys, InputAction::OrbitRight) {
             settings.yaw -= settings.rotate_speed * time.delta_secs();
         }
+
+        // Tilt
+        let mut tilt = 0.0;
+        if bindings.pressed(&keys, InputAction::TiltSteeper) {
+            tilt += 1.0;
+        }
+        if bindings.pressed(&keys, InputAction::TiltFlatter) {
+            tilt -= 1.0;
+        }
+        if bindings.just_pressed(&keys, InputAction::ResetCameraAngle) {
+            let defaults = TopDownCameraSettings::default();
+            settings.yaw = defaults.yaw;
+            settings.pitch = defaults.pitch;
+        }
+        if tilt != 0.0 {
+            settings.pitch += tilt * settings.rotate_speed * time.delta_secs();
+        }
+        let pitch = settings.pitch.clamp(
+            settings.min_pitch,
+            settings.max_pitch.max(settings.min_pitch),
+        );
+        if settings.pitch != pitch {
+            settings.pitch = pitch;
+        }
     }
 
     // Pointer input: ignore while cursor is over / interacting with egui.
@@ -94,27 +254,44 @@ pub fn top_down_camera_input(
         for ev in mouse_wheel.read() {
             scroll += ev.y;
         }
-        if scroll.abs() > 0.0 {
+        if scroll.abs() > 0.0 && !scroll_captured.0 {
+            tween.cancel();
             // Exponential-ish feel, similar to city builder cameras.
             let factor = (1.0 - scroll * settings.zoom_speed).clamp(0.2, 5.0);
+            let old_distance = settings.distance;
             settings.distance =
                 (settings.distance * factor).clamp(settings.min_distance, settings.max_distance);
+
+            // Scale the focus about the point under the cursor by the same ratio as the zoom,
+            // so that point stays (roughly) where it is on screen.
+            if settings.zoom_to_cursor
+                && follow.0.is_none()
+                && let Some(anchor) = hit.world
+            {
+                let ratio = settings.distance / old_distance;
+                let offset = Vec2::new(
+                    focus.translation.x - anchor.x,
+                    focus.translation.z - anchor.z,
+                ) * ratio;
+                focus.translation.x = anchor.x + offset.x;
+                focus.translation.z = anchor.z + offset.y;
+            }
         }
     }
 
     // Pan (keyboard) on XZ plane, relative to camera yaw.
     let mut input = Vec2::ZERO;
     if !ui_capture.keyboard {
-        if keys.pressed(KeyCode::KeyW) {
+        if bindings.pressed(&keys, InputAction::PanForward) {
             input.y += 1.0;
         }
-        if keys.pressed(KeyCode::KeyS) {
+        if bindings.pressed(&keys, InputAction::PanBackward) {
             input.y -= 1.0;
         }
-        if keys.pressed(KeyCode::KeyA) {
+        if bindings.pressed(&keys, InputAction::PanLeft) {
             input.x += 1.0;
         }
-        if keys.pressed(KeyCode::KeyD) {
+        if bindings.pressed(&keys, InputAction::PanRight) {
             input.x -= 1.0;
         }
     }
@@ -124,6 +301,10 @@ pub fn top_down_camera_input(
     let forward = yaw_rot * Vec3::Z;
 
     if input.length_squared() > 0.0 {
+        tween.cancel();
+        if follow.0.is_some() {
+            follow.clear();
+        }
         let speed = if keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight) {
             settings.pan_speed_fast
         } else {
@@ -142,6 +323,10 @@ pub fn top_down_camera_input(
                 drag += ev.delta;
             }
             if drag.length_squared() > 0.0 {
+                tween.cancel();
+                if follow.0.is_some() {
+                    follow.clear();
+                }
                 let scale = settings.mouse_pan_sensitivity * (settings.distance / 80.0);
                 // Screen-space: +x right, +y up. Dragging right should move focus left.
                 let delta = (-right * drag.x + forward * drag.y) * scale;
@@ -151,16 +336,174 @@ pub fn top_down_camera_input(
     }
 }
 
+pub fn toggle_projection_mode(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ui_capture: Res<UiInputCapture>,
+    mut settings: ResMut<TopDownCameraSettings>,
+) {
+    if ui_capture.keyboard || !bindings.just_pressed(&keys, InputAction::ToggleProjection) {
+        return;
+    }
+    settings.projection = match settings.projection {
+        CameraProjectionMode::Perspective => CameraProjectionMode::Orthographic,
+        CameraProjectionMode::Orthographic => CameraProjectionMode::Perspective,
+    };
+}
+
+pub fn focus_hovered_object(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ui_capture: Res<UiInputCapture>,
+    hovered: Res<HoveredObject>,
+    settings: Res<TopDownCameraSettings>,
+    mut tween: ResMut<CameraTween>,
+    mut follow: ResMut<CameraFollow>,
+    q_transforms: Query<&GlobalTransform>,
+) {
+    if ui_capture.keyboard || !bindings.just_pressed(&keys, InputAction::FocusHovered) {
+        return;
+    }
+    // Ctrl+C is copy.
+    if keys.pressed(KeyCode::ControlLeft) || keys.pressed(KeyCode::ControlRight) {
+        return;
+    }
+    let Some((entity, transform)) = hovered
+        .0
+        .and_then(|e| q_transforms.get(e).ok().map(|t| (e, t)))
+    else {
+        return;
+    };
+
+    let target = transform.translation();
+    let distance = settings.distance.min(settings.focus_distance);
+    tween.fly_to(Vec2::new(target.x, target.z), distance, 0.5);
+    // Shift keeps following it after the flight.
+    if keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight) {
+        follow.set(entity);
+    } else if follow.0.is_some() {
+        follow.clear();
+    }
+}
+
+pub fn follow_entity(
+    time: Res<Time>,
+    settings: Res<TopDownCameraSettings>,
+    tween: Res<CameraTween>,
+    mut follow: ResMut<CameraFollow>,
+    q_targets: Query<&GlobalTransform>,
+    mut q_focus: Query<&mut Transform, With<Viewer>>,
+) {
+    let Some(entity) = follow.0 else {
+        return;
+    };
+    let Ok(target) = q_targets.get(entity) else {
+        // Despawned.
+        follow.clear();
+        return;
+    };
+    if tween.is_active() {
+        return;
+    }
+    let Ok(mut focus) = q_focus.single_mut() else {
+        return;
+    };
+
+    let target = target.translation();
+    let goal = Vec2::new(target.x, target.z);
+    let current = Vec2::new(focus.translation.x, focus.translation.z);
+    let next = if settings.follow_smoothing > 0.0 {
+        let t = 1.0 - (-settings.follow_smoothing * time.delta_secs()).exp();
+        current.lerp(goal, t)
+    } else {
+        goal
+    };
+    if next != current {
+        focus.translation.x = next.x;
+        focus.translation.z = next.y;
+    }
+}
+
+pub fn update_camera_tween(
+    time: Res<Time>,
+    mut tween: ResMut<CameraTween>,
+    mut settings: ResMut<TopDownCameraSettings>,
+    mut terrain: ResMut<TerrainWorld>,
+    mut prefetching: Local<bool>,
+    mut q_focus: Query<&mut Transform, With<Viewer>>,
+) {
+    if !tween.is_active() {
+        // Finished or cancelled by manual input; let the normal view distance take over.
+        if *prefetching {
+            terrain.cancel_prefetch();
+            *prefetching = false;
+        }
+        return;
+    }
+    let Ok(mut focus) = q_focus.single_mut() else {
+        return;
+    };
+    let Some(active) = tween.active.as_mut() else {
+        return;
+    };
+
+    if active.from.is_none() {
+        // Warm the destination so the flight doesn't outrun chunk streaming.
+        let center = terrain.world_to_chunk_coord(active.to_xz);
+        let radius = terrain.config.view_distance_chunks;
+        terrain.prefetch(center, radius);
+        *prefetching = true;
+    }
+    let (from_xz, from_distance) = *active.from.get_or_insert((
+        Vec2::new(focus.translation.x, focus.translation.z),
+        settings.distance,
+    ));
+
+    active.elapsed += time.delta_secs();
+    let t = if active.duration > 0.0 {
+        (active.elapsed / active.duration).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    // Smoothstep: eases in and out so the move doesn't start or stop abruptly.
+    let eased = t * t * (3.0 - 2.0 * t);
+
+    let xz = from_xz.lerp(active.to_xz, eased);
+    focus.translation.x = xz.x;
+    focus.translation.z = xz.y;
+    settings.distance = (from_distance + (active.to_distance - from_distance) * eased)
+        .clamp(settings.min_distance, settings.max_distance);
+
+    if let Some(to) = active.to_angles {
+        let from = *active
+            .from_angles
+            .get_or_insert(Vec2::new(settings.yaw, settings.pitch));
+        let yaw_delta = (to.x - from.x + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU)
+            - std::f32::consts::PI;
+        settings.yaw = from.x + yaw_delta * eased;
+        settings.pitch = (from.y + (to.y - from.y) * eased).clamp(
+            settings.min_pitch,
+            settings.max_pitch.max(settings.min_pitch),
+        );
+    }
+
+    if t >= 1.0 {
+        tween.cancel();
+    }
+}
+
+type PrimaryCameraPose = (&'static mut Transform, &'static mut Projection);
+
 pub fn update_top_down_camera(
     settings: Res<TopDownCameraSettings>,
-    q_focus: Query<&Transform, (With<Viewer>, Without<TopDownCamera>)>,
-    mut q_cam: Query<&mut Transform, (With<TopDownCamera>, Without<Viewer>)>,
+    q_focus: Query<&Transform, (With<Viewer>, Without<PrimaryCamera>)>,
+    mut q_cam: Query<PrimaryCameraPose, (With<PrimaryCamera>, Without<Viewer>)>,
 ) {
     let focus = match q_focus.single() {
         Ok(v) => v.translation,
         Err(_) => return,
     };
-    let mut cam = match q_cam.single_mut() {
+    let (mut cam, mut projection) = match q_cam.single_mut() {
         Ok(c) => c,
         Err(_) => return,
     };
@@ -169,4 +512,245 @@ pub fn update_top_down_camera(
     let offset = rot * Vec3::new(0.0, 0.0, -settings.distance);
     cam.translation = focus + offset;
     cam.look_at(focus, Vec3::Y);
+
+    apply_projection_mode(&settings, &mut projection);
+}
+
+/// Switches the projection to `settings.projection`, writing only when it has to change.
+fn apply_projection_mode(settings: &TopDownCameraSettings, projection: &mut Mut<Projection>) {
+    let perspective = PerspectiveProjection::default();
+    // Height perspective shows at the focus, so switching modes keeps the zoom.
+    let viewport_height = 2.0 * settings.distance * (perspective.fov * 0.5).tan();
+
+    let ortho_height = match &**projection {
+        Projection::Orthographic(OrthographicProjection {
+            scaling_mode: ScalingMode::FixedVertical { viewport_height },
+            ..
+        }) => Some(*viewport_height),
+        _ => None,
+    };
+
+    match settings.projection {
+        CameraProjectionMode::Perspective => {
+            if !matches!(**projection, Projection::Perspective(_)) {
+                **projection = Projection::Perspective(perspective);
+            }
+        }
+        CameraProjectionMode::Orthographic => {
+            if ortho_height != Some(viewport_height) {
+                **projection = Projection::Orthographic(OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical { viewport_height },
+                    ..OrthographicProjection::default_3d()
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::camera::{ComputedCameraValues, RenderTargetInfo};
+    use bevy::window::WindowResolution;
+    use std::time::Duration;
+    use terrain::{HeightSource, TerrainConfig, TerrainSource};
+
+    use crate::game::input::update_cursor_hit;
+
+    const WINDOW: UVec2 = UVec2::new(800, 600);
+
+    /// Flat ground at height 0, so the ray under the window centre lands on the focus.
+    struct Flat;
+
+    impl HeightSource for Flat {
+        fn sample(&self, _x: f64, _z: f64) -> f64 {
+            0.0
+        }
+
+        fn height_range(&self) -> (f32, f32) {
+            (0.0, 0.0)
+        }
+    }
+
+    /// Stands in for the renderer's camera and transform systems, which don't run headless:
+    /// fits each projection to the window and fills in what `viewport_to_world` reads.
+    fn fit_cameras(
+        mut q: Query<(
+            &mut Camera,
+            &mut Projection,
+            &Transform,
+            &mut GlobalTransform,
+        )>,
+    ) {
+        for (mut camera, mut projection, transform, mut global) in &mut q {
+            projection.update(WINDOW.x as f32, WINDOW.y as f32);
+            camera.computed = ComputedCameraValues {
+                clip_from_view: projection.get_clip_from_view(),
+                target_info: Some(RenderTargetInfo {
+                    physical_size: WINDOW,
+                    scale_factor: 1.0,
+                }),
+                ..default()
+            };
+            *global = (*transform).into();
+        }
+    }
+
+    fn app() -> App {
+        let mut terrain = TerrainWorld::new(TerrainConfig::default());
+        let _ = terrain.set_source(TerrainSource::Custom(Box::new(Flat)));
+
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<UiInputCapture>()
+            .init_resource::<ScrollCaptured>()
+            .init_resource::<TopDownCameraSettings>()
+            .init_resource::<CameraTween>()
+            .init_resource::<CameraFollow>()
+            .init_resource::<CursorHit>()
+            .insert_resource(terrain)
+            .add_message::<MouseWheel>()
+            .add_message::<MouseMotion>()
+            .add_systems(
+                Update,
+                (
+                    top_down_camera_input,
+                    update_top_down_camera,
+                    fit_cameras,
+                    update_cursor_hit,
+                )
+                    .chain(),
+            );
+
+        let mut window = Window {
+            resolution: WindowResolution::new(WINDOW.x, WINDOW.y),
+            ..default()
+        };
+        window.set_cursor_position(Some(WINDOW.as_vec2() / 2.0));
+        app.world_mut().spawn(window);
+        app.world_mut().spawn((Viewer, Transform::default()));
+        app
+    }
+
+    fn spawn_camera(app: &mut App, transform: Transform) -> Entity {
+        app.world_mut()
+            .spawn((
+                TopDownCamera,
+                Camera::default(),
+                Projection::default(),
+                transform,
+            ))
+            .id()
+    }
+
+    fn viewer(app: &mut App) -> Vec3 {
+        app.world_mut()
+            .query_filtered::<&Transform, With<Viewer>>()
+            .single(app.world())
+            .unwrap()
+            .translation
+    }
+
+    #[test]
+    fn input_and_picking_follow_the_primary_camera() {
+        let mut app = app();
+        // A second view spawned first, looking somewhere else entirely.
+        let side_view = Transform::from_xyz(500.0, 40.0, 500.0)
+            .looking_at(Vec3::new(520.0, 0.0, 500.0), Vec3::Y);
+        let side = spawn_camera(&mut app, side_view);
+        let primary = spawn_camera(&mut app, Transform::default());
+        app.world_mut().entity_mut(primary).insert(PrimaryCamera);
+
+        let forward = app
+            .world()
+            .resource::<KeyBindings>()
+            .key(InputAction::PanForward)
+            .unwrap();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(forward);
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(0.5));
+        app.update();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .release(forward);
+        app.update();
+
+        let settings = app.world().resource::<TopDownCameraSettings>().clone();
+        let panned = Quat::from_rotation_y(settings.yaw) * Vec3::Z * settings.pan_speed * 0.5;
+        let focus = viewer(&mut app);
+        assert!(
+            focus.distance(panned) < 1e-3,
+            "viewer at {focus}, expected {panned}"
+        );
+
+        let camera = app.world().get::<Transform>(primary).unwrap();
+        assert!((camera.translation.distance(focus) - settings.distance).abs() < 1e-3);
+        assert!(
+            camera
+                .forward()
+                .dot((focus - camera.translation).normalize())
+                > 0.9999
+        );
+        assert_eq!(app.world().get::<Transform>(side), Some(&side_view));
+
+        let hit = app.world().resource::<CursorHit>().world.unwrap();
+        assert!(hit.distance(focus) < 0.05, "picked {hit}, focus {focus}");
+    }
+
+    #[test]
+    fn orthographic_picking_hits_under_the_cursor() {
+        let mut app = app();
+        let camera = spawn_camera(&mut app, Transform::default());
+        app.world_mut().entity_mut(camera).insert(PrimaryCamera);
+        app.world_mut()
+            .resource_mut::<TopDownCameraSettings>()
+            .projection = CameraProjectionMode::Orthographic;
+        app.update();
+        app.update();
+
+        let Some(Projection::Orthographic(ortho)) = app.world().get::<Projection>(camera) else {
+            panic!("camera didn't switch to orthographic");
+        };
+        let ScalingMode::FixedVertical { viewport_height } = ortho.scaling_mode else {
+            panic!("unexpected scaling mode {:?}", ortho.scaling_mode);
+        };
+        let hit = app.world().resource::<CursorHit>().world.unwrap();
+        assert!(hit.length() < 0.05, "picked {hit}, focus at the origin");
+
+        // Rays are parallel, so a cursor moved sideways hits the same distance along the
+        // camera's right, however far the ground is.
+        let centre = WINDOW.as_vec2() / 2.0;
+        let offset = 100.0;
+        let mut windows = app.world_mut().query::<&mut Window>();
+        windows
+            .single_mut(app.world_mut())
+            .unwrap()
+            .set_cursor_position(Some(centre + Vec2::X * offset));
+        app.update();
+
+        let transform = *app.world().get::<Transform>(camera).unwrap();
+        let expected = transform.right() * offset * viewport_height / WINDOW.y as f32;
+        let hit = app.world().resource::<CursorHit>().world.unwrap();
+        assert!(
+            hit.distance(expected) < 0.05,
+            "picked {hit}, expected {expected}"
+        );
+
+        let (cam, global) = app
+            .world_mut()
+            .query::<(&Camera, &GlobalTransform)>()
+            .get(app.world(), camera)
+            .unwrap();
+        let a = cam.viewport_to_world(global, centre).unwrap();
+        let b = cam
+            .viewport_to_world(global, Vec2::new(30.0, 500.0))
+            .unwrap();
+        assert!(a.direction.dot(*b.direction) > 0.99999);
+    }
 }
@@ -0,0 +1,297 @@
+use bevy::prelude::*;
+
+use objects::highlight;
+use objects::instancing::InstancedMeshes;
+use objects::spatial::SpatialHashGrid;
+use objects::system::{
+    CursorHit, HoveredObject, ObjectKind, ObjectScale, ObjectTypes, Selection, heading_of,
+};
+use terrain::{BiomeConfig, TerrainWorld};
+use ui::{ToolId, ToolbarState, UiInputCapture};
+
+use crate::game::UpdateSet;
+use crate::game::history::{EditAction, EditHistory};
+use crate::game::modes::construction::{
+    HologramMaterials, HologramPreview, PlacedObjects, PlacementBudget, PlacementCheck,
+    PlacementError, PlacementSettings, placement_xz,
+};
+use crate::game::modes::edit::EditTarget;
+
+/// A copied object. The type is kept by name so the copy survives definition reloads.
+#[derive(Clone, Debug)]
+pub struct ClipboardEntry {
+    pub type_name: String,
+    pub yaw: f32,
+    pub scale: Vec3,
+}
+
+#[derive(Resource, Default)]
+pub struct Clipboard(pub Option<ClipboardEntry>);
+
+/// Pasting is its own state rather than a tool: Ctrl+V enters it from any tool (dropping the
+/// active one) and picking a tool again leaves it.
+#[derive(Resource, Default)]
+pub struct PasteState {
+    pub active: bool,
+    preview: HologramPreview,
+}
+
+pub struct ClipboardPlugin;
+
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Clipboard>()
+            .init_resource::<PasteState>()
+            .add_systems(
+                Update,
+                (
+                    handle_copy_paste_keys.after(UpdateSet::UiCapture),
+                    update_paste_preview,
+                    handle_paste_click,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn handle_copy_paste_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    ui_capture: Res<UiInputCapture>,
+    mut toolbar: ResMut<ToolbarState>,
+    selection: Res<Selection>,
+    edit_target: Res<EditTarget>,
+    hovered: Res<HoveredObject>,
+    types: Option<Res<ObjectTypes>>,
+    q_objects: Query<(&Transform, &ObjectKind, &ObjectScale)>,
+    mut clipboard: ResMut<Clipboard>,
+    mut paste: ResMut<PasteState>,
+) {
+    if ui_capture.keyboard {
+        return;
+    }
+
+    if paste.active && keys.just_pressed(KeyCode::Escape) {
+        paste.active = false;
+        return;
+    }
+
+    let ctrl = keys.pressed(KeyCode::ControlLeft) || keys.pressed(KeyCode::ControlRight);
+    if !ctrl {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyC) {
+        // The object being edited, else the most recently selected one, else whatever is under
+        // the cursor.
+        let source = edit_target
+            .entity
+            .filter(|_| toolbar.active_tool == Some(ToolId::Edit))
+            .or_else(|| selection.0.last().copied())
+            .or(hovered.0);
+        let Some(types) = types else {
+            return;
+        };
+        let Some((transform, kind, scale)) = source.and_then(|e| q_objects.get(e).ok()) else {
+            return;
+        };
+        let Some(spec) = types.registry.get(kind.0) else {
+            return;
+        };
+        clipboard.0 = Some(ClipboardEntry {
+            type_name: spec.name.clone(),
+            yaw: heading_of(transform.rotation),
+            scale: scale.0,
+        });
+    } else if keys.just_pressed(KeyCode::KeyV) && clipboard.0.is_some() {
+        paste.active = true;
+        toolbar.active_tool = None;
+    }
+}
+
+fn update_paste_preview(
+    mut commands: Commands,
+    terrain: Res<TerrainWorld>,
+    asset_server: Res<AssetServer>,
+    types: Option<Res<ObjectTypes>>,
+    q_objects: PlacedObjects,
+    toolbar: Res<ToolbarState>,
+    clipboard: Res<Clipboard>,
+    hit: Res<CursorHit>,
+    placement: Res<PlacementSettings>,
+    hologram_materials: Res<HologramMaterials>,
+    mut paste: ResMut<PasteState>,
+    children: Query<&Children>,
+    mut q_materials: Query<&mut MeshMaterial3d<StandardMaterial>>,
+    grid: Res<SpatialHashGrid>,
+    // Grouped to stay within the system parameter limit.
+    (biomes, budget): (Option<Res<BiomeConfig>>, Option<Res<PlacementBudget>>),
+) {
+    // Choosing a tool ends the paste.
+    if toolbar.active_tool.is_some() && paste.active {
+        paste.active = false;
+    }
+
+    let entry = clipboard.0.as_ref().filter(|_| paste.active);
+    let object_type = entry.and_then(|e| types.as_ref().and_then(|t| t.by_name(&e.type_name)));
+
+    let paste = &mut *paste;
+    let preview = &mut paste.preview;
+    let (Some(types), Some(entry), Some(object_type), Some(hit_world)) =
+        (types, entry, object_type, hit.world)
+    else {
+        if let Some(e) = preview.entity.take() {
+            highlight::despawn_recursive(&mut commands, &children, e);
+        }
+        preview.scene_child = None;
+        preview.object_type = None;
+        return;
+    };
+
+    // A different type was copied while pasting; rebuild so the old scene doesn't linger.
+    if preview.object_type != Some(object_type) {
+        if let Some(e) = preview.entity.take() {
+            highlight::despawn_recursive(&mut commands, &children, e);
+        }
+        preview.scene_child = None;
+        preview.object_type = Some(object_type);
+    }
+
+    let Some(spec) = types.registry.get(object_type) else {
+        return;
+    };
+    if spec.gltf.trim().is_empty() {
+        return;
+    }
+
+    let mut check = PlacementCheck::new(
+        &terrain,
+        &types,
+        &grid,
+        &q_objects,
+        &placement,
+        budget.as_deref(),
+        biomes.as_deref(),
+    );
+    // Pasting doesn't level the ground.
+    check.flatten = false;
+    let xz = placement_xz(&placement, &terrain, hit_world);
+    let (pos_world, rot, _) = check.pose(spec, xz, entry.yaw, entry.scale, &[]);
+    let transform = Transform::from_translation(pos_world)
+        .with_rotation(rot)
+        .with_scale(spec.render_scale * entry.scale);
+
+    preview.blocked = check
+        .validate(object_type, xz, entry.yaw, entry.scale, &[])
+        .err()
+        .map(PlacementError::reason);
+    let can_place = preview.blocked.is_none();
+    let chosen_material = if can_place {
+        &hologram_materials.valid
+    } else {
+        &hologram_materials.blocked
+    };
+
+    let scene_handle = asset_server.load(GltfAssetLabel::Scene(0).from_asset(spec.gltf.clone()));
+    let (preview_entity, scene_child) = highlight::update_hologram(
+        &mut commands,
+        preview.entity,
+        preview.scene_child,
+        scene_handle,
+        transform,
+        spec.scene_offset_local,
+    );
+    preview.entity = Some(preview_entity);
+    preview.scene_child = Some(scene_child);
+
+    highlight::apply_hologram_material_recursive(
+        &children,
+        &mut q_materials,
+        preview_entity,
+        chosen_material,
+        0,
+    );
+}
+
+fn handle_paste_click(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    hit: Res<CursorHit>,
+    clipboard: Res<Clipboard>,
+    placement: Res<PlacementSettings>,
+    types: Option<Res<ObjectTypes>>,
+    q_objects: PlacedObjects,
+    terrain: Res<TerrainWorld>,
+    asset_server: Res<AssetServer>,
+    ui_capture: Res<UiInputCapture>,
+    grid: Res<SpatialHashGrid>,
+    instanced: Res<InstancedMeshes>,
+    mut paste: ResMut<PasteState>,
+    mut history: ResMut<EditHistory>,
+    // Grouped to stay within the system parameter limit.
+    (biomes, mut budget): (Option<Res<BiomeConfig>>, Option<ResMut<PlacementBudget>>),
+) {
+    if !paste.active || ui_capture.pointer {
+        return;
+    }
+
+    if mouse_buttons.just_pressed(MouseButton::Right) {
+        paste.active = false;
+        return;
+    }
+
+    if !mouse_buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (Some(types), Some(entry), Some(world)) = (types, clipboard.0.as_ref(), hit.world) else {
+        return;
+    };
+    let Some(object) = types.by_name(&entry.type_name) else {
+        warn!("paste: object type '{}' no longer exists", entry.type_name);
+        paste.active = false;
+        return;
+    };
+
+    let xz = placement_xz(&placement, &terrain, world);
+    let mut check = PlacementCheck::new(
+        &terrain,
+        &types,
+        &grid,
+        &q_objects,
+        &placement,
+        budget.as_deref(),
+        biomes.as_deref(),
+    );
+    check.flatten = false;
+    let Ok((position, rotation)) = check.validate(object, xz, entry.yaw, entry.scale, &[]) else {
+        return;
+    };
+
+    if let Some(spawned) = objects::system::spawn_object(
+        &mut commands,
+        &types.registry,
+        &asset_server,
+        &instanced,
+        object,
+        position,
+        rotation,
+        entry.scale,
+    ) {
+        if let Some(budget) = budget.as_mut() {
+            budget.charge(&mut commands, spawned, object);
+        }
+        history.push(vec![EditAction::Place {
+            type_id: object,
+            position,
+            rotation,
+            scale: entry.scale,
+            stacked: false,
+        }]);
+    }
+
+    // Holding Shift keeps pasting copies.
+    if !(keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight)) {
+        paste.active = false;
+    }
+}
@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use glam::IVec2;
+
+use terrain::TerrainWorld;
+use ui::{InputAction, KeyBindings, UiInputCapture};
+
+use crate::game::camera::Viewer;
+
+/// Draw topographic contour lines around the camera focus.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct ShowContours(pub bool);
+
+#[derive(Resource, Clone, Debug)]
+pub struct ContourSettings {
+    /// Height between neighbouring contour lines.
+    pub interval: f32,
+    /// Every this many lines is drawn brighter, like index contours on a map. 0 disables.
+    pub major_every: i32,
+    /// World-space radius around the camera focus to draw lines in.
+    pub radius: f32,
+    /// Cap on tiles walked per axis; the radius is shrunk to fit so gizmo cost stays bounded.
+    pub max_tiles_per_axis: u32,
+}
+
+impl Default for ContourSettings {
+    fn default() -> Self {
+        Self {
+            interval: 2.0,
+            major_every: 5,
+            radius: 64.0,
+            max_tiles_per_axis: 96,
+        }
+    }
+}
+
+pub struct ContourPlugin;
+
+impl Plugin for ContourPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowContours>()
+            .init_resource::<ContourSettings>()
+            .add_systems(Update, (toggle_contours, draw_contours));
+    }
+}
+
+fn toggle_contours(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ui_capture: Res<UiInputCapture>,
+    mut show: ResMut<ShowContours>,
+) {
+    if ui_capture.keyboard {
+        return;
+    }
+    if bindings.just_pressed(&keys, InputAction::ToggleContours) {
+        show.0 = !show.0;
+    }
+}
+
+fn draw_contours(
+    mut gizmos: Gizmos,
+    show: Res<ShowContours>,
+    settings: Res<ContourSettings>,
+    terrain: Res<TerrainWorld>,
+    q_viewer: Query<&Transform, With<Viewer>>,
+) {
+    if !show.0 || settings.interval <= 0.0 {
+        return;
+    }
+    let Ok(viewer) = q_viewer.single() else {
+        return;
+    };
+
+    let ts = terrain.config.tile_size.max(0.001);
+    let radius = settings
+        .radius
+        .min(ts * settings.max_tiles_per_axis as f32 * 0.5);
+    if radius <= 0.0 {
+        return;
+    }
+
+    let focus = Vec2::new(viewer.translation.x, viewer.translation.z);
+    let lo = ((focus - Vec2::splat(radius)) / ts).floor().as_ivec2();
+    let hi = ((focus + Vec2::splat(radius)) / ts).ceil().as_ivec2();
+
+    // Sample each vertex once; every tile reads its four corners from here.
+    let w = (hi.x - lo.x + 1) as usize;
+    let mut heights = Vec::with_capacity(w * (hi.y - lo.y + 1) as usize);
+    for z in lo.y..=hi.y {
+        for x in lo.x..=hi.x {
+            heights.push(terrain.vertex_height(IVec2::new(x, z)));
+        }
+    }
+    let height = |v: IVec2| heights[(v.y - lo.y) as usize * w + (v.x - lo.x) as usize];
+
+    let lift = 0.05;
+    for z in lo.y..hi.y {
+        for x in lo.x..hi.x {
+            // Corners in order around the tile, so consecutive pairs are its edges.
+            let corners = [
+                IVec2::new(x, z),
+                IVec2::new(x + 1, z),
+                IVec2::new(x + 1, z + 1),
+                IVec2::new(x, z + 1),
+            ]
+            .map(|v| Vec3::new(v.x as f32 * ts, height(v), v.y as f32 * ts));
+
+            let center = Vec2::new(corners[0].x + corners[2].x, corners[0].z + corners[2].z) * 0.5;
+            let fade = 1.0 - center.distance(focus) / radius;
+            if fade <= 0.0 {
+                continue;
+            }
+
+            let (min_h, max_h) = corners
+                .iter()
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), c| {
+                    (lo.min(c.y), hi.max(c.y))
+                });
+            let first = (min_h / settings.interval).ceil() as i32;
+            let last = (max_h / settings.interval).floor() as i32;
+
+            for level in first..=last {
+                let c = level as f32 * settings.interval;
+
+                // Marching squares: where the level crosses each edge. Two crossings make one
+                // segment; a saddle gives four, paired up in edge order.
+                let mut crossings: Vec<Vec3> = Vec::with_capacity(4);
+                for i in 0..4 {
+                    let (a, b) = (corners[i], corners[(i + 1) % 4]);
+                    if (a.y < c) != (b.y < c) {
+                        let t = (c - a.y) / (b.y - a.y);
+                        crossings.push(a.lerp(b, t) + Vec3::Y * lift);
+                    }
+                }
+
+                let major = settings.major_every > 0 && level % settings.major_every == 0;
+                let color = if major {
+                    Color::srgba(0.45, 0.25, 0.1, 0.9 * fade)
+                } else {
+                    Color::srgba(0.45, 0.25, 0.1, 0.5 * fade)
+                };
+                for pair in crossings.chunks_exact(2) {
+                    gizmos.line(pair[0], pair[1], color);
+                }
+            }
+        }
+    }
+}
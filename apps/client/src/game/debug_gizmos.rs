@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use glam::IVec2;
+
+use objects::spatial::SpatialHashGrid;
+use objects::system::{ObjectKind, ObjectTypes};
+use terrain::TerrainWorld;
+use terrain::conform::{conform_circle, conform_segment};
+use ui::{InputAction, KeyBindings, UiInputCapture};
+
+use crate::game::camera::Viewer;
+
+/// Overlays for seeing why a placement was rejected. Everything is off by default.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct DebugGizmos {
+    pub show_hover_radius: bool,
+    /// The radius `can_place_non_overlapping` tests against.
+    pub show_collision_radius: bool,
+    /// Spatial hash cell boundaries near the camera focus.
+    pub show_spatial_grid: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct DebugGizmosPanel {
+    pub open: bool,
+}
+
+/// Cells drawn either side of the focus cell when the spatial grid is shown.
+const GRID_CELLS_AROUND_FOCUS: i32 = 6;
+
+pub struct DebugGizmosPlugin;
+
+impl Plugin for DebugGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugGizmos>()
+            .init_resource::<DebugGizmosPanel>()
+            .add_systems(
+                Update,
+                (toggle_debug_panel, draw_object_radii, draw_spatial_grid),
+            )
+            .add_systems(EguiPrimaryContextPass, draw_debug_panel);
+    }
+}
+
+fn toggle_debug_panel(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ui_capture: Res<UiInputCapture>,
+    mut panel: ResMut<DebugGizmosPanel>,
+) {
+    if ui_capture.keyboard {
+        return;
+    }
+    if bindings.just_pressed(&keys, InputAction::ToggleDebugGizmos) {
+        panel.open = !panel.open;
+    }
+}
+
+fn draw_debug_panel(
+    mut contexts: EguiContexts,
+    mut panel: ResMut<DebugGizmosPanel>,
+    mut debug: ResMut<DebugGizmos>,
+) {
+    if !panel.open {
+        return;
+    }
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+
+    let mut edited = debug.clone();
+    egui::Window::new("Debug Gizmos")
+        .open(&mut panel.open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.checkbox(&mut edited.show_hover_radius, "Hover radius");
+            ui.checkbox(&mut edited.show_collision_radius, "Collision radius");
+            ui.checkbox(&mut edited.show_spatial_grid, "Spatial grid");
+        });
+    if edited.show_hover_radius != debug.show_hover_radius
+        || edited.show_collision_radius != debug.show_collision_radius
+        || edited.show_spatial_grid != debug.show_spatial_grid
+    {
+        *debug = edited;
+    }
+}
+
+/// A circle lying on the terrain, lifted slightly so it isn't hidden in the surface.
+fn ground_circle(
+    gizmos: &mut Gizmos,
+    terrain: &TerrainWorld,
+    center: Vec3,
+    radius: f32,
+    color: Color,
+) {
+    gizmos.linestrip(
+        conform_circle(terrain, center.xz(), radius, 32, 0.08),
+        color,
+    );
+}
+
+fn draw_object_radii(
+    mut gizmos: Gizmos,
+    debug: Res<DebugGizmos>,
+    types: Option<Res<ObjectTypes>>,
+    terrain: Res<TerrainWorld>,
+    q_objects: Query<(&Transform, &ObjectKind)>,
+) {
+    if !debug.show_hover_radius && !debug.show_collision_radius {
+        return;
+    }
+    let Some(types) = types else {
+        return;
+    };
+
+    for (transform, kind) in &q_objects {
+        let Some(spec) = types.registry.get(kind.0) else {
+            continue;
+        };
+        if debug.show_hover_radius {
+            ground_circle(
+                &mut gizmos,
+                &terrain,
+                transform.translation,
+                spec.hover_radius,
+                Color::srgb(0.3, 0.8, 1.0),
+            );
+        }
+        if debug.show_collision_radius {
+            ground_circle(
+                &mut gizmos,
+                &terrain,
+                transform.translation,
+                spec.effective_collision_radius(),
+                Color::srgb(1.0, 0.5, 0.1),
+            );
+        }
+    }
+}
+
+fn draw_spatial_grid(
+    mut gizmos: Gizmos,
+    debug: Res<DebugGizmos>,
+    grid: Res<SpatialHashGrid>,
+    terrain: Res<TerrainWorld>,
+    q_viewer: Query<&Transform, With<Viewer>>,
+) {
+    if !debug.show_spatial_grid {
+        return;
+    }
+    let Ok(viewer) = q_viewer.single() else {
+        return;
+    };
+
+    let cs = grid.cell_size();
+    let focus = grid.cell_of_world(Vec2::new(viewer.translation.x, viewer.translation.z));
+    let lo = focus - IVec2::splat(GRID_CELLS_AROUND_FOCUS);
+    let hi = focus + IVec2::splat(GRID_CELLS_AROUND_FOCUS + 1);
+    let color = Color::srgba(1.0, 0.2, 0.8, 0.6);
+
+    // Cell edges follow the ground, a vertex per tile, so they don't vanish into hills.
+    let step = terrain.config.tile_size;
+    let (min, max) = (lo.as_vec2() * cs, hi.as_vec2() * cs);
+
+    for x in lo.x..=hi.x {
+        let wx = x as f32 * cs;
+        gizmos.linestrip(
+            conform_segment(
+                &terrain,
+                Vec2::new(wx, min.y),
+                Vec2::new(wx, max.y),
+                step,
+                0.1,
+            ),
+            color,
+        );
+    }
+    for z in lo.y..=hi.y {
+        let wz = z as f32 * cs;
+        gizmos.linestrip(
+            conform_segment(
+                &terrain,
+                Vec2::new(min.x, wz),
+                Vec2::new(max.x, wz),
+                step,
+                0.1,
+            ),
+            color,
+        );
+    }
+}
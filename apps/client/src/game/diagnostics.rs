@@ -0,0 +1,105 @@
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use objects::ids::ObjectIds;
+use terrain::{LoadedChunkEntities, TerrainConfig, TerrainWorld};
+use ui::{InputAction, KeyBindings, UiInputCapture};
+
+/// Show the performance overlay: frame time, streaming queues and object counts.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct ShowDiagnostics(pub bool);
+
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+        app.init_resource::<ShowDiagnostics>()
+            .add_systems(Update, toggle_diagnostics)
+            .add_systems(EguiPrimaryContextPass, draw_diagnostics);
+    }
+}
+
+fn toggle_diagnostics(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ui_capture: Res<UiInputCapture>,
+    mut show: ResMut<ShowDiagnostics>,
+) {
+    if ui_capture.keyboard {
+        return;
+    }
+    if bindings.just_pressed(&keys, InputAction::ToggleDiagnostics) {
+        show.0 = !show.0;
+    }
+}
+
+fn draw_diagnostics(
+    mut contexts: EguiContexts,
+    show: Res<ShowDiagnostics>,
+    diagnostics: Res<DiagnosticsStore>,
+    terrain: Res<TerrainWorld>,
+    config: Res<TerrainConfig>,
+    loaded: Option<Res<LoadedChunkEntities>>,
+    ids: Res<ObjectIds>,
+) {
+    if !show.0 {
+        return;
+    }
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|d| d.smoothed());
+    let frame_ms = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|d| d.smoothed());
+    let chunk_entities = loaded.map(|l| l.entities.len()).unwrap_or(0);
+
+    egui::Area::new("diagnostics_overlay".into())
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 10.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(egui::Color32::from_rgba_unmultiplied(20, 20, 20, 200))
+                .corner_radius(6)
+                .inner_margin(6)
+                .show(ui, |ui| {
+                    ui.label(match (fps, frame_ms) {
+                        (Some(fps), Some(ms)) => format!("{fps:.0} fps ({ms:.2} ms)"),
+                        _ => "fps: measuring...".to_string(),
+                    });
+                    ui.label(format!(
+                        "Chunks: {chunk_entities} loaded / {} wanted",
+                        terrain.desired_chunk_count()
+                    ));
+                    ui.label(format!(
+                        "Streaming: {} to spawn, {} to despawn, {} to remesh",
+                        terrain.pending_spawn_len(),
+                        terrain.pending_despawn_len(),
+                        terrain.dirty_chunk_count()
+                    ));
+                    let mode = if config.adaptive_spawn_budget.is_some() {
+                        "adaptive"
+                    } else {
+                        "fixed"
+                    };
+                    ui.label(format!(
+                        "Spawn budget: {}/frame ({mode})",
+                        terrain.spawn_budget()
+                    ));
+                    ui.label(format!("Objects: {}", ids.len()));
+                    ui.label(match &config.seed_text {
+                        Some(text) => format!("Seed: {} (\"{text}\")", config.seed),
+                        None => format!("Seed: {}", config.seed),
+                    });
+                });
+        });
+}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use glam::IVec2;
+
+use objects::system::CursorHit;
+use terrain::TerrainWorld;
+use ui::{InputAction, KeyBindings, UiInputCapture};
+
+/// Draw terrain-conforming tile grid lines around the cursor.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct ShowTileGrid(pub bool);
+
+#[derive(Resource, Clone, Debug)]
+pub struct TileGridSettings {
+    /// World-space radius around the cursor hit to draw lines in.
+    pub radius: f32,
+    /// Cap on grid lines per axis; the radius is shrunk to fit so gizmo cost stays bounded.
+    pub max_lines_per_axis: u32,
+}
+
+impl Default for TileGridSettings {
+    fn default() -> Self {
+        Self {
+            radius: 24.0,
+            max_lines_per_axis: 48,
+        }
+    }
+}
+
+pub struct TileGridPlugin;
+
+impl Plugin for TileGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowTileGrid>()
+            .init_resource::<TileGridSettings>()
+            .add_systems(Update, (toggle_tile_grid, draw_tile_grid));
+    }
+}
+
+fn toggle_tile_grid(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ui_capture: Res<UiInputCapture>,
+    mut show: ResMut<ShowTileGrid>,
+) {
+    if ui_capture.keyboard {
+        return;
+    }
+    if bindings.just_pressed(&keys, InputAction::ToggleTileGrid) {
+        show.0 = !show.0;
+    }
+}
+
+fn draw_tile_grid(
+    mut gizmos: Gizmos,
+    show: Res<ShowTileGrid>,
+    settings: Res<TileGridSettings>,
+    hit: Res<CursorHit>,
+    terrain: Res<TerrainWorld>,
+) {
+    if !show.0 {
+        return;
+    }
+    let Some(world) = hit.world else {
+        return;
+    };
+
+    let ts = terrain.config.tile_size.max(0.001);
+    let radius = settings
+        .radius
+        .min(ts * settings.max_lines_per_axis as f32 * 0.5);
+    if radius <= 0.0 {
+        return;
+    }
+
+    let focus = Vec2::new(world.x, world.z);
+    let lo = ((focus - Vec2::splat(radius)) / ts).floor().as_ivec2();
+    let hi = ((focus + Vec2::splat(radius)) / ts).ceil().as_ivec2();
+
+    // Lines run along tile edges, where the mesh is straight between vertices, so sampling
+    // just the vertex heights conforms exactly. Lifted slightly to avoid z-fighting.
+    let lift = 0.05;
+    let point = |v: IVec2| {
+        Vec3::new(
+            v.x as f32 * ts,
+            terrain.vertex_height(v) + lift,
+            v.y as f32 * ts,
+        )
+    };
+    let mut segment = |a: IVec2, b: IVec2| {
+        let pa = point(a);
+        let pb = point(b);
+        let mid = Vec2::new(pa.x + pb.x, pa.z + pb.z) * 0.5;
+        let fade = 1.0 - mid.distance(focus) / radius;
+        if fade <= 0.0 {
+            return;
+        }
+        gizmos.line(pa, pb, Color::srgba(1.0, 1.0, 1.0, 0.6 * fade));
+    };
+
+    for x in lo.x..=hi.x {
+        for z in lo.y..hi.y {
+            segment(IVec2::new(x, z), IVec2::new(x, z + 1));
+        }
+    }
+    for z in lo.y..=hi.y {
+        for x in lo.x..hi.x {
+            segment(IVec2::new(x, z), IVec2::new(x + 1, z));
+        }
+    }
+}
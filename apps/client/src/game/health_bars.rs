@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+use objects::system::{ObjectKind, ObjectState, ObjectTypes};
+
+use crate::game::camera::PrimaryCamera;
+
+/// Draws a bar over every damaged object; full-health objects show nothing.
+pub struct HealthBarPlugin;
+
+impl Plugin for HealthBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, draw_health_bars);
+    }
+}
+
+fn draw_health_bars(
+    mut gizmos: Gizmos,
+    types: Option<Res<ObjectTypes>>,
+    q_camera: Query<&Transform, With<PrimaryCamera>>,
+    q_objects: Query<(&Transform, &ObjectKind, &ObjectState), Without<PrimaryCamera>>,
+) {
+    let Some(types) = types else {
+        return;
+    };
+    let Ok(camera) = q_camera.single() else {
+        return;
+    };
+    // Bars face the camera sideways so they read the same from any orbit angle.
+    let right = camera.right().as_vec3();
+
+    for (transform, kind, state) in &q_objects {
+        if !state.is_damaged() {
+            continue;
+        }
+        let Some(spec) = types.registry.get(kind.0) else {
+            continue;
+        };
+
+        let half = spec.hover_radius.max(0.5);
+        let center = transform.translation + Vec3::Y * (spec.hover_radius * 2.0 + 0.5);
+        let start = center - right * half;
+        let end = center + right * half;
+        let split = start.lerp(end, state.fraction());
+
+        let health = Color::srgb(1.0, 0.2, 0.2).mix(&Color::srgb(0.2, 1.0, 0.2), state.fraction());
+        gizmos.line(start, split, health);
+        gizmos.line(split, end, Color::srgb(0.2, 0.2, 0.2));
+    }
+}
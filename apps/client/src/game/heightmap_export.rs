@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use bevy::tasks::IoTaskPool;
+use glam::UVec2;
+
+use terrain::{TerrainViewerWorldXz, TerrainWorld};
+use ui::{InputAction, KeyBindings, UiInputCapture};
+
+/// Writes the terrain around the viewer to a 16-bit grayscale PNG in the working directory.
+pub struct HeightmapExportPlugin;
+
+impl Plugin for HeightmapExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, export_heightmap_on_key);
+    }
+}
+
+fn export_heightmap_on_key(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ui_capture: Res<UiInputCapture>,
+    terrain: Res<TerrainWorld>,
+    viewer_xz: Res<TerrainViewerWorldXz>,
+) {
+    if ui_capture.keyboard || !bindings.just_pressed(&keys, InputAction::ExportHeightmap) {
+        return;
+    }
+
+    // The streamed-in square around the viewer, one pixel per tile.
+    let chunk_world_size = terrain.config.chunk_size as f32 * terrain.config.tile_size;
+    let r = terrain.config.view_distance_chunks;
+    let center = terrain.world_to_chunk_coord(viewer_xz.0);
+    let min_xz = (center - IVec2::splat(r)).as_vec2() * chunk_world_size;
+    let max_xz = (center + IVec2::splat(r + 1)).as_vec2() * chunk_world_size;
+    let tiles = ((2 * r + 1) * terrain.config.chunk_size).max(0) as u32;
+    let resolution = UVec2::splat(tiles);
+
+    let pixels = terrain.export_heightmap(min_xz, max_xz, resolution);
+    if pixels.is_empty() {
+        warn!("heightmap export: nothing to export");
+        return;
+    }
+    let Some(image) =
+        image::ImageBuffer::<image::Luma<u16>, _>::from_raw(resolution.x, resolution.y, pixels)
+    else {
+        return;
+    };
+
+    let (lo, hi) = terrain.height_range();
+    let path = format!(
+        "heightmap_{}_{}_{}.png",
+        terrain.config.seed, min_xz.x as i32, min_xz.y as i32
+    );
+    // Pixel 0 is height `lo` and 65535 is `hi`; log them so the file can be re-imported.
+    info!(
+        "exporting {}x{} heightmap of {min_xz}..{max_xz} to {path} (heights {lo}..{hi})",
+        resolution.x, resolution.y
+    );
+
+    // Encoding a large PNG can take a while; keep it off the main thread.
+    IoTaskPool::get()
+        .spawn(async move {
+            if let Err(e) = image.save_with_format(&path, image::ImageFormat::Png) {
+                error!("heightmap export to {path} failed: {e}");
+            }
+        })
+        .detach();
+}
@@ -0,0 +1,531 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use objects::ObjectTypeId;
+use objects::highlight;
+use objects::instancing::InstancedMeshes;
+use objects::spatial::SpatialHashGrid;
+use objects::system::{ObjectKind, ObjectScale, ObjectTypes, heading_of};
+use terrain::{BiomeConfig, HeightDeltaChange, TerrainWorld};
+use ui::UiInputCapture;
+
+use crate::game::modes::construction::{
+    MeshBounds, PlacedObjects, PlacementBudget, PlacementCheck, PlacementSettings, Stacked,
+};
+use crate::game::modes::edit::pose_in_place;
+
+/// Where an object stands and how big it is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ObjectPose {
+    pub position: Vec3,
+    pub rotation: Quat,
+    /// Its [`ObjectScale`].
+    pub scale: Vec3,
+}
+
+impl ObjectPose {
+    pub fn of(transform: &Transform, scale: &ObjectScale) -> Self {
+        Self {
+            position: transform.translation,
+            rotation: transform.rotation,
+            scale: scale.0,
+        }
+    }
+}
+
+/// A single reversible edit.
+///
+/// Entities are re-created on undo/redo, so actions record what the object was (type and
+/// placement) rather than its `Entity`, and find it again by its XZ position when it has to go.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditAction {
+    Place {
+        type_id: ObjectTypeId,
+        position: Vec3,
+        rotation: Quat,
+        scale: Vec3,
+        /// Sat on other objects; see [`Stacked`].
+        stacked: bool,
+    },
+    Remove {
+        type_id: ObjectTypeId,
+        position: Vec3,
+        rotation: Quat,
+        scale: Vec3,
+        stacked: bool,
+    },
+    /// An object turned or rescaled where it stands.
+    Adjust {
+        type_id: ObjectTypeId,
+        from: ObjectPose,
+        to: ObjectPose,
+    },
+    /// Height edits, e.g. the pad levelled under a placement.
+    Terrain(Vec<HeightDeltaChange>),
+}
+
+impl EditAction {
+    fn inverse(&self) -> Self {
+        match *self {
+            EditAction::Place {
+                type_id,
+                position,
+                rotation,
+                scale,
+                stacked,
+            } => EditAction::Remove {
+                type_id,
+                position,
+                rotation,
+                scale,
+                stacked,
+            },
+            EditAction::Remove {
+                type_id,
+                position,
+                rotation,
+                scale,
+                stacked,
+            } => EditAction::Place {
+                type_id,
+                position,
+                rotation,
+                scale,
+                stacked,
+            },
+            EditAction::Adjust { type_id, from, to } => EditAction::Adjust {
+                type_id,
+                from: to,
+                to: from,
+            },
+            EditAction::Terrain(ref changes) => EditAction::Terrain(
+                changes
+                    .iter()
+                    .map(|c| HeightDeltaChange {
+                        vertex: c.vertex,
+                        before: c.after,
+                        after: c.before,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The `EditAction::Remove` for an existing object.
+    pub fn remove(
+        kind: &ObjectKind,
+        transform: &Transform,
+        scale: &ObjectScale,
+        stacked: bool,
+    ) -> Self {
+        EditAction::Remove {
+            type_id: kind.0,
+            position: transform.translation,
+            rotation: transform.rotation,
+            scale: scale.0,
+            stacked,
+        }
+    }
+}
+
+/// Undo/redo stacks. Each entry is one user edit, which may touch several objects (e.g. a
+/// bulk delete) and is undone as a unit.
+#[derive(Resource)]
+pub struct EditHistory {
+    undo: VecDeque<Vec<EditAction>>,
+    redo: Vec<Vec<EditAction>>,
+    /// Oldest entries are dropped past this many.
+    pub max_len: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            max_len: 100,
+        }
+    }
+}
+
+impl EditHistory {
+    /// Records a new edit. This clears the redo stack.
+    pub fn push(&mut self, actions: Vec<EditAction>) {
+        if actions.is_empty() {
+            return;
+        }
+        self.push_undo(actions);
+        self.redo.clear();
+    }
+
+    /// Undoes the newest edit, last action first. `apply` carries out one action and returns
+    /// whether it could.
+    ///
+    /// Only the actions that were undone move to the redo stack. The rest (e.g. a removal whose
+    /// spot has been built on since) stay done and drop out of the history, so a redo never
+    /// repeats something that wasn't undone.
+    pub fn undo(&mut self, mut apply: impl FnMut(&EditAction) -> bool) {
+        let Some(entry) = self.undo.pop_back() else {
+            return;
+        };
+        let mut undone: Vec<EditAction> = entry
+            .into_iter()
+            .rev()
+            .filter(|action| apply(&action.inverse()))
+            .collect();
+        undone.reverse();
+        if !undone.is_empty() {
+            self.redo.push(undone);
+        }
+    }
+
+    /// Redoes the newest undone edit; the counterpart of [`undo`](Self::undo).
+    pub fn redo(&mut self, mut apply: impl FnMut(&EditAction) -> bool) {
+        let Some(entry) = self.redo.pop() else {
+            return;
+        };
+        let redone: Vec<EditAction> = entry.into_iter().filter(|action| apply(action)).collect();
+        if !redone.is_empty() {
+            self.push_undo(redone);
+        }
+    }
+
+    fn push_undo(&mut self, actions: Vec<EditAction>) {
+        self.undo.push_back(actions);
+        while self.undo.len() > self.max_len {
+            self.undo.pop_front();
+        }
+    }
+}
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditHistory>()
+            .add_systems(Update, handle_undo_redo);
+    }
+}
+
+fn handle_undo_redo(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    ui_capture: Res<UiInputCapture>,
+    types: Option<Res<ObjectTypes>>,
+    asset_server: Res<AssetServer>,
+    instanced: Res<InstancedMeshes>,
+    grid: Res<SpatialHashGrid>,
+    q_objects: PlacedObjects,
+    // Grouped to stay within the system parameter limit.
+    (children, q_bounds, q_stacked): (
+        Query<&'static Children>,
+        MeshBounds,
+        Query<(), With<Stacked>>,
+    ),
+    mut history: ResMut<EditHistory>,
+    mut budget: Option<ResMut<PlacementBudget>>,
+    mut terrain: ResMut<TerrainWorld>,
+    placement: Res<PlacementSettings>,
+    biomes: Option<Res<BiomeConfig>>,
+) {
+    if ui_capture.keyboard {
+        return;
+    }
+
+    let ctrl = keys.pressed(KeyCode::ControlLeft) || keys.pressed(KeyCode::ControlRight);
+    let shift = keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight);
+    if !ctrl {
+        return;
+    }
+
+    let redo = keys.just_pressed(KeyCode::KeyY) || (shift && keys.just_pressed(KeyCode::KeyZ));
+    let undo = !redo && keys.just_pressed(KeyCode::KeyZ);
+    if !undo && !redo {
+        return;
+    }
+
+    let Some(types) = types else {
+        return;
+    };
+
+    // Objects moved since the edit was recorded can't be matched; leave them be. Height isn't
+    // compared, since a re-placed object is posed on the ground as it is now.
+    let find = |type_id: ObjectTypeId, xz: Vec2| {
+        grid.query_candidates(xz, 0.01).into_iter().find(|e| {
+            q_objects.get(*e).is_ok_and(|(t, k, _)| {
+                k.0 == type_id && t.translation.xz().distance_squared(xz) < 1e-4
+            })
+        })
+    };
+    // Removals only take effect once commands apply, so later actions in the same edit (e.g.
+    // putting back what a replace removed) have to be told to ignore them.
+    let mut removed = Vec::new();
+
+    let mut apply = |action: &EditAction| match *action {
+        EditAction::Place {
+            type_id,
+            position,
+            rotation,
+            scale,
+            stacked,
+        } => {
+            // The spot may have been built on, flooded or reshaped since the edit was recorded,
+            // so it's checked and posed like a fresh placement. Re-placing also costs like
+            // placing did, so undoing a removal can't be used to get a second refund.
+            let Some(spec) = types.registry.get(type_id) else {
+                return false;
+            };
+            let mut check = PlacementCheck::new(
+                &terrain,
+                &types,
+                &grid,
+                &q_objects,
+                &placement,
+                budget.as_deref(),
+                biomes.as_deref(),
+            );
+            // Any levelling is its own `EditAction::Terrain`.
+            check.flatten = false;
+            if stacked {
+                check.stacking = Some((&children, &q_bounds));
+            }
+            let xz = position.xz();
+            let yaw = heading_of(rotation);
+            let (_, _, stacked) = check.pose(spec, xz, yaw, scale, &removed);
+            let (position, rotation) = match check.validate(type_id, xz, yaw, scale, &removed) {
+                Ok(pose) => pose,
+                Err(e) => {
+                    warn!(
+                        "undo/redo: can't place the object back at {position:?}: {}",
+                        e.reason()
+                    );
+                    return false;
+                }
+            };
+            let Some(spawned) = objects::system::spawn_object(
+                &mut commands,
+                &types.registry,
+                &asset_server,
+                &instanced,
+                type_id,
+                position,
+                rotation,
+                scale,
+            ) else {
+                return false;
+            };
+            if let Some(budget) = budget.as_mut() {
+                budget.charge(&mut commands, spawned, type_id);
+            }
+            if stacked {
+                commands.entity(spawned).insert(Stacked);
+            }
+            true
+        }
+        EditAction::Remove {
+            type_id, position, ..
+        } => match find(type_id, position.xz()) {
+            Some(entity) => {
+                highlight::despawn_recursive(&mut commands, &children, entity);
+                removed.push(entity);
+                true
+            }
+            None => {
+                debug!("undo/redo: no object left at {position:?} to remove");
+                false
+            }
+        },
+        EditAction::Adjust { type_id, from, to } => {
+            let (Some(entity), Some(spec)) = (
+                find(type_id, from.position.xz()),
+                types.registry.get(type_id),
+            ) else {
+                debug!("undo/redo: no object left at {:?} to adjust", from.position);
+                return false;
+            };
+            // Posed and checked like the edit tool does it.
+            let stacked = q_stacked.contains(entity);
+            let (position, rotation) = pose_in_place(
+                spec,
+                &terrain,
+                &Transform::from_translation(to.position),
+                heading_of(to.rotation),
+                to.scale,
+                stacked,
+            );
+            if !stacked
+                && !objects::system::can_place_non_overlapping_spatial(
+                    &types.registry,
+                    type_id,
+                    position,
+                    to.scale,
+                    &grid,
+                    &q_objects,
+                    &[entity],
+                )
+            {
+                warn!("undo/redo: can't resize the object at {position:?}: it would overlap");
+                return false;
+            }
+            commands.entity(entity).insert((
+                Transform::from_translation(position)
+                    .with_rotation(rotation)
+                    .with_scale(spec.render_scale * to.scale),
+                ObjectScale(to.scale),
+            ));
+            true
+        }
+        EditAction::Terrain(ref changes) => {
+            terrain.set_height_deltas(changes.iter().map(|c| (c.vertex, c.after)));
+            true
+        }
+    };
+
+    if undo {
+        history.undo(&mut apply);
+    } else {
+        history.redo(&mut apply);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use terrain::TerrainConfig;
+
+    fn place(x: f32) -> EditAction {
+        EditAction::Place {
+            type_id: ObjectTypeId(0),
+            position: Vec3::new(x, 0.0, 0.0),
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+            stacked: false,
+        }
+    }
+
+    #[test]
+    fn undo_keeps_only_what_went_through_for_redo() {
+        let mut history = EditHistory::default();
+        history.push(vec![place(0.0), place(5.0), place(10.0)]);
+
+        // The removal of the middle object fails, e.g. it was moved since.
+        let mut seen = Vec::new();
+        history.undo(|action| {
+            seen.push(action.clone());
+            *action != place(5.0).inverse()
+        });
+        assert_eq!(
+            seen,
+            vec![
+                place(10.0).inverse(),
+                place(5.0).inverse(),
+                place(0.0).inverse()
+            ]
+        );
+        assert!(history.undo.is_empty());
+
+        let mut redone = Vec::new();
+        history.redo(|action| {
+            redone.push(action.clone());
+            true
+        });
+        assert_eq!(redone, vec![place(0.0), place(10.0)]);
+        assert_eq!(history.undo.back(), Some(&vec![place(0.0), place(10.0)]));
+        assert!(history.redo.is_empty());
+    }
+
+    #[test]
+    fn an_edit_that_cant_be_undone_leaves_the_history() {
+        let mut history = EditHistory::default();
+        history.push(vec![place(0.0)]);
+        history.push(vec![place(5.0)]);
+
+        history.undo(|_| false);
+        assert!(history.redo.is_empty());
+        assert_eq!(history.undo.len(), 1);
+
+        // Redoing with nothing undone doesn't bring it back either.
+        history.redo(|_| true);
+        assert_eq!(history.undo.len(), 1);
+    }
+
+    #[test]
+    fn new_edits_clear_redo_and_old_ones_fall_off() {
+        let mut history = EditHistory {
+            max_len: 2,
+            ..default()
+        };
+        history.push(vec![place(0.0)]);
+        history.push(vec![place(5.0)]);
+        history.undo(|_| true);
+        assert_eq!(history.redo.len(), 1);
+
+        history.push(vec![place(10.0)]);
+        history.push(vec![place(15.0)]);
+        assert!(history.redo.is_empty());
+        assert_eq!(
+            history.undo,
+            VecDeque::from([vec![place(10.0)], vec![place(15.0)]])
+        );
+    }
+
+    #[test]
+    fn adjust_undoes_to_where_it_started() {
+        let from = ObjectPose {
+            position: Vec3::new(1.0, 2.0, 3.0),
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        };
+        let to = ObjectPose {
+            rotation: Quat::from_rotation_y(1.0),
+            scale: Vec3::splat(2.0),
+            ..from
+        };
+        let adjust = EditAction::Adjust {
+            type_id: ObjectTypeId(0),
+            from,
+            to,
+        };
+        assert_eq!(
+            adjust.inverse(),
+            EditAction::Adjust {
+                type_id: ObjectTypeId(0),
+                from: to,
+                to: from,
+            }
+        );
+        assert_eq!(adjust.inverse().inverse(), adjust);
+    }
+
+    #[test]
+    fn undoing_a_levelled_placement_restores_the_ground() {
+        let mut terrain = TerrainWorld::new(TerrainConfig {
+            chunk_size: 8,
+            ..default()
+        });
+        let heights = |terrain: &TerrainWorld| -> Vec<f32> {
+            (-4..=4)
+                .map(|x| terrain.sample_height_at(x as f32, 0.5))
+                .collect()
+        };
+        let ground = heights(&terrain);
+
+        // What a placement with `flatten_under_footprint` records.
+        let changes = terrain.flatten_pad(Vec2::ZERO, 2.0, 1.0, ground[4] + 3.0);
+        let levelled = heights(&terrain);
+        assert_ne!(levelled, ground);
+        let mut history = EditHistory::default();
+        history.push(vec![EditAction::Terrain(changes), place(0.0)]);
+
+        let apply = |terrain: &mut TerrainWorld, action: &EditAction| {
+            if let EditAction::Terrain(changes) = action {
+                terrain.set_height_deltas(changes.iter().map(|c| (c.vertex, c.after)));
+            }
+            true
+        };
+        history.undo(|a| apply(&mut terrain, a));
+        assert_eq!(heights(&terrain), ground);
+        history.redo(|a| apply(&mut terrain, a));
+        assert_eq!(heights(&terrain), levelled);
+    }
+}
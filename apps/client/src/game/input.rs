@@ -2,10 +2,11 @@ use bevy::prelude::*;
 use glam::{Vec2, Vec3 as GVec3};
 
 use objects::system::CursorHit;
-use terrain::{TerrainViewerWorldXz, TerrainWorld};
-use ui::UiInputCapture;
+use terrain::{TerrainViewerFacing, TerrainViewerWorldXz, TerrainWorld};
+use ui::{KeyBindingsPanel, UiInputCapture};
 
-use crate::game::camera::TopDownCamera;
+use crate::game::camera::PrimaryCamera;
+use crate::game::camera::TopDownCameraSettings;
 use crate::game::camera::Viewer;
 use bevy_egui::EguiContexts;
 
@@ -15,17 +16,24 @@ pub struct InputPlugin;
 
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update_ui_input_capture.in_set(UpdateSet::UiCapture))
-            .add_systems(Update, update_cursor_hit.in_set(UpdateSet::CursorHit))
-            .add_systems(
-                Update,
-                update_terrain_viewer_world_xz.in_set(UpdateSet::TerrainViewer),
-            );
+        app.add_systems(
+            Update,
+            update_ui_input_capture
+                .in_set(UpdateSet::UiCapture)
+                .before(ui::capture_rebind_key),
+        )
+        .add_systems(Update, update_cursor_hit.in_set(UpdateSet::CursorHit))
+        .add_systems(
+            Update,
+            (update_terrain_viewer_world_xz, update_terrain_viewer_facing)
+                .in_set(UpdateSet::TerrainViewer),
+        );
     }
 }
 
 pub(crate) fn update_ui_input_capture(
     mut contexts: EguiContexts,
+    key_panel: Res<KeyBindingsPanel>,
     mut capture: ResMut<UiInputCapture>,
 ) {
     let ctx = match contexts.ctx_mut() {
@@ -42,13 +50,14 @@ pub(crate) fn update_ui_input_capture(
     capture.pointer = ctx.wants_pointer_input() || ctx.is_pointer_over_area();
 
     // Keyboard capture: egui wants keyboard (this is usually true when a text field is active).
-    capture.keyboard = ctx.wants_keyboard_input();
+    // Also while the key bindings panel waits for a key, so the press doesn't reach the game.
+    capture.keyboard = ctx.wants_keyboard_input() || key_panel.listening.is_some();
 }
 
 /// Update the current mouse cursor hit point against the procedural terrain heightfield.
 pub(crate) fn update_cursor_hit(
     windows: Query<&Window>,
-    camera_q: Query<(&Camera, &GlobalTransform), With<TopDownCamera>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
     terrain: Res<TerrainWorld>,
     mut hit: ResMut<CursorHit>,
     ui_capture: Res<UiInputCapture>,
@@ -105,3 +114,11 @@ pub(crate) fn update_terrain_viewer_world_xz(
     };
     viewer_xz.0 = Vec2::new(t.translation.x, t.translation.z);
 }
+
+pub(crate) fn update_terrain_viewer_facing(
+    settings: Res<TopDownCameraSettings>,
+    mut facing: ResMut<TerrainViewerFacing>,
+) {
+    // The camera orbits the focus and looks at it, along yaw on the XZ plane.
+    facing.0 = Vec2::new(settings.yaw.sin(), settings.yaw.cos());
+}
@@ -1,4 +1,6 @@
+use bevy::light::{CascadeShadowConfigBuilder, DirectionalLightShadowMap};
 use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
 
 use super::StartupSet;
 
@@ -6,12 +8,95 @@ pub struct LightingPlugin;
 
 impl Plugin for LightingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_sun_light.in_set(StartupSet::Lighting));
+        app.init_resource::<ShadowSettings>()
+            .add_systems(Startup, setup_sun_light.in_set(StartupSet::Lighting))
+            .add_systems(
+                Update,
+                (
+                    advance_time_of_day,
+                    apply_time_of_day,
+                    apply_shadow_settings,
+                )
+                    .chain(),
+            )
+            .add_systems(EguiPrimaryContextPass, draw_lighting_ui);
+    }
+}
+
+/// Marks the directional light that [`TimeOfDay`] drives.
+#[derive(Component)]
+pub struct Sun;
+
+/// Clock for the day/night cycle. Lighting stays as set up at startup unless this is inserted.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct TimeOfDay {
+    /// Hour of the day, `0.0..24.0`. The sun rises at 6 and sets at 18.
+    pub hours: f32,
+    /// In-game hours per real second; 0 holds the time.
+    pub speed: f32,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self {
+            hours: 12.0,
+            speed: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShadowQuality {
+    #[default]
+    Off,
+    Low,
+    High,
+}
+
+impl ShadowQuality {
+    fn cascades(self) -> usize {
+        match self {
+            ShadowQuality::Off => 0,
+            ShadowQuality::Low => 2,
+            ShadowQuality::High => 4,
+        }
+    }
+
+    fn map_size(self) -> usize {
+        match self {
+            ShadowQuality::Off | ShadowQuality::Low => 2048,
+            ShadowQuality::High => 4096,
+        }
+    }
+}
+
+/// Sun shadows. Off by default since they cost a lot over the streamed view distance; enable
+/// with `app.insert_resource(ShadowSettings { quality: ShadowQuality::High, ..default() })`.
+///
+/// Cascades are fitted to the camera frustum rather than the scene bounds, so chunks streaming
+/// in and out don't move them around.
+#[derive(Resource, Clone, Debug)]
+pub struct ShadowSettings {
+    pub quality: ShadowQuality,
+    /// Far edge of the nearest (sharpest) cascade.
+    pub first_cascade_far_bound: f32,
+    /// Shadows fade out past this distance from the camera.
+    pub max_distance: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            quality: ShadowQuality::Off,
+            first_cascade_far_bound: 30.0,
+            max_distance: 250.0,
+        }
     }
 }
 
 pub fn setup_sun_light(mut commands: Commands) {
     commands.spawn((
+        Sun,
         DirectionalLight {
             illuminance: 20_000.0,
             shadows_enabled: false,
@@ -20,3 +105,222 @@ pub fn setup_sun_light(mut commands: Commands) {
         Transform::from_rotation(Quat::from_euler(EulerRot::XYZ, -0.8, 0.7, 0.0)),
     ));
 }
+
+/// Lighting at one hour; the day is interpolated between these.
+struct LightKey {
+    hour: f32,
+    sun: Vec3,
+    illuminance: f32,
+    sky: Vec3,
+    ambient: f32,
+}
+
+/// Noon matches the static startup lighting. Night keeps a dim bluish "moon" so the map stays
+/// readable.
+const LIGHT_KEYS: [LightKey; 8] = [
+    LightKey {
+        hour: 0.0,
+        sun: Vec3::new(0.55, 0.65, 1.0),
+        illuminance: 800.0,
+        sky: Vec3::new(0.02, 0.03, 0.08),
+        ambient: 8.0,
+    },
+    LightKey {
+        hour: 5.0,
+        sun: Vec3::new(0.55, 0.65, 1.0),
+        illuminance: 1_000.0,
+        sky: Vec3::new(0.15, 0.15, 0.30),
+        ambient: 10.0,
+    },
+    LightKey {
+        hour: 6.5,
+        sun: Vec3::new(1.0, 0.6, 0.4),
+        illuminance: 6_000.0,
+        sky: Vec3::new(0.90, 0.60, 0.50),
+        ambient: 18.0,
+    },
+    LightKey {
+        hour: 9.0,
+        sun: Vec3::new(1.0, 0.93, 0.85),
+        illuminance: 16_000.0,
+        sky: Vec3::new(0.55, 0.75, 0.92),
+        ambient: 26.0,
+    },
+    LightKey {
+        hour: 12.0,
+        sun: Vec3::new(1.0, 1.0, 1.0),
+        illuminance: 20_000.0,
+        sky: Vec3::new(0.60, 0.80, 0.95),
+        ambient: 30.0,
+    },
+    LightKey {
+        hour: 15.0,
+        sun: Vec3::new(1.0, 0.93, 0.85),
+        illuminance: 16_000.0,
+        sky: Vec3::new(0.55, 0.75, 0.92),
+        ambient: 26.0,
+    },
+    LightKey {
+        hour: 17.5,
+        sun: Vec3::new(1.0, 0.5, 0.3),
+        illuminance: 6_000.0,
+        sky: Vec3::new(0.95, 0.50, 0.35),
+        ambient: 18.0,
+    },
+    LightKey {
+        hour: 19.0,
+        sun: Vec3::new(0.55, 0.65, 1.0),
+        illuminance: 1_000.0,
+        sky: Vec3::new(0.15, 0.12, 0.30),
+        ambient: 10.0,
+    },
+];
+
+/// The keys either side of `hours` and how far between them it is, wrapping past midnight.
+fn light_keys_around(hours: f32) -> (&'static LightKey, &'static LightKey, f32) {
+    let next = LIGHT_KEYS.iter().position(|k| k.hour > hours).unwrap_or(0);
+    let prev = (next + LIGHT_KEYS.len() - 1) % LIGHT_KEYS.len();
+    let (a, b) = (&LIGHT_KEYS[prev], &LIGHT_KEYS[next]);
+    let span = (b.hour - a.hour).rem_euclid(24.0);
+    let t = if span > 0.0 {
+        (hours - a.hour).rem_euclid(24.0) / span
+    } else {
+        0.0
+    };
+    (a, b, t)
+}
+
+/// Direction the light shines in. The sun crosses from east to west between 6 and 18; at
+/// night the opposite point of its circle stands in as the moon.
+fn light_direction(hours: f32) -> Vec3 {
+    let max_elevation = 60f32.to_radians();
+    let angle = (hours - 6.0) / 12.0 * std::f32::consts::PI;
+    let elevation = angle.sin() * max_elevation;
+    let azimuth = angle;
+    let to_sun = Vec3::new(
+        elevation.cos() * azimuth.cos(),
+        elevation.sin(),
+        elevation.cos() * azimuth.sin() * 0.5,
+    );
+    // Keep a light source above the horizon at all times, and never quite grazing it.
+    let to_light = if to_sun.y >= 0.0 { to_sun } else { -to_sun };
+    Vec3::new(to_light.x, to_light.y.max(0.15), to_light.z).normalize()
+}
+
+fn advance_time_of_day(time: Res<Time>, time_of_day: Option<ResMut<TimeOfDay>>) {
+    let Some(mut time_of_day) = time_of_day else {
+        return;
+    };
+    if time_of_day.speed != 0.0 {
+        time_of_day.hours =
+            (time_of_day.hours + time_of_day.speed * time.delta_secs()).rem_euclid(24.0);
+    }
+}
+
+fn apply_time_of_day(
+    time_of_day: Option<Res<TimeOfDay>>,
+    mut clear_color: ResMut<ClearColor>,
+    mut ambient: ResMut<AmbientLight>,
+    mut q_sun: Query<(&mut DirectionalLight, &mut Transform), With<Sun>>,
+) {
+    let Some(time_of_day) = time_of_day else {
+        return;
+    };
+    if !time_of_day.is_changed() {
+        return;
+    }
+
+    let hours = time_of_day.hours.rem_euclid(24.0);
+    let (a, b, t) = light_keys_around(hours);
+    let sun = a.sun.lerp(b.sun, t);
+    let sky = a.sky.lerp(b.sky, t);
+
+    for (mut light, mut transform) in &mut q_sun {
+        light.illuminance = a.illuminance + (b.illuminance - a.illuminance) * t;
+        light.color = Color::srgb(sun.x, sun.y, sun.z);
+        *transform = Transform::default().looking_to(-light_direction(hours), Vec3::Y);
+    }
+    clear_color.0 = Color::srgb(sky.x, sky.y, sky.z);
+    ambient.color = Color::srgb(sky.x, sky.y, sky.z).mix(&Color::WHITE, 0.5);
+    ambient.brightness = a.ambient + (b.ambient - a.ambient) * t;
+}
+
+fn apply_shadow_settings(
+    mut commands: Commands,
+    settings: Res<ShadowSettings>,
+    mut shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut q_sun: Query<(Entity, &mut DirectionalLight), With<Sun>>,
+    q_added: Query<(), Added<Sun>>,
+) {
+    // The sun spawns in Startup after the resource exists, so catch it on its first frame too.
+    if !settings.is_changed() && q_added.is_empty() {
+        return;
+    }
+
+    let enabled = settings.quality != ShadowQuality::Off;
+    if enabled && shadow_map.size != settings.quality.map_size() {
+        shadow_map.size = settings.quality.map_size();
+    }
+    for (entity, mut light) in &mut q_sun {
+        if light.shadows_enabled != enabled {
+            light.shadows_enabled = enabled;
+        }
+        if !enabled {
+            continue;
+        }
+        let cascades = CascadeShadowConfigBuilder {
+            num_cascades: settings.quality.cascades(),
+            first_cascade_far_bound: settings.first_cascade_far_bound,
+            maximum_distance: settings.max_distance.max(settings.first_cascade_far_bound),
+            ..default()
+        }
+        .build();
+        commands.entity(entity).insert(cascades);
+    }
+}
+
+fn draw_lighting_ui(
+    mut contexts: EguiContexts,
+    mut shadows: ResMut<ShadowSettings>,
+    time_of_day: Option<ResMut<TimeOfDay>>,
+) {
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+
+    // Edit copies so the resources only read as changed when a control actually moves.
+    let mut quality = shadows.quality;
+    let mut edited = time_of_day.as_deref().copied();
+    egui::Window::new("Lighting")
+        .default_open(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(10.0, 10.0))
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Shadows");
+                ui.selectable_value(&mut quality, ShadowQuality::Off, "Off");
+                ui.selectable_value(&mut quality, ShadowQuality::Low, "Low");
+                ui.selectable_value(&mut quality, ShadowQuality::High, "High");
+            });
+            if let Some(edited) = edited.as_mut() {
+                ui.separator();
+                let h = edited.hours as u32;
+                let m = ((edited.hours - h as f32) * 60.0) as u32;
+                ui.add(
+                    egui::Slider::new(&mut edited.hours, 0.0..=23.99)
+                        .text(format!("{h:02}:{m:02}"))
+                        .show_value(false),
+                );
+                ui.add(egui::Slider::new(&mut edited.speed, 0.0..=2.0).text("Hours per second"));
+            }
+        });
+    if quality != shadows.quality {
+        shadows.quality = quality;
+    }
+    if let (Some(mut time_of_day), Some(edited)) = (time_of_day, edited)
+        && (edited.hours != time_of_day.hours || edited.speed != time_of_day.speed)
+    {
+        *time_of_day = edited;
+    }
+}
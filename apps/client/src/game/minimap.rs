@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use glam::IVec2;
+use std::collections::HashMap;
+
+use objects::system::ObjectKind;
+use terrain::{BiomeConfig, LoadedChunkEntities, TerrainWorld};
+use ui::{InputAction, KeyBindings, UiInputCapture};
+
+use crate::game::camera::{CameraTween, TopDownCameraSettings, Viewer};
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct ShowMinimap(pub bool);
+
+impl Default for ShowMinimap {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Average tile colour per loaded chunk, keyed by the chunk entity it was computed for.
+///
+/// Chunks get a new entity whenever they're (re)spawned, including after terrain edits, so a
+/// mismatched entity is what triggers a recompute.
+#[derive(Resource, Default)]
+struct MinimapChunkColors {
+    colors: HashMap<IVec2, (Entity, egui::Color32)>,
+}
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowMinimap>()
+            .init_resource::<MinimapChunkColors>()
+            .add_systems(Update, (toggle_minimap, update_minimap_chunk_colors))
+            .add_systems(EguiPrimaryContextPass, draw_minimap);
+    }
+}
+
+fn toggle_minimap(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ui_capture: Res<UiInputCapture>,
+    mut show: ResMut<ShowMinimap>,
+) {
+    if ui_capture.keyboard {
+        return;
+    }
+    if bindings.just_pressed(&keys, InputAction::ToggleMinimap) {
+        show.0 = !show.0;
+    }
+}
+
+fn update_minimap_chunk_colors(
+    terrain: Res<TerrainWorld>,
+    biomes: Option<Res<BiomeConfig>>,
+    loaded: Option<Res<LoadedChunkEntities>>,
+    mut cache: ResMut<MinimapChunkColors>,
+) {
+    let (Some(biomes), Some(loaded)) = (biomes, loaded) else {
+        return;
+    };
+    if !loaded.is_changed() {
+        return;
+    }
+
+    cache
+        .colors
+        .retain(|coord, _| loaded.entities.contains_key(coord));
+
+    let n = terrain.config.chunk_size.max(1);
+    // A coarse sample is plenty for one colour per chunk.
+    let step = (n / 8).max(1);
+
+    for (&coord, &entity) in &loaded.entities {
+        if cache.colors.get(&coord).is_some_and(|(e, _)| *e == entity) {
+            continue;
+        }
+
+        let base = coord * n;
+        let mut sum = Vec3::ZERO;
+        let mut count = 0.0;
+        for z in (0..n).step_by(step as usize) {
+            for x in (0..n).step_by(step as usize) {
+                let vertex = base + IVec2::new(x, z);
+                let h = terrain.vertex_height(vertex);
+                let world = vertex.as_vec2() * terrain.config.tile_size;
+                let field = terrain.biome_field(world.x, world.y, &biomes.field);
+                let (r, g, b) = biomes.color_srgb(h, field);
+                sum += Vec3::new(r, g, b);
+                count += 1.0;
+            }
+        }
+        let avg = sum / count;
+        let color = egui::Color32::from_rgb(
+            (avg.x * 255.0) as u8,
+            (avg.y * 255.0) as u8,
+            (avg.z * 255.0) as u8,
+        );
+        cache.colors.insert(coord, (entity, color));
+    }
+}
+
+fn draw_minimap(
+    mut contexts: EguiContexts,
+    show: Res<ShowMinimap>,
+    terrain: Res<TerrainWorld>,
+    cache: Res<MinimapChunkColors>,
+    camera: Res<TopDownCameraSettings>,
+    q_objects: Query<&Transform, (With<ObjectKind>, Without<Viewer>)>,
+    q_viewer: Query<&Transform, With<Viewer>>,
+    mut tween: ResMut<CameraTween>,
+) {
+    if !show.0 {
+        return;
+    }
+
+    let Ok(viewer) = q_viewer.single() else {
+        return;
+    };
+
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+
+    let size = 200.0;
+    let margin = 10.0;
+
+    // Show the whole streamed area around the viewer.
+    let chunk_world = terrain.config.chunk_size as f32 * terrain.config.tile_size;
+    let span_world = (terrain.config.view_distance_chunks * 2 + 1) as f32 * chunk_world;
+    let scale = size / span_world;
+    let focus = Vec2::new(viewer.translation.x, viewer.translation.z);
+
+    let viewport = ctx.viewport_rect();
+
+    egui::Area::new("minimap".into())
+        .pivot(egui::Align2::RIGHT_TOP)
+        .fixed_pos(egui::pos2(viewport.width() - margin, margin))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(egui::Color32::from_rgb(35, 35, 35))
+                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(90, 90, 90)))
+                .corner_radius(6)
+                .inner_margin(4)
+                .show(ui, |ui| {
+                    let (response, painter) =
+                        ui.allocate_painter(egui::vec2(size, size), egui::Sense::click());
+                    let rect = response.rect;
+                    let painter = painter.with_clip_rect(rect);
+
+                    // World +X is right and world +Z is down.
+                    let to_screen = |world_xz: Vec2| {
+                        let p = (world_xz - focus) * scale;
+                        rect.center() + egui::vec2(p.x, p.y)
+                    };
+
+                    for (coord, (_, color)) in &cache.colors {
+                        let origin = terrain.chunk_origin_world(*coord);
+                        let min = to_screen(Vec2::new(origin.x, origin.z));
+                        let max =
+                            to_screen(Vec2::new(origin.x, origin.z) + Vec2::splat(chunk_world));
+                        painter.rect_filled(egui::Rect::from_min_max(min, max), 0.0, *color);
+                    }
+
+                    for t in q_objects.iter() {
+                        let p = to_screen(Vec2::new(t.translation.x, t.translation.z));
+                        painter.circle_filled(p, 1.5, egui::Color32::from_rgb(230, 230, 230));
+                    }
+
+                    painter.circle_stroke(
+                        rect.center(),
+                        4.0,
+                        egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 60, 60)),
+                    );
+
+                    if response.clicked()
+                        && let Some(pos) = response.interact_pointer_pos()
+                    {
+                        let offset = (pos - rect.center()) / scale;
+                        tween.fly_to(focus + Vec2::new(offset.x, offset.y), camera.distance, 0.5);
+                    }
+                });
+        });
+}
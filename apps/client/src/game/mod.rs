@@ -1,8 +1,21 @@
+pub mod bookmarks;
 pub mod camera;
+pub mod clipboard;
+pub mod contours;
+pub mod debug_gizmos;
+pub mod diagnostics;
+pub mod grid;
+pub mod health_bars;
+pub mod heightmap_export;
+pub mod history;
 pub mod input;
 pub mod lighting;
+pub mod minimap;
 pub mod modes;
 pub mod physics;
+pub mod scatter;
+pub mod terrain_settings;
+pub mod tooltip;
 
 use bevy::prelude::*;
 
@@ -35,12 +48,29 @@ impl Plugin for GamePlugin {
             .add_plugins(terrain_crate::TerrainPlugin {
                 config: self.terrain_config.clone(),
             })
-            .add_plugins(objects_crate::ObjectsPlugin)
+            .add_plugins(objects_crate::ObjectsPlugin::default())
             .add_plugins(camera::CameraPlugin)
+            .add_plugins(bookmarks::CameraBookmarksPlugin)
             .add_plugins(input::InputPlugin)
             .add_plugins(lighting::LightingPlugin)
+            .add_plugins(history::HistoryPlugin)
+            .add_plugins(clipboard::ClipboardPlugin)
             .add_plugins(modes::construction::ConstructionModePlugin)
             .add_plugins(modes::destruction::DestructionModePlugin)
+            .add_plugins(modes::selection::SelectionModePlugin)
+            .add_plugins(modes::terraform::TerraformModePlugin)
+            .add_plugins(modes::paint::PaintModePlugin)
+            .add_plugins(modes::edit::EditModePlugin)
+            .add_plugins(tooltip::TooltipPlugin)
+            .add_plugins(health_bars::HealthBarPlugin)
+            .add_plugins(minimap::MinimapPlugin)
+            .add_plugins(grid::TileGridPlugin)
+            .add_plugins(contours::ContourPlugin)
+            .add_plugins(heightmap_export::HeightmapExportPlugin)
+            .add_plugins(debug_gizmos::DebugGizmosPlugin)
+            .add_plugins(diagnostics::DiagnosticsOverlayPlugin)
+            .add_plugins(scatter::ScatterPlugin)
+            .add_plugins(terrain_settings::TerrainSettingsPlugin)
             .configure_sets(
                 Startup,
                 (
@@ -1,17 +1,39 @@
+use bevy::camera::primitives::Aabb;
 use bevy::prelude::*;
-use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use bevy::window::SystemCursorIcon;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, EguiTextureHandle, egui};
+use std::collections::HashMap;
 
-use objects::ObjectTypeId;
 use objects::highlight;
+use objects::instancing::InstancedMeshes;
 use objects::spatial::SpatialHashGrid;
-use objects::system::{CursorHit, ObjectKind, ObjectTypes};
-use terrain::TerrainWorld;
-use ui::{ToolId, ToolbarActionText, ToolbarRegistry, ToolbarState, ToolbarTool, UiInputCapture};
+use objects::system::{CursorHit, HoveredObject, ObjectKind, ObjectScale, ObjectTypes};
+use objects::{ObjectTypeId, ObjectTypeSpec};
+use terrain::conform::{conform_circle, conform_polygon};
+use terrain::{BiomeConfig, TerrainWorld};
+use ui::{
+    InputAction, KeyBindings, ToolChanged, ToolCursorOverride, ToolId, ToolbarActionText,
+    ToolbarRegistry, ToolbarState, ToolbarTool, UiInputCapture,
+};
+
+use crate::game::UpdateSet;
+use crate::game::history::{EditAction, EditHistory};
 
 #[derive(Resource, Default)]
 pub struct ConstructionState {
     /// Selected object for construction
     pub selected: Option<ObjectTypeId>,
+    /// Palette filter; matched case-insensitively against object names.
+    pub search: String,
+    /// Keep placing while LMB is held, leaving a trail spaced one collision diameter apart.
+    pub continuous: bool,
+    /// Clicking a hovered object swaps it for the selected type, keeping its position, yaw and
+    /// scale.
+    pub replace: bool,
+    /// Objects placed by the current continuous stroke; recorded as one history entry on release.
+    stroke: Vec<EditAction>,
+    /// Where the object being aimed will go, set on press when `aim_on_place` is on.
+    aim_anchor: Option<Vec2>,
 }
 
 #[derive(Resource, Default)]
@@ -19,6 +41,214 @@ pub struct PlacementRotation {
     pub yaw: f32,
 }
 
+/// Grid alignment for placement; both off is freeform.
+#[derive(Resource, Default)]
+pub struct PlacementSettings {
+    /// Place on the centre of the tile under the cursor instead of at the cursor.
+    pub snap_to_tiles: bool,
+    /// Rotate in 90 degree steps, one per key press.
+    pub snap_yaw: bool,
+    /// Press to set the position, drag to point the object's +Z at the cursor, release to place.
+    /// Not used with continuous placement, which places while the button is held.
+    pub aim_on_place: bool,
+    /// Level the ground under each placed object to its base height, blending back to the
+    /// surrounding terrain over one tile. Steep ground is allowed since it gets levelled.
+    pub flatten_under_footprint: bool,
+    /// Sit new objects on top of objects under the cursor instead of on the terrain. Stacked
+    /// objects skip the slope and overlap checks, since they overlap what they sit on.
+    pub allow_stacking: bool,
+    /// Refuse to place into a terrain chunk that already holds this many objects, so one
+    /// chunk can't end up with thousands. `None` is unlimited.
+    pub max_objects_per_chunk: Option<usize>,
+    /// Outline the ground the hologram covers, coloured by whether it can be placed.
+    pub show_footprint: bool,
+}
+
+/// Limits on what can be built. Without this resource placement is unlimited.
+///
+/// Each placement by the player (construct, replace, paste, undo and redo) spends from it and
+/// marks the object [`ChargedPlacement`]; removing a marked object, by any means, refunds it.
+/// Objects that were never charged, e.g. scattered ones or those placed before the budget was
+/// set, are not refunded.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct PlacementBudget {
+    /// Placements left per type; types not listed are unlimited.
+    pub remaining: HashMap<ObjectTypeId, u32>,
+    /// Shared currency; `None` turns costs off.
+    pub currency: Option<u32>,
+    /// What each type costs from `currency`; types not listed are free.
+    pub costs: HashMap<ObjectTypeId, u32>,
+}
+
+impl PlacementBudget {
+    pub fn can_afford(&self, type_id: ObjectTypeId) -> bool {
+        let count_left = self.remaining.get(&type_id).is_none_or(|n| *n > 0);
+        let cost = self.costs.get(&type_id).copied().unwrap_or(0);
+        count_left && self.currency.is_none_or(|c| c >= cost)
+    }
+
+    fn spend(&mut self, type_id: ObjectTypeId) {
+        if let Some(n) = self.remaining.get_mut(&type_id) {
+            *n = n.saturating_sub(1);
+        }
+        let cost = self.costs.get(&type_id).copied().unwrap_or(0);
+        if let Some(c) = self.currency.as_mut() {
+            *c = c.saturating_sub(cost);
+        }
+    }
+
+    /// Spends for an object just spawned as `entity` and marks it to be refunded on removal.
+    pub(crate) fn charge(
+        &mut self,
+        commands: &mut Commands,
+        entity: Entity,
+        type_id: ObjectTypeId,
+    ) {
+        self.spend(type_id);
+        commands.entity(entity).insert(ChargedPlacement);
+    }
+
+    fn refund(&mut self, type_id: ObjectTypeId) {
+        if let Some(n) = self.remaining.get_mut(&type_id) {
+            *n += 1;
+        }
+        let cost = self.costs.get(&type_id).copied().unwrap_or(0);
+        if let Some(c) = self.currency.as_mut() {
+            *c += cost;
+        }
+    }
+}
+
+/// On objects whose placement was paid for from the [`PlacementBudget`].
+#[derive(Component)]
+pub struct ChargedPlacement;
+
+/// On objects sitting on top of other objects rather than on the ground (see `allow_stacking`).
+/// Whatever turns or rescales an object in place keeps their height instead of putting them
+/// back on the terrain.
+#[derive(Component)]
+pub struct Stacked;
+
+/// Quarter turn, the `snap_yaw` step.
+const YAW_STEP: f32 = std::f32::consts::FRAC_PI_2;
+
+/// Where an object placed at `world` goes, after tile snapping.
+pub(crate) fn placement_xz(
+    settings: &PlacementSettings,
+    terrain: &TerrainWorld,
+    world: Vec3,
+) -> Vec2 {
+    if settings.snap_to_tiles {
+        terrain.tile_center(terrain.world_to_tile_coord(world.x, world.z))
+    } else {
+        Vec2::new(world.x, world.z)
+    }
+}
+
+/// Whether the terrain chunk containing `position` already holds `max_objects_per_chunk`, not
+/// counting `exclude`.
+fn chunk_full(
+    settings: &PlacementSettings,
+    terrain: &TerrainWorld,
+    grid: &SpatialHashGrid,
+    q_objects: &PlacedObjects,
+    position: Vec3,
+    exclude: &[Entity],
+) -> bool {
+    let Some(limit) = settings.max_objects_per_chunk else {
+        return false;
+    };
+    let coord = terrain.world_to_chunk_coord(Vec2::new(position.x, position.z));
+    let origin = terrain.chunk_origin_world(coord);
+    let min = Vec2::new(origin.x, origin.z);
+    let max = min + Vec2::splat(terrain.config.chunk_size as f32 * terrain.config.tile_size);
+    let count = grid
+        .query_aabb(min, max)
+        .into_iter()
+        .filter(|e| !exclude.contains(e))
+        .filter_map(|e| q_objects.get(e).ok())
+        .filter(|(t, _, _)| {
+            terrain.world_to_chunk_coord(Vec2::new(t.translation.x, t.translation.z)) == coord
+        })
+        .count();
+    count >= limit
+}
+
+/// Mesh bounding boxes, for [`stack_height`].
+pub(crate) type MeshBounds<'w, 's> = Query<'w, 's, (&'static Aabb, &'static GlobalTransform)>;
+
+/// Placed objects, as [`PlacementCheck`] sees them.
+pub(crate) type PlacedObjects<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static ObjectKind,
+        &'static ObjectScale,
+    ),
+>;
+
+/// What the hologram is checked against besides the terrain itself.
+type PlacementLimits<'w> = (
+    Res<'w, SpatialHashGrid>,
+    Option<Res<'w, PlacementBudget>>,
+    Option<Res<'w, BiomeConfig>>,
+);
+
+/// Height an object placed at `xz` would sit at on top of other objects (besides `exclude`), if
+/// any are under it and higher than `ground`. Only the tops of their meshes' bounding boxes are
+/// tested; this is for props on platforms, not a physics stack.
+fn stack_height(
+    types: &ObjectTypes,
+    grid: &SpatialHashGrid,
+    children: &Query<&Children>,
+    q_bounds: &MeshBounds,
+    xz: Vec2,
+    ground: f32,
+    exclude: &[Entity],
+) -> Option<f32> {
+    let mut top: Option<f32> = None;
+    for object in grid.query_candidates(xz, types.max_hover_radius) {
+        if exclude.contains(&object) {
+            continue;
+        }
+        for e in std::iter::once(object).chain(children.iter_descendants(object)) {
+            let Ok((aabb, transform)) = q_bounds.get(e) else {
+                continue;
+            };
+            // World-space box around the transformed local box.
+            let affine = transform.affine();
+            let center = affine.transform_point3a(aabb.center);
+            let m = affine.matrix3;
+            let half = m.x_axis.abs() * aabb.half_extents.x
+                + m.y_axis.abs() * aabb.half_extents.y
+                + m.z_axis.abs() * aabb.half_extents.z;
+            if (xz.x - center.x).abs() <= half.x && (xz.y - center.z).abs() <= half.z {
+                let y = center.y + half.y;
+                top = Some(top.map_or(y, |t| t.max(y)));
+            }
+        }
+    }
+    top.filter(|t| *t > ground)
+}
+
+/// Drags shorter than this keep the R/F yaw, so a plain click still places as before.
+const MIN_AIM_DISTANCE: f32 = 0.25;
+
+/// Yaw that points +Z from `anchor` towards `cursor`, or `fallback` if they're too close.
+fn aim_yaw(settings: &PlacementSettings, anchor: Vec2, cursor: Vec3, fallback: f32) -> f32 {
+    let delta = Vec2::new(cursor.x, cursor.z) - anchor;
+    if delta.length() < MIN_AIM_DISTANCE {
+        return fallback;
+    }
+    let yaw = delta.x.atan2(delta.y);
+    if settings.snap_yaw {
+        ((yaw / YAW_STEP).round() * YAW_STEP).rem_euclid(std::f32::consts::TAU)
+    } else {
+        yaw.rem_euclid(std::f32::consts::TAU)
+    }
+}
+
 #[derive(Resource)]
 pub struct HologramMaterials {
     pub valid: Handle<StandardMaterial>,
@@ -30,6 +260,8 @@ pub struct HologramPreview {
     pub entity: Option<Entity>,
     pub scene_child: Option<Entity>,
     pub object_type: Option<ObjectTypeId>,
+    /// Why the hologram can't be placed where it is, for the info text.
+    pub blocked: Option<&'static str>,
 }
 
 pub struct ConstructionModePlugin;
@@ -37,6 +269,7 @@ pub struct ConstructionModePlugin;
 impl Plugin for ConstructionModePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<PlacementRotation>()
+            .init_resource::<PlacementSettings>()
             .init_resource::<HologramPreview>()
             .init_resource::<ConstructionState>()
             .add_systems(
@@ -46,33 +279,45 @@ impl Plugin for ConstructionModePlugin {
             .add_systems(
                 Update,
                 (
-                    update_placement_rotation,
+                    // Keyboard gating must see this frame's capture state, otherwise typing
+                    // into the palette search box would rotate the hologram.
+                    update_placement_rotation.after(UpdateSet::UiCapture),
                     update_hologram_preview,
                     handle_construction_click,
-                    reset_on_tool_change,
+                    handle_replace_click,
+                    exit_construction_mode.after(ui::emit_tool_changed),
                 ),
             )
+            .add_observer(refund_placement_budget)
             .add_systems(EguiPrimaryContextPass, draw_construction_ui);
     }
 }
 
 fn setup_construction_toolbar(mut registry: ResMut<ToolbarRegistry>) {
-    registry.tools.push(ToolbarTool {
+    registry.register(ToolbarTool {
         id: ToolId::Construct,
         label: "Construct".to_string(),
         order: 0,
-        key: Some(KeyCode::Digit1),
+        group: Some("Build".to_string()),
+        key: None,
+        secondary_key: None,
+        cursor: Some(SystemCursorIcon::Crosshair.into()),
     });
 }
 
-fn reset_on_tool_change(
-    toolbar: Res<ToolbarState>,
+fn exit_construction_mode(
+    mut tool_changed: MessageReader<ToolChanged>,
     mut construction: ResMut<ConstructionState>,
     mut preview: ResMut<HologramPreview>,
+    mut cursor_override: ResMut<ToolCursorOverride>,
 ) {
-    if toolbar.is_changed() && toolbar.active_tool != Some(ToolId::Construct) {
-        construction.selected = None;
-        preview.object_type = None;
+    for change in tool_changed.read() {
+        if change.from == Some(ToolId::Construct) {
+            construction.selected = None;
+            construction.aim_anchor = None;
+            preview.object_type = None;
+            cursor_override.0 = None;
+        }
     }
 }
 
@@ -99,18 +344,37 @@ fn setup_construction_materials(
 fn update_placement_rotation(
     time: Res<Time>,
     keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
     mut rot: ResMut<PlacementRotation>,
+    settings: Res<PlacementSettings>,
     ui_capture: Res<UiInputCapture>,
 ) {
     if ui_capture.keyboard {
         return;
     }
 
+    if settings.snap_yaw {
+        let mut steps: f32 = 0.0;
+        if bindings.just_pressed(&keys, InputAction::RotateCW) {
+            steps += 1.0;
+        }
+        if bindings.just_pressed(&keys, InputAction::RotateCCW) {
+            steps -= 1.0;
+        }
+        // Rounding first also lines up a yaw left over from free rotation.
+        let snapped = ((rot.yaw / YAW_STEP).round() + steps) * YAW_STEP;
+        let snapped = snapped.rem_euclid(std::f32::consts::TAU);
+        if rot.yaw != snapped {
+            rot.yaw = snapped;
+        }
+        return;
+    }
+
     let mut delta: f32 = 0.0;
-    if keys.pressed(KeyCode::KeyR) {
+    if bindings.pressed(&keys, InputAction::RotateCW) {
         delta += 1.0;
     }
-    if keys.pressed(KeyCode::KeyF) {
+    if bindings.pressed(&keys, InputAction::RotateCCW) {
         delta -= 1.0;
     }
 
@@ -129,16 +393,24 @@ fn update_hologram_preview(
     terrain: Res<TerrainWorld>,
     asset_server: Res<AssetServer>,
     types: Option<Res<ObjectTypes>>,
-    q_objects: Query<(&Transform, &ObjectKind)>,
+    q_objects: PlacedObjects,
     toolbar: Res<ToolbarState>,
     construction: Res<ConstructionState>,
     hit: Res<CursorHit>,
     placement_rot: Res<PlacementRotation>,
+    placement: Res<PlacementSettings>,
     hologram_materials: Res<HologramMaterials>,
     mut preview: ResMut<HologramPreview>,
-    children: Query<&Children>,
+    children: Query<&'static Children>,
     mut q_materials: Query<&mut MeshMaterial3d<StandardMaterial>>,
-    grid: Res<SpatialHashGrid>,
+    mut cursor_override: ResMut<ToolCursorOverride>,
+    // Grouped to stay within the system parameter limit.
+    ((grid, budget, biomes), q_bounds, q_global, mut gizmos): (
+        PlacementLimits,
+        MeshBounds,
+        Query<&GlobalTransform>,
+        Gizmos,
+    ),
 ) {
     let Some(types) = types else {
         return;
@@ -146,13 +418,18 @@ fn update_hologram_preview(
 
     let show = toolbar.active_tool == Some(ToolId::Construct)
         && hit.world.is_some()
-        && construction.selected.is_some();
+        && construction.selected.is_some()
+        && !construction.replace;
     if !show {
+        if toolbar.active_tool == Some(ToolId::Construct) && cursor_override.0.is_some() {
+            cursor_override.0 = None;
+        }
         if let Some(e) = preview.entity.take() {
             highlight::despawn_recursive(&mut commands, &children, e);
         }
         preview.scene_child = None;
         preview.object_type = None;
+        preview.blocked = None;
         return;
     }
 
@@ -179,20 +456,47 @@ fn update_hologram_preview(
         return;
     };
 
-    let base_h = terrain.sample_height_at(hit_world.x, hit_world.z);
-    let pos_world = Vec3::new(hit_world.x, base_h, hit_world.z);
-    let rot = Quat::from_rotation_y(placement_rot.yaw);
+    // While aiming the hologram stays put and turns to follow the cursor.
+    let (xz, yaw) = match construction.aim_anchor {
+        Some(anchor) => (
+            anchor,
+            aim_yaw(&placement, anchor, hit_world, placement_rot.yaw),
+        ),
+        None => (
+            placement_xz(&placement, &terrain, hit_world),
+            placement_rot.yaw,
+        ),
+    };
+    let mut check = PlacementCheck::new(
+        &terrain,
+        &types,
+        &grid,
+        &q_objects,
+        &placement,
+        budget.as_deref(),
+        biomes.as_deref(),
+    );
+    if placement.allow_stacking {
+        check.stacking = Some((&children, &q_bounds));
+    }
+    let (pos_world, rot, stacked) = check.pose(spec, xz, yaw, Vec3::ONE, &[]);
     let transform = Transform::from_translation(pos_world)
         .with_rotation(rot)
         .with_scale(spec.render_scale);
 
-    let can_place = objects::system::can_place_non_overlapping_spatial(
-        &types.registry,
-        object_type,
-        pos_world,
-        &grid,
-        &q_objects,
-    );
+    let blocked = check
+        .validate(object_type, xz, yaw, Vec3::ONE, &[])
+        .err()
+        .map(PlacementError::reason);
+    if preview.blocked != blocked {
+        preview.blocked = blocked;
+    }
+    let can_place = blocked.is_none();
+
+    let blocked_cursor = (!can_place).then(|| SystemCursorIcon::NotAllowed.into());
+    if cursor_override.0 != blocked_cursor {
+        cursor_override.0 = blocked_cursor;
+    }
 
     let chosen_material = if can_place {
         &hologram_materials.valid
@@ -215,6 +519,39 @@ fn update_hologram_preview(
     preview.entity = Some(preview_entity);
     preview.scene_child = Some(scene_child);
 
+    if placement.show_footprint {
+        let color = if can_place {
+            Color::srgb(0.3, 1.0, 0.4)
+        } else {
+            Color::srgb(1.0, 0.3, 0.3)
+        };
+        let lift = 0.08;
+        let mut outline = match hologram_footprint(preview_entity, &children, &q_bounds, &q_global)
+        {
+            Some((min, max)) => {
+                let corners = [
+                    Vec3::new(min.x, 0.0, min.y),
+                    Vec3::new(max.x, 0.0, min.y),
+                    Vec3::new(max.x, 0.0, max.y),
+                    Vec3::new(min.x, 0.0, max.y),
+                ]
+                .map(|c| transform.transform_point(c).xz());
+                // About twice per tile, so long sides bend with the ground.
+                let step = terrain.config.tile_size * 0.5;
+                conform_polygon(&terrain, &corners, step, lift)
+            }
+            // The model hasn't loaded yet, so fall back to the hover radius.
+            None => conform_circle(&terrain, xz, spec.hover_radius, 32, lift),
+        };
+        // Stacked or levelled objects sit on a flat surface at their base, not on the terrain.
+        if stacked || placement.flatten_under_footprint {
+            for p in &mut outline {
+                p.y = pos_world.y + lift;
+            }
+        }
+        gizmos.linestrip(outline, color);
+    }
+
     highlight::apply_hologram_material_recursive(
         &children,
         &mut q_materials,
@@ -224,69 +561,556 @@ fn update_hologram_preview(
     );
 }
 
+/// The XZ extent of the hologram's meshes in its own (unrotated, unscaled) space, or `None`
+/// until its scene has spawned meshes.
+fn hologram_footprint(
+    root: Entity,
+    children: &Query<&Children>,
+    q_bounds: &MeshBounds,
+    q_global: &Query<&GlobalTransform>,
+) -> Option<(Vec2, Vec2)> {
+    let to_local = q_global.get(root).ok()?.affine().inverse();
+    let mut extent: Option<(Vec2, Vec2)> = None;
+    for e in children.iter_descendants(root) {
+        let Ok((aabb, transform)) = q_bounds.get(e) else {
+            continue;
+        };
+        let affine = to_local * transform.affine();
+        let (lo, hi) = (aabb.min(), aabb.max());
+        for i in 0..8 {
+            let corner = Vec3A::new(
+                if i & 1 == 0 { lo.x } else { hi.x },
+                if i & 2 == 0 { lo.y } else { hi.y },
+                if i & 4 == 0 { lo.z } else { hi.z },
+            );
+            let p = affine.transform_point3a(corner);
+            let p = Vec2::new(p.x, p.z);
+            extent = Some(extent.map_or((p, p), |(min, max)| (min.min(p), max.max(p))));
+        }
+    }
+    extent
+}
+
+/// Whether the ground under an object at `position` can be built on: it isn't below sea level
+/// while `water_blocks_placement` is on, and no tile under its footprint is unbuildable. Water
+/// is reported as such whichever rule catches it.
+fn check_ground(
+    terrain: &TerrainWorld,
+    biomes: Option<&BiomeConfig>,
+    spec: &ObjectTypeSpec,
+    position: Vec3,
+    scale: Vec3,
+) -> Result<(), PlacementError> {
+    if terrain.config.water_blocks_placement && terrain.is_underwater(position.x, position.z) {
+        return Err(PlacementError::Water);
+    }
+    match unbuildable_spot(terrain, biomes, spec, position, scale) {
+        Some(p) if terrain.is_underwater(p.x, p.y) => Err(PlacementError::Water),
+        Some(_) => Err(PlacementError::Unbuildable),
+        None => Ok(()),
+    }
+}
+
+/// The first point of the object's footprint (its centre and the corners of its collision
+/// radius at `scale`) on a tile that can't be built on. Everything is buildable until the tile
+/// types load.
+fn unbuildable_spot(
+    terrain: &TerrainWorld,
+    biomes: Option<&BiomeConfig>,
+    spec: &ObjectTypeSpec,
+    position: Vec3,
+    scale: Vec3,
+) -> Option<Vec2> {
+    let biomes = biomes?;
+    let r = spec.effective_collision_radius() * scale.max_element();
+    [(0.0, 0.0), (-r, -r), (r, -r), (r, r), (-r, r)]
+        .into_iter()
+        .map(|(dx, dz)| Vec2::new(position.x + dx, position.z + dz))
+        .find(|p| !terrain.tile_type_at(p.x, p.y, biomes).buildable)
+}
+
+fn too_steep(terrain: &TerrainWorld, spec: &ObjectTypeSpec, position: Vec3) -> bool {
+    spec.max_placement_slope
+        .is_some_and(|max| terrain.slope_at(position.x, position.z) > max)
+}
+
+/// Why [`PlacementCheck::validate`] refused a placement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PlacementError {
+    /// No type is registered under the id.
+    UnknownType,
+    /// The [`PlacementBudget`] can't pay for it.
+    CantAfford,
+    /// Its chunk already holds `max_objects_per_chunk` objects.
+    ChunkFull,
+    /// It's in water: below sea level with `water_blocks_placement` on, or on an unbuildable
+    /// tile that's underwater.
+    Water,
+    /// Part of its footprint is on a tile that can't be built on.
+    Unbuildable,
+    /// The ground is steeper than its type allows.
+    TooSteep,
+    /// It would overlap another object.
+    Overlaps,
+}
+
+impl PlacementError {
+    /// Short explanation for the info text and logs.
+    pub(crate) fn reason(self) -> &'static str {
+        match self {
+            PlacementError::UnknownType => "unknown object type",
+            PlacementError::CantAfford => "can't afford",
+            PlacementError::ChunkFull => "too many objects in this chunk",
+            PlacementError::Water => "can't build on water",
+            PlacementError::Unbuildable => "can't build on this ground",
+            PlacementError::TooSteep => "too steep",
+            PlacementError::Overlaps => "overlaps another object",
+        }
+    }
+}
+
+/// Everything a placement is checked against. Every way of placing an object (construct,
+/// replace, paste, undo and redo) goes through [`validate`](Self::validate), so they all follow
+/// the same rules.
+///
+/// It only borrows, so systems that go on to edit the terrain or spend from the budget build one
+/// from their own params for the check.
+pub(crate) struct PlacementCheck<'a, 'w, 's> {
+    pub terrain: &'a TerrainWorld,
+    pub types: &'a ObjectTypes,
+    pub grid: &'a SpatialHashGrid,
+    pub objects: &'a PlacedObjects<'w, 's>,
+    pub settings: &'a PlacementSettings,
+    pub budget: Option<&'a PlacementBudget>,
+    pub biomes: Option<&'a BiomeConfig>,
+    /// Pose on the level pad `flatten_under_footprint` will give it, and allow steep ground.
+    /// Off for placements that don't level the ground.
+    pub flatten: bool,
+    /// Sit objects on top of objects under them; see `allow_stacking`.
+    pub stacking: Option<(&'a Query<'w, 's, &'static Children>, &'a MeshBounds<'w, 's>)>,
+}
+
+impl<'a, 'w, 's> PlacementCheck<'a, 'w, 's> {
+    /// A check with flattening as `settings` has it and no stacking.
+    pub(crate) fn new(
+        terrain: &'a TerrainWorld,
+        types: &'a ObjectTypes,
+        grid: &'a SpatialHashGrid,
+        objects: &'a PlacedObjects<'w, 's>,
+        settings: &'a PlacementSettings,
+        budget: Option<&'a PlacementBudget>,
+        biomes: Option<&'a BiomeConfig>,
+    ) -> Self {
+        Self {
+            terrain,
+            types,
+            grid,
+            objects,
+            settings,
+            budget,
+            biomes,
+            flatten: settings.flatten_under_footprint,
+            stacking: None,
+        }
+    }
+
+    /// Where an object placed at `xz` goes, and whether it's stacked on other objects. Objects
+    /// in `exclude` aren't stacked on.
+    pub(crate) fn pose(
+        &self,
+        spec: &ObjectTypeSpec,
+        xz: Vec2,
+        yaw: f32,
+        scale: Vec3,
+        exclude: &[Entity],
+    ) -> (Vec3, Quat, bool) {
+        let terrain = self.terrain;
+        let (mut position, rotation) = if self.flatten {
+            let pad = terrain.sample_height_at(xz.x, xz.y);
+            objects::system::ground_pose(spec, xz, yaw, scale, |_, _| pad)
+        } else {
+            objects::system::ground_pose(spec, xz, yaw, scale, |x, z| {
+                terrain.sample_height_at(x, z)
+            })
+        };
+        let top = self.stacking.and_then(|(children, q_bounds)| {
+            stack_height(
+                self.types, self.grid, children, q_bounds, xz, position.y, exclude,
+            )
+        });
+        if let Some(top) = top {
+            position.y = top;
+        }
+        (position, rotation, top.is_some())
+    }
+
+    /// Poses an object of `type_id` at `xz` like [`pose`](Self::pose) and checks it can go
+    /// there. Objects in `exclude` don't count, e.g. the one it replaces.
+    pub(crate) fn validate(
+        &self,
+        type_id: ObjectTypeId,
+        xz: Vec2,
+        yaw: f32,
+        scale: Vec3,
+        exclude: &[Entity],
+    ) -> Result<(Vec3, Quat), PlacementError> {
+        let spec = self
+            .types
+            .registry
+            .get(type_id)
+            .ok_or(PlacementError::UnknownType)?;
+        let (position, rotation, stacked) = self.pose(spec, xz, yaw, scale, exclude);
+
+        if self.budget.is_some_and(|b| !b.can_afford(type_id)) {
+            return Err(PlacementError::CantAfford);
+        }
+        if chunk_full(
+            self.settings,
+            self.terrain,
+            self.grid,
+            self.objects,
+            position,
+            exclude,
+        ) {
+            return Err(PlacementError::ChunkFull);
+        }
+        check_ground(self.terrain, self.biomes, spec, position, scale)?;
+        // Stacked objects overlap what they sit on, and don't rest on the ground.
+        if stacked {
+            return Ok((position, rotation));
+        }
+        if !self.flatten && too_steep(self.terrain, spec, position) {
+            return Err(PlacementError::TooSteep);
+        }
+        if !objects::system::can_place_non_overlapping_spatial(
+            &self.types.registry,
+            type_id,
+            position,
+            scale,
+            self.grid,
+            self.objects,
+            exclude,
+        ) {
+            return Err(PlacementError::Overlaps);
+        }
+        Ok((position, rotation))
+    }
+}
+
 fn handle_construction_click(
     mut commands: Commands,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     hit: Res<CursorHit>,
     toolbar: Res<ToolbarState>,
-    construction: Res<ConstructionState>,
+    mut construction: ResMut<ConstructionState>,
     placement_rot: Res<PlacementRotation>,
+    placement: Res<PlacementSettings>,
     types: Option<Res<ObjectTypes>>,
-    q_objects: Query<(&Transform, &ObjectKind)>,
-    terrain: Res<TerrainWorld>,
+    q_objects: PlacedObjects,
+    mut terrain: ResMut<TerrainWorld>,
     asset_server: Res<AssetServer>,
     ui_capture: Res<UiInputCapture>,
-    grid: Res<SpatialHashGrid>,
+    // Grouped to stay within the system parameter limit.
+    (grid, children, q_bounds, biomes): (
+        Res<SpatialHashGrid>,
+        Query<&'static Children>,
+        MeshBounds,
+        Option<Res<BiomeConfig>>,
+    ),
+    instanced: Res<InstancedMeshes>,
+    mut history: ResMut<EditHistory>,
+    mut budget: Option<ResMut<PlacementBudget>>,
 ) {
+    // A continuous stroke ends when the button is released (or the tool changes).
+    if (!mouse_buttons.pressed(MouseButton::Left) || toolbar.active_tool != Some(ToolId::Construct))
+        && !construction.stroke.is_empty()
+    {
+        let stroke = std::mem::take(&mut construction.stroke);
+        history.push(stroke);
+    }
+
     let Some(types) = types else {
         return;
     };
 
-    if ui_capture.pointer {
-        return;
-    }
-
-    if !mouse_buttons.just_pressed(MouseButton::Left) {
+    if toolbar.active_tool != Some(ToolId::Construct) || construction.replace {
+        construction.aim_anchor = None;
         return;
     }
 
-    if toolbar.active_tool != Some(ToolId::Construct) {
-        return;
-    }
+    // Aiming: the press picks the spot, the release places facing the cursor. The release
+    // counts even over the UI so an aim can't get stuck.
+    let aiming = placement.aim_on_place && !construction.continuous;
+    let target = if aiming {
+        if let Some(anchor) = construction.aim_anchor {
+            if !mouse_buttons.just_released(MouseButton::Left) {
+                return;
+            }
+            construction.aim_anchor = None;
+            let yaw = hit
+                .world
+                .map(|w| aim_yaw(&placement, anchor, w, placement_rot.yaw))
+                .unwrap_or(placement_rot.yaw);
+            Some((anchor, yaw))
+        } else {
+            if !ui_capture.pointer
+                && mouse_buttons.just_pressed(MouseButton::Left)
+                && construction.selected.is_some()
+                && let Some(world) = hit.world
+            {
+                construction.aim_anchor = Some(placement_xz(&placement, &terrain, world));
+            }
+            return;
+        }
+    } else {
+        construction.aim_anchor = None;
+        if ui_capture.pointer {
+            return;
+        }
+        let placing = if construction.continuous {
+            mouse_buttons.pressed(MouseButton::Left)
+        } else {
+            mouse_buttons.just_pressed(MouseButton::Left)
+        };
+        if !placing {
+            return;
+        }
+        hit.world
+            .map(|w| (placement_xz(&placement, &terrain, w), placement_rot.yaw))
+    };
 
     if let Some(object) = construction.selected {
-        let Some(world) = hit.world else {
+        let Some((xz, yaw)) = target else {
+            return;
+        };
+        let Some(spec) = types.registry.get(object) else {
             return;
         };
-        let base_h = terrain.sample_height_at(world.x, world.z);
-        let position = Vec3::new(world.x, base_h, world.z);
+        let mut check = PlacementCheck::new(
+            &terrain,
+            &types,
+            &grid,
+            &q_objects,
+            &placement,
+            budget.as_deref(),
+            biomes.as_deref(),
+        );
+        if placement.allow_stacking {
+            check.stacking = Some((&children, &q_bounds));
+        }
+        let (_, _, stacked) = check.pose(spec, xz, yaw, Vec3::ONE, &[]);
+        let (position, rotation) = match check.validate(object, xz, yaw, Vec3::ONE, &[]) {
+            Ok(pose) => pose,
+            // A held continuous stroke would repeat this every frame.
+            Err(PlacementError::ChunkFull) if !construction.continuous => {
+                warn!(
+                    "not placing '{}': chunk {} already has {} objects",
+                    spec.name,
+                    terrain.world_to_chunk_coord(xz),
+                    placement.max_objects_per_chunk.unwrap_or_default()
+                );
+                return;
+            }
+            Err(_) => return,
+        };
 
-        let can_place = objects::system::can_place_non_overlapping_spatial(
+        // Objects placed earlier in the stroke may not be in the spatial index yet, so space
+        // the trail against them directly.
+        let spacing = spec.effective_collision_radius() * 2.0;
+        let too_close_to_stroke = construction.stroke.iter().any(|a| match a {
+            EditAction::Place { position: p, .. } => {
+                Vec2::new(p.x - position.x, p.z - position.z).length() < spacing
+            }
+            _ => false,
+        });
+        if too_close_to_stroke {
+            return;
+        }
+
+        if let Some(spawned) = objects::system::spawn_object(
+            &mut commands,
             &types.registry,
+            &asset_server,
+            &instanced,
             object,
             position,
-            &grid,
-            &q_objects,
-        );
-        if can_place {
-            let _ = objects::system::spawn_object(
-                &mut commands,
-                &types.registry,
-                &asset_server,
-                object,
+            rotation,
+            Vec3::ONE,
+        ) {
+            if let Some(budget) = budget.as_mut() {
+                budget.charge(&mut commands, spawned, object);
+            }
+            if stacked {
+                commands.entity(spawned).insert(Stacked);
+            }
+            let mut actions = Vec::new();
+            if placement.flatten_under_footprint && !stacked {
+                let blend = terrain.config.tile_size;
+                let changes =
+                    terrain.flatten_pad(xz, spec.effective_collision_radius(), blend, position.y);
+                actions.push(EditAction::Terrain(changes));
+            }
+            actions.push(EditAction::Place {
+                type_id: object,
                 position,
-                placement_rot.yaw,
+                rotation,
+                scale: Vec3::ONE,
+                stacked,
+            });
+            if construction.continuous {
+                construction.stroke.extend(actions);
+            } else {
+                history.push(actions);
+            }
+        }
+    }
+}
+
+/// Swaps the hovered object for the selected type in one edit, at the old object's yaw and
+/// scale. The new object is posed and checked like any other placement, except that the old one
+/// doesn't count against it; if it doesn't fit, the original is kept.
+fn handle_replace_click(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    toolbar: Res<ToolbarState>,
+    construction: Res<ConstructionState>,
+    hovered: Res<HoveredObject>,
+    ui_capture: Res<UiInputCapture>,
+    types: Option<Res<ObjectTypes>>,
+    q_objects: PlacedObjects,
+    children: Query<&'static Children>,
+    // Grouped to stay within the system parameter limit.
+    (grid, terrain, biomes, placement): (
+        Res<SpatialHashGrid>,
+        Res<TerrainWorld>,
+        Option<Res<BiomeConfig>>,
+        Res<PlacementSettings>,
+    ),
+    (q_bounds, q_stacked): (MeshBounds, Query<(), With<Stacked>>),
+    asset_server: Res<AssetServer>,
+    instanced: Res<InstancedMeshes>,
+    mut history: ResMut<EditHistory>,
+    mut budget: Option<ResMut<PlacementBudget>>,
+) {
+    if toolbar.active_tool != Some(ToolId::Construct)
+        || !construction.replace
+        || ui_capture.pointer
+        || !mouse_buttons.just_pressed(MouseButton::Left)
+    {
+        return;
+    }
+
+    let (Some(entity), Some(object), Some(types)) = (hovered.0, construction.selected, types)
+    else {
+        return;
+    };
+    let Ok((transform, kind, scale)) = q_objects.get(entity) else {
+        return;
+    };
+    if kind.0 == object {
+        return;
+    }
+    let (Some(old_spec), Some(spec)) = (types.registry.get(kind.0), types.registry.get(object))
+    else {
+        return;
+    };
+
+    if old_spec.protected {
+        info!("'{}' is protected and can't be replaced", old_spec.name);
+        return;
+    }
+
+    let mut check = PlacementCheck::new(
+        &terrain,
+        &types,
+        &grid,
+        &q_objects,
+        &placement,
+        budget.as_deref(),
+        biomes.as_deref(),
+    );
+    // Replacing doesn't level the ground, and a stacked object's replacement goes on top of
+    // what it sat on.
+    check.flatten = false;
+    if q_stacked.contains(entity) {
+        check.stacking = Some((&children, &q_bounds));
+    }
+    let xz = transform.translation.xz();
+    let yaw = objects::system::heading_of(transform.rotation);
+    let (_, _, stacked) = check.pose(spec, xz, yaw, scale.0, &[entity]);
+    let (position, rotation) = match check.validate(object, xz, yaw, scale.0, &[entity]) {
+        Ok(pose) => pose,
+        Err(e) => {
+            warn!(
+                "not replacing '{}' with '{}': {}",
+                old_spec.name,
+                spec.name,
+                e.reason()
             );
+            return;
         }
+    };
+
+    let removed = EditAction::remove(kind, transform, scale, q_stacked.contains(entity));
+    let Some(spawned) = objects::system::spawn_object(
+        &mut commands,
+        &types.registry,
+        &asset_server,
+        &instanced,
+        object,
+        position,
+        rotation,
+        scale.0,
+    ) else {
+        return;
+    };
+    // If the old object was charged, `refund_placement_budget` refunds it as it goes.
+    highlight::despawn_recursive(&mut commands, &children, entity);
+    if let Some(budget) = budget.as_mut() {
+        budget.charge(&mut commands, spawned, object);
+    }
+    if stacked {
+        commands.entity(spawned).insert(Stacked);
     }
+    history.push(vec![
+        removed,
+        EditAction::Place {
+            type_id: object,
+            position,
+            rotation,
+            scale: scale.0,
+            stacked,
+        },
+    ]);
 }
 
+/// Observer, so the object's type can still be read as it goes.
+fn refund_placement_budget(
+    remove: On<Remove, ChargedPlacement>,
+    budget: Option<ResMut<PlacementBudget>>,
+    q_kinds: Query<&ObjectKind>,
+) {
+    let (Some(mut budget), Ok(kind)) = (budget, q_kinds.get(remove.entity)) else {
+        return;
+    };
+    budget.refund(kind.0);
+}
+
+/// A palette button: the type, and why it's disabled if its model failed to load.
+type PaletteEntry<'a> = (ObjectTypeId, Option<&'a str>);
+
+/// Side length, in points, of palette icon buttons.
+const PALETTE_ICON_SIZE: f32 = 48.0;
+
 fn draw_construction_ui(
     mut contexts: EguiContexts,
     toolbar: Res<ToolbarState>,
     mut construction: ResMut<ConstructionState>,
+    mut placement: ResMut<PlacementSettings>,
     types: Option<Res<ObjectTypes>>,
+    preview: Res<HologramPreview>,
     mut action_text: ResMut<ToolbarActionText>,
+    images: Res<Assets<Image>>,
 ) {
     let Some(types) = types else {
         return;
@@ -296,21 +1120,38 @@ fn draw_construction_ui(
         return;
     }
 
+    // Icons that have finished loading; the rest show their name until they do.
+    // `ObjectTypes` holds the strong handles, so egui only needs weak ones.
+    let icons: HashMap<ObjectTypeId, egui::TextureId> = types
+        .available
+        .iter()
+        .filter_map(|id| {
+            let handle = types.icon(*id)?;
+            images.get(handle)?;
+            Some((
+                *id,
+                contexts.add_image(EguiTextureHandle::Weak(handle.id())),
+            ))
+        })
+        .collect();
+
     let ctx = match contexts.ctx_mut() {
         Ok(ctx) => ctx,
         Err(_) => return,
     };
     let toolbar_width = 360.0;
     let toolbar_height = 40.0;
-    let secondary_height = 52.0;
+    let secondary_max_height = 220.0;
     let margin = 10.0;
 
     let viewport = ctx.viewport_rect();
 
+    // Anchored by its bottom edge so it grows upwards as sections are expanded.
     egui::Area::new("bottom_toolbar_secondary".into())
+        .pivot(egui::Align2::CENTER_BOTTOM)
         .fixed_pos(egui::pos2(
-            (viewport.width() - toolbar_width) / 2.0,
-            viewport.height() - toolbar_height - secondary_height - margin * 2.0,
+            viewport.center().x,
+            viewport.height() - toolbar_height - margin * 2.0,
         ))
         .order(egui::Order::Foreground)
         .show(ctx, |ui| {
@@ -319,34 +1160,122 @@ fn draw_construction_ui(
                 .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(95, 95, 95)))
                 .corner_radius(6)
                 .show(ui, |ui| {
-                    ui.set_min_size(egui::vec2(toolbar_width, secondary_height));
-                    ui.set_max_width(toolbar_width);
+                    ui.set_width(toolbar_width);
+
+                    ui.add(
+                        egui::TextEdit::singleline(&mut construction.search)
+                            .hint_text("Search objects")
+                            .desired_width(f32::INFINITY),
+                    );
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut construction.continuous, "Continuous placement");
+                        ui.checkbox(&mut construction.replace, "Replace hovered");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut placement.snap_to_tiles, "Snap to tiles");
+                        ui.checkbox(&mut placement.snap_yaw, "Snap rotation");
+                        ui.add_enabled(
+                            !construction.continuous,
+                            egui::Checkbox::new(&mut placement.aim_on_place, "Drag to aim"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut placement.flatten_under_footprint, "Flatten ground");
+                        ui.checkbox(&mut placement.allow_stacking, "Stack on objects");
+                        ui.checkbox(&mut placement.show_footprint, "Show footprint");
+                    });
 
-                    egui::ScrollArea::horizontal()
-                        .auto_shrink([false, true])
-                        .max_width(toolbar_width)
+                    let query = construction.search.trim().to_lowercase();
+
+                    // Group the matching objects by category, keeping the order in which
+                    // categories first appear.
+                    // Types whose model failed to load are listed too, but disabled, with the
+                    // reason on hover.
+                    let mut categories: Vec<(&str, Vec<PaletteEntry>)> = Vec::new();
+                    let entries = types.available.iter().map(|id| (*id, None)).chain(
+                        types
+                            .broken
+                            .iter()
+                            .map(|(id, reason)| (*id, Some(reason.as_str()))),
+                    );
+                    for (id, broken) in entries {
+                        let Some(spec) = types.registry.get(id) else {
+                            continue;
+                        };
+                        if !query.is_empty() && !spec.name.to_lowercase().contains(&query) {
+                            continue;
+                        }
+                        let category = spec.category.as_str();
+                        match categories.iter_mut().find(|(c, _)| *c == category) {
+                            Some((_, ids)) => ids.push((id, broken)),
+                            None => categories.push((category, vec![(id, broken)])),
+                        }
+                    }
+
+                    if categories.is_empty() {
+                        ui.label("No matches");
+                        return;
+                    }
+
+                    egui::ScrollArea::vertical()
+                        .max_height(secondary_max_height)
                         .show(ui, |ui| {
-                            ui.horizontal(|ui| {
-                                for id in types.available.iter().copied() {
-                                    let name = types
-                                        .registry
-                                        .get(id)
-                                        .map(|s| s.name.as_str())
-                                        .unwrap_or("Object");
-
-                                    let is_selected = construction.selected == Some(id);
-                                    if ui
-                                        .add(egui::Button::new(name).selected(is_selected))
-                                        .clicked()
-                                    {
-                                        if is_selected {
-                                            construction.selected = None;
-                                        } else {
-                                            construction.selected = Some(id);
-                                        }
-                                    }
-                                }
-                            });
+                            for (category, ids) in &categories {
+                                let title = if category.is_empty() {
+                                    "Other"
+                                } else {
+                                    category
+                                };
+                                egui::CollapsingHeader::new(title)
+                                    .id_salt(("construction_category", *category))
+                                    .default_open(true)
+                                    .show(ui, |ui| {
+                                        ui.horizontal_wrapped(|ui| {
+                                            for (id, broken) in ids.iter().copied() {
+                                                let name = types
+                                                    .registry
+                                                    .get(id)
+                                                    .map(|s| s.name.as_str())
+                                                    .unwrap_or("Object");
+
+                                                if let Some(reason) = broken {
+                                                    ui.add_enabled(false, egui::Button::new(name))
+                                                        .on_disabled_hover_text(format!(
+                                                            "Model failed to load: {reason}"
+                                                        ));
+                                                    continue;
+                                                }
+
+                                                let is_selected = construction.selected == Some(id);
+                                                let button = match icons.get(&id) {
+                                                    Some(texture) => {
+                                                        egui::Button::image(egui::Image::new((
+                                                            *texture,
+                                                            egui::vec2(
+                                                                PALETTE_ICON_SIZE,
+                                                                PALETTE_ICON_SIZE,
+                                                            ),
+                                                        )))
+                                                    }
+                                                    None => egui::Button::new(name),
+                                                };
+                                                let response = ui.add(button.selected(is_selected));
+                                                let response = if icons.contains_key(&id) {
+                                                    response.on_hover_text(name)
+                                                } else {
+                                                    response
+                                                };
+                                                if response.clicked() {
+                                                    if is_selected {
+                                                        construction.selected = None;
+                                                    } else {
+                                                        construction.selected = Some(id);
+                                                    }
+                                                }
+                                            }
+                                        });
+                                    });
+                            }
                         });
                 });
         });
@@ -366,10 +1295,157 @@ fn draw_construction_ui(
                 .unwrap_or("Object");
             let mut s = String::new();
             s.push_str(&format!("Mode: Construct ({name})\n"));
-            s.push_str("LMB: Place\n");
-            s.push_str("R / F: Rotate (hold Shift for faster)");
+            if construction.replace {
+                s.push_str("LMB: Replace hovered object\n");
+            } else if construction.continuous {
+                s.push_str("LMB (hold): Place along path\n");
+            } else if placement.aim_on_place {
+                s.push_str("LMB (drag): Place and aim\n");
+            } else {
+                s.push_str("LMB: Place\n");
+            }
+            if placement.snap_yaw {
+                s.push_str("R / F: Rotate 90 degrees");
+            } else {
+                s.push_str("R / F: Rotate (hold Shift for faster)");
+            }
+            if let Some(reason) = preview.blocked {
+                s.push_str(&format!("\nBlocked: {reason}"));
+            }
             s
         }
     };
     action_text.0 = text;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use objects::GroundSnap;
+    use terrain::{
+        Biome, BiomeField, HeightSource, TerrainConfig, TerrainSource, TileType, TileTypes,
+    };
+
+    /// Ground rising the given height per unit along X.
+    struct Ramp(f64);
+
+    impl HeightSource for Ramp {
+        fn sample(&self, x: f64, _z: f64) -> f64 {
+            self.0 * x
+        }
+
+        fn height_range(&self) -> (f32, f32) {
+            (-1e6, 1e6)
+        }
+    }
+
+    fn ramp(rise: f64) -> TerrainWorld {
+        let mut terrain = TerrainWorld::new(TerrainConfig::default());
+        let _ = terrain.set_source(TerrainSource::Custom(Box::new(Ramp(rise))));
+        terrain
+    }
+
+    fn spec(collision_radius: f32, max_placement_slope: Option<f32>) -> ObjectTypeSpec {
+        ObjectTypeSpec {
+            name: "hut".to_string(),
+            gltf: String::new(),
+            gltf_variants: Vec::new(),
+            gltf_lod1: None,
+            render_scale: Vec3::ONE,
+            hover_radius: collision_radius,
+            collision_radius: None,
+            scene_offset_local: Vec3::ZERO,
+            category: String::new(),
+            tags: Vec::new(),
+            instanced: false,
+            ground_snap: GroundSnap::Center,
+            align_to_normal: false,
+            protected: false,
+            max_hp: None,
+            max_placement_slope,
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn slope_limit_rejects_only_steeper_ground() {
+        let terrain = ramp(0.5);
+        let at = Vec3::new(3.3, 0.0, -2.1);
+        assert!((terrain.slope_at(at.x, at.z) - 0.5).abs() < 1e-4);
+
+        assert!(too_steep(&terrain, &spec(1.0, Some(0.25)), at));
+        assert!(!too_steep(&terrain, &spec(1.0, Some(0.75)), at));
+        assert!(!too_steep(&terrain, &spec(1.0, None), at));
+        assert!(!too_steep(&ramp(0.0), &spec(1.0, Some(0.0)), at));
+    }
+
+    /// Water below height 0, buildable grass up to 5 and unbuildable cliffs above that.
+    fn shore() -> BiomeConfig {
+        let tile = |name: &str, height_lt, buildable| TileType {
+            name: name.to_string(),
+            color_srgb: (0.5, 0.5, 0.5),
+            height_lt,
+            texture: None,
+            buildable,
+        };
+        let biome = Biome {
+            name: "shore".to_string(),
+            weight: 1.0,
+            tiles: TileTypes {
+                tiles: vec![
+                    tile("water", 0.0, false),
+                    tile("grass", 5.0, true),
+                    tile("cliff", 100.0, false),
+                ],
+            },
+        };
+        BiomeConfig::new(vec![biome], BiomeField::default()).unwrap()
+    }
+
+    #[test]
+    fn footprint_touching_water_is_unbuildable() {
+        let mut terrain = ramp(0.1);
+        // The sea ends where the water tiles do.
+        terrain.config.sea_level = 0.0;
+        let biomes = shore();
+        let hut = spec(1.5, None);
+        let ground = |terrain: &TerrainWorld, biomes, x: f32, scale: f32| {
+            check_ground(
+                terrain,
+                biomes,
+                &hut,
+                Vec3::new(x, 0.0, 2.0),
+                Vec3::splat(scale),
+            )
+        };
+
+        assert_eq!(ground(&terrain, Some(&biomes), 6.0, 1.0), Ok(()));
+        assert_eq!(
+            ground(&terrain, Some(&biomes), -6.0, 1.0),
+            Err(PlacementError::Water)
+        );
+        // Scaled up, its footprint reaches the water from further away.
+        assert_eq!(
+            ground(&terrain, Some(&biomes), 6.0, 5.0),
+            Err(PlacementError::Water)
+        );
+        // Centre on grass, but the western corners reach into the water.
+        assert_eq!(
+            ground(&terrain, Some(&biomes), 1.0, 1.0),
+            Err(PlacementError::Water)
+        );
+        // Unbuildable ground that isn't water has its own reason.
+        assert_eq!(
+            ground(&terrain, Some(&biomes), 60.0, 1.0),
+            Err(PlacementError::Unbuildable)
+        );
+        // Without tile types loaded nothing is off limits, unless the sea itself blocks.
+        assert_eq!(ground(&terrain, None, -6.0, 1.0), Ok(()));
+        terrain.config.water_blocks_placement = true;
+        assert_eq!(
+            ground(&terrain, None, -6.0, 1.0),
+            Err(PlacementError::Water)
+        );
+        assert_eq!(PlacementError::Water.reason(), "can't build on water");
+    }
+}
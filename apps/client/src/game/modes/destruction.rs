@@ -1,41 +1,316 @@
+use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
+use bevy::window::SystemCursorIcon;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
 
 use objects::highlight;
-use objects::system::{HoveredObject, ObjectKind, ObjectTypes};
+use objects::spatial::SpatialHashGrid;
+use objects::system::{CursorHit, HoveredObject, ObjectKind, ObjectScale, ObjectTypes, Selection};
 use terrain::TerrainWorld;
+use terrain::conform::conform_circle;
 use ui::{ToolId, ToolbarActionText, ToolbarRegistry, ToolbarState, ToolbarTool, UiInputCapture};
 
+use crate::game::UpdateSet;
+use crate::game::camera::ScrollCaptured;
+use crate::game::history::{EditAction, EditHistory};
+use crate::game::modes::construction::Stacked;
+
+const MIN_AREA_RADIUS: f32 = 1.0;
+const MAX_AREA_RADIUS: f32 = 40.0;
+
+/// How long the hover ring flashes red after clicking a protected object.
+const REFUSED_FLASH_SECS: f32 = 0.4;
+
+#[derive(Resource)]
+pub struct DestructionSettings {
+    /// Single-click removal needs a second click on the same object within `confirm_window`.
+    pub require_confirm: bool,
+    /// Seconds allowed between the two clicks.
+    pub confirm_window: f32,
+}
+
+impl Default for DestructionSettings {
+    fn default() -> Self {
+        Self {
+            require_confirm: false,
+            confirm_window: 0.5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DestroyBrush {
+    /// Click removes the hovered object.
+    #[default]
+    Single,
+    /// Holding LMB removes everything within `radius` of the cursor.
+    Area,
+}
+
+impl DestroyBrush {
+    fn label(self) -> &'static str {
+        match self {
+            DestroyBrush::Single => "Single",
+            DestroyBrush::Area => "Area",
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct DestructionState {
+    pub brush: DestroyBrush,
+    pub radius: f32,
+    /// Objects removed by the current area stroke; recorded as one history entry on release.
+    stroke: Vec<EditAction>,
+    /// Object clicked once while confirmation is required, and when.
+    pending_confirm: Option<(Entity, f32)>,
+    /// Protected object whose removal was just refused, and until when to flash it.
+    refused: Option<(Entity, f32)>,
+}
+
+impl Default for DestructionState {
+    fn default() -> Self {
+        Self {
+            brush: DestroyBrush::Single,
+            radius: 6.0,
+            stroke: Vec::new(),
+            pending_confirm: None,
+            refused: None,
+        }
+    }
+}
+
+impl DestructionState {
+    fn area_active(&self, toolbar: &ToolbarState) -> bool {
+        toolbar.active_tool == Some(ToolId::Destroy) && self.brush == DestroyBrush::Area
+    }
+}
+
 pub struct DestructionModePlugin;
 
 impl Plugin for DestructionModePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_destruction_toolbar)
+        app.init_resource::<DestructionState>()
+            .init_resource::<DestructionSettings>()
+            .add_systems(Startup, setup_destruction_toolbar)
             .add_systems(
                 Update,
                 (
                     draw_hover_highlight,
                     handle_destruction_click,
+                    handle_area_destroy,
+                    handle_bulk_delete,
+                    draw_area_brush,
                     draw_destruction_ui,
+                    adjust_area_radius
+                        .after(UpdateSet::UiCapture)
+                        .before(UpdateSet::CameraInput),
                 ),
-            );
+            )
+            .add_systems(EguiPrimaryContextPass, draw_destruction_panel);
     }
 }
 
 fn setup_destruction_toolbar(mut registry: ResMut<ToolbarRegistry>) {
-    registry.tools.push(ToolbarTool {
+    registry.register(ToolbarTool {
         id: ToolId::Destroy,
         label: "Destroy".to_string(),
         order: 10,
-        key: Some(KeyCode::Digit2),
+        group: Some("Build".to_string()),
+        key: None,
+        secondary_key: None,
+        cursor: Some(SystemCursorIcon::Grab.into()),
     });
 }
 
-fn draw_destruction_ui(toolbar: Res<ToolbarState>, mut action_text: ResMut<ToolbarActionText>) {
+fn draw_destruction_ui(
+    toolbar: Res<ToolbarState>,
+    selection: Res<Selection>,
+    state: Res<DestructionState>,
+    settings: Res<DestructionSettings>,
+    mut action_text: ResMut<ToolbarActionText>,
+) {
+    if toolbar.active_tool != Some(ToolId::Destroy) {
+        return;
+    }
+
+    let mut s = String::new();
+    s.push_str(&format!("Mode: Destroy ({})\n", state.brush.label()));
+    match state.brush {
+        DestroyBrush::Single if settings.require_confirm => {
+            s.push_str("LMB twice: Remove hovered object\n")
+        }
+        DestroyBrush::Single => s.push_str("LMB: Remove hovered object\n"),
+        DestroyBrush::Area => {
+            s.push_str("LMB drag: Remove everything in the brush\n");
+            s.push_str("Scroll: Brush radius\n");
+        }
+    }
+    if selection.is_empty() {
+        s.push_str("Delete: Remove selection (nothing selected)");
+    } else {
+        s.push_str(&format!(
+            "Delete: Remove selection ({} objects)",
+            selection.0.len()
+        ));
+    }
+    action_text.0 = s;
+}
+
+fn draw_destruction_panel(
+    mut contexts: EguiContexts,
+    toolbar: Res<ToolbarState>,
+    mut state: ResMut<DestructionState>,
+    mut settings: ResMut<DestructionSettings>,
+) {
     if toolbar.active_tool != Some(ToolId::Destroy) {
         return;
     }
 
-    action_text.0 = "Mode: Destroy\nLMB: Remove hovered object".to_string();
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+    let toolbar_width = 360.0;
+    let toolbar_height = 40.0;
+    let margin = 10.0;
+
+    let viewport = ctx.viewport_rect();
+
+    egui::Area::new("destruction_secondary".into())
+        .pivot(egui::Align2::CENTER_BOTTOM)
+        .fixed_pos(egui::pos2(
+            viewport.center().x,
+            viewport.height() - toolbar_height - margin * 2.0,
+        ))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(egui::Color32::from_rgb(45, 45, 45))
+                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(95, 95, 95)))
+                .corner_radius(6)
+                .show(ui, |ui| {
+                    ui.set_width(toolbar_width);
+
+                    ui.horizontal(|ui| {
+                        for brush in [DestroyBrush::Single, DestroyBrush::Area] {
+                            if ui
+                                .add(
+                                    egui::Button::new(brush.label()).selected(state.brush == brush),
+                                )
+                                .clicked()
+                            {
+                                state.brush = brush;
+                            }
+                        }
+                    });
+                    match state.brush {
+                        DestroyBrush::Single => {
+                            ui.checkbox(&mut settings.require_confirm, "Click twice to confirm");
+                        }
+                        DestroyBrush::Area => {
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut state.radius,
+                                    MIN_AREA_RADIUS..=MAX_AREA_RADIUS,
+                                )
+                                .text("Radius"),
+                            );
+                        }
+                    }
+                });
+        });
+}
+
+/// Scrolling resizes the area brush instead of zooming while it's in use.
+fn adjust_area_radius(
+    mut mouse_wheel: MessageReader<MouseWheel>,
+    toolbar: Res<ToolbarState>,
+    ui_capture: Res<UiInputCapture>,
+    mut state: ResMut<DestructionState>,
+    mut scroll_captured: ResMut<ScrollCaptured>,
+) {
+    let active = state.area_active(&toolbar) && !ui_capture.pointer;
+    if scroll_captured.0 != active {
+        scroll_captured.0 = active;
+    }
+
+    let scroll: f32 = mouse_wheel.read().map(|ev| ev.y).sum();
+    if !active || scroll == 0.0 {
+        return;
+    }
+    let factor = (1.0 + scroll * 0.1).clamp(0.5, 2.0);
+    state.radius = (state.radius * factor).clamp(MIN_AREA_RADIUS, MAX_AREA_RADIUS);
+}
+
+fn handle_area_destroy(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    toolbar: Res<ToolbarState>,
+    hit: Res<CursorHit>,
+    ui_capture: Res<UiInputCapture>,
+    grid: Res<SpatialHashGrid>,
+    types: Option<Res<ObjectTypes>>,
+    q_objects: Query<(&Transform, &ObjectKind, &ObjectScale, Has<Stacked>)>,
+    children: Query<&Children>,
+    mut state: ResMut<DestructionState>,
+    mut history: ResMut<EditHistory>,
+) {
+    // The stroke ends when the button is released (or the tool or brush changes).
+    if (!mouse_buttons.pressed(MouseButton::Left) || !state.area_active(&toolbar))
+        && !state.stroke.is_empty()
+    {
+        let stroke = std::mem::take(&mut state.stroke);
+        history.push(stroke);
+    }
+
+    if !state.area_active(&toolbar) || !mouse_buttons.pressed(MouseButton::Left) {
+        return;
+    }
+    if ui_capture.pointer {
+        return;
+    }
+    let (Some(world), Some(types)) = (hit.world, types) else {
+        return;
+    };
+
+    let center = Vec2::new(world.x, world.z);
+    let radius_sq = state.radius * state.radius;
+    for entity in grid.query_candidates(center, state.radius) {
+        // Objects removed on an earlier frame can linger in the index until it catches up.
+        let Ok((transform, kind, scale, stacked)) = q_objects.get(entity) else {
+            continue;
+        };
+        let xz = Vec2::new(transform.translation.x, transform.translation.z);
+        if xz.distance_squared(center) > radius_sq || is_protected(&types, kind) {
+            continue;
+        }
+        state
+            .stroke
+            .push(EditAction::remove(kind, transform, scale, stacked));
+        highlight::despawn_recursive(&mut commands, &children, entity);
+    }
+}
+
+/// The area brush outline, following the ground.
+fn draw_area_brush(
+    mut gizmos: Gizmos,
+    toolbar: Res<ToolbarState>,
+    hit: Res<CursorHit>,
+    terrain: Res<TerrainWorld>,
+    state: Res<DestructionState>,
+) {
+    if !state.area_active(&toolbar) {
+        return;
+    }
+    let Some(world) = hit.world else {
+        return;
+    };
+
+    gizmos.linestrip(
+        conform_circle(&terrain, world.xz(), state.radius, 48, 0.1),
+        Color::srgb(1.0, 0.4, 0.3),
+    );
 }
 
 fn draw_hover_highlight(
@@ -43,6 +318,9 @@ fn draw_hover_highlight(
     hovered: Res<HoveredObject>,
     types: Option<Res<ObjectTypes>>,
     toolbar: Res<ToolbarState>,
+    state: Res<DestructionState>,
+    settings: Res<DestructionSettings>,
+    time: Res<Time>,
     terrain: Res<TerrainWorld>,
     q_objects: Query<(&Transform, &ObjectKind)>,
 ) {
@@ -50,7 +328,7 @@ fn draw_hover_highlight(
         return;
     };
 
-    if toolbar.active_tool != Some(ToolId::Destroy) {
+    if toolbar.active_tool != Some(ToolId::Destroy) || state.brush != DestroyBrush::Single {
         return;
     }
 
@@ -66,41 +344,140 @@ fn draw_hover_highlight(
         return;
     };
 
-    let base_h = terrain.sample_height_at(transform.translation.x, transform.translation.z);
+    let now = time.elapsed_secs();
+    let color = if state
+        .refused
+        .is_some_and(|(e, until)| e == entity && now < until)
+    {
+        Color::srgb(1.0, 0.2, 0.2)
+    } else if state.pending_confirm.is_some_and(|(e, at)| {
+        settings.require_confirm && e == entity && now - at <= settings.confirm_window
+    }) {
+        Color::srgb(1.0, 0.8, 0.2)
+    } else {
+        Color::WHITE
+    };
 
-    gizmos.circle(
-        Isometry3d::new(
-            Vec3::new(
-                transform.translation.x,
-                base_h + 0.1,
-                transform.translation.z,
-            ),
-            Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2),
+    gizmos.linestrip(
+        conform_circle(
+            &terrain,
+            transform.translation.xz(),
+            spec.hover_radius,
+            32,
+            0.1,
         ),
-        spec.hover_radius,
-        Color::WHITE,
+        color,
     );
 }
 
+fn is_protected(types: &ObjectTypes, kind: &ObjectKind) -> bool {
+    types.registry.get(kind.0).is_some_and(|s| s.protected)
+}
+
 fn handle_destruction_click(
     mut commands: Commands,
+    time: Res<Time>,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     toolbar: Res<ToolbarState>,
     hovered: Res<HoveredObject>,
     ui_capture: Res<UiInputCapture>,
+    settings: Res<DestructionSettings>,
+    types: Option<Res<ObjectTypes>>,
+    mut state: ResMut<DestructionState>,
+    q_objects: Query<(&Transform, &ObjectKind, &ObjectScale, Has<Stacked>)>,
     children: Query<&Children>,
+    mut history: ResMut<EditHistory>,
 ) {
     if ui_capture.pointer {
         return;
     }
 
-    if !mouse_buttons.just_pressed(MouseButton::Left) {
+    if !mouse_buttons.just_pressed(MouseButton::Left) || state.brush != DestroyBrush::Single {
+        return;
+    }
+
+    if toolbar.active_tool != Some(ToolId::Destroy) {
         return;
     }
 
-    if toolbar.active_tool == Some(ToolId::Destroy) {
-        if let Some(entity) = hovered.0 {
+    let (Some(entity), Some(types)) = (hovered.0, types) else {
+        return;
+    };
+    let Ok((transform, kind, scale, stacked)) = q_objects.get(entity) else {
+        return;
+    };
+
+    let now = time.elapsed_secs();
+    if is_protected(&types, kind) {
+        if let Some(spec) = types.registry.get(kind.0) {
+            info!("'{}' is protected and can't be destroyed", spec.name);
+        }
+        state.refused = Some((entity, now + REFUSED_FLASH_SECS));
+        return;
+    }
+
+    // The first click only arms the removal.
+    if settings.require_confirm {
+        let confirmed = state
+            .pending_confirm
+            .is_some_and(|(e, at)| e == entity && now - at <= settings.confirm_window);
+        if !confirmed {
+            state.pending_confirm = Some((entity, now));
+            return;
+        }
+        state.pending_confirm = None;
+    }
+
+    history.push(vec![EditAction::remove(kind, transform, scale, stacked)]);
+    highlight::despawn_recursive(&mut commands, &children, entity);
+}
+
+/// Removes every selected object when Delete is pressed.
+///
+/// The spatial index drops them through its `RemovedComponents` pathway, and the selection
+/// itself is pruned the same way.
+fn handle_bulk_delete(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    toolbar: Res<ToolbarState>,
+    selection: Res<Selection>,
+    ui_capture: Res<UiInputCapture>,
+    types: Option<Res<ObjectTypes>>,
+    q_objects: Query<(&Transform, &ObjectKind, &ObjectScale, Has<Stacked>)>,
+    children: Query<&Children>,
+    mut history: ResMut<EditHistory>,
+) {
+    if ui_capture.keyboard {
+        return;
+    }
+
+    if toolbar.active_tool != Some(ToolId::Destroy) || !keys.just_pressed(KeyCode::Delete) {
+        return;
+    }
+
+    let Some(types) = types else {
+        return;
+    };
+
+    if selection.is_empty() {
+        return;
+    }
+
+    let mut removed = Vec::new();
+    let mut kept = 0;
+    for &entity in &selection.0 {
+        // Skip anything that was already despawned this frame.
+        if let Ok((transform, kind, scale, stacked)) = q_objects.get(entity) {
+            if is_protected(&types, kind) {
+                kept += 1;
+                continue;
+            }
+            removed.push(EditAction::remove(kind, transform, scale, stacked));
             highlight::despawn_recursive(&mut commands, &children, entity);
         }
     }
+    if kept > 0 {
+        info!("kept {kept} protected object(s)");
+    }
+    history.push(removed);
 }
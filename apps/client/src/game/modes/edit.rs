@@ -0,0 +1,534 @@
+use bevy::prelude::*;
+use bevy::window::SystemCursorIcon;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use objects::spatial::SpatialHashGrid;
+use objects::system::{
+    CursorHit, HoveredObject, ObjectKind, ObjectScale, ObjectTypes, Selection, heading_of,
+};
+use objects::types::ObjectTypeSpec;
+use terrain::TerrainWorld;
+use terrain::conform::conform_circle;
+use ui::{
+    ToolChanged, ToolId, ToolbarActionText, ToolbarRegistry, ToolbarState, ToolbarTool,
+    UiInputCapture,
+};
+
+use crate::game::history::{EditAction, EditHistory, ObjectPose};
+use crate::game::modes::construction::Stacked;
+
+/// Per-instance scale multipliers are kept within these bounds.
+const MIN_SCALE: f32 = 0.25;
+const MAX_SCALE: f32 = objects::system::MAX_OBJECT_SCALE;
+
+/// How close (in world units) the cursor has to be to a handle to grab it.
+const HANDLE_PICK_RADIUS: f32 = 0.6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EditHandle {
+    Rotate,
+    Scale,
+}
+
+struct EditDrag {
+    handle: EditHandle,
+    /// Cursor heading (rotate) or distance (scale) from the object centre when the drag started.
+    start: f32,
+    original: Transform,
+    original_scale: Vec3,
+}
+
+/// The object being edited and the handle currently being dragged, if any.
+#[derive(Resource, Default)]
+pub struct EditTarget {
+    pub entity: Option<Entity>,
+    drag: Option<EditDrag>,
+    /// The object whose yaw field is being edited, and its pose before the edit, so the whole
+    /// edit goes into the history as one step once the field is let go.
+    yaw_edit: Option<(Entity, ObjectPose)>,
+}
+
+pub struct EditModePlugin;
+
+impl Plugin for EditModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditTarget>()
+            .add_systems(Startup, setup_edit_toolbar)
+            .add_systems(
+                Update,
+                (
+                    (
+                        enter_exit_edit_mode.after(ui::emit_tool_changed),
+                        handle_edit_click,
+                        update_edit_drag,
+                        finish_edit_drag,
+                    )
+                        .chain(),
+                    draw_edit_gizmos,
+                    draw_edit_ui,
+                ),
+            )
+            .add_systems(EguiPrimaryContextPass, draw_edit_properties);
+    }
+}
+
+fn setup_edit_toolbar(mut registry: ResMut<ToolbarRegistry>) {
+    registry.register(ToolbarTool {
+        id: ToolId::Edit,
+        label: "Edit".to_string(),
+        order: 40,
+        group: Some("Edit".to_string()),
+        key: None,
+        secondary_key: None,
+        cursor: Some(SystemCursorIcon::Move.into()),
+    });
+}
+
+/// Radius of the rotate ring around an object, and the world XZ of its scale handle.
+///
+/// The handle sits just outside the ring in the direction the object faces, so it turns with it.
+fn gizmo_layout(spec: &ObjectTypeSpec, transform: &Transform, scale: &ObjectScale) -> (f32, Vec2) {
+    let ring = spec.hover_radius * scale.0.max_element() + 0.75;
+    let heading = heading_of(transform.rotation);
+    let center = Vec2::new(transform.translation.x, transform.translation.z);
+    let handle = center + Vec2::new(heading.sin(), heading.cos()) * (ring + 0.75);
+    (ring, handle)
+}
+
+/// Where an object turned to `yaw` or rescaled in place goes: re-posed on the ground like a new
+/// placement, except that a [`Stacked`] object keeps its height on whatever it sits on.
+pub(crate) fn pose_in_place(
+    spec: &ObjectTypeSpec,
+    terrain: &TerrainWorld,
+    current: &Transform,
+    yaw: f32,
+    scale: Vec3,
+    stacked: bool,
+) -> (Vec3, Quat) {
+    let xz = current.translation.xz();
+    let (mut position, rotation) =
+        objects::system::ground_pose(spec, xz, yaw, scale, |x, z| terrain.sample_height_at(x, z));
+    if stacked {
+        position.y = current.translation.y;
+    }
+    (position, rotation)
+}
+
+/// Heading of `point` as seen from `center`, matching [`heading_of`].
+fn heading_towards(center: Vec2, point: Vec2) -> f32 {
+    let d = point - center;
+    d.x.atan2(d.y)
+}
+
+fn enter_exit_edit_mode(
+    mut tool_changed: MessageReader<ToolChanged>,
+    selection: Res<Selection>,
+    mut target: ResMut<EditTarget>,
+) {
+    for change in tool_changed.read() {
+        // An open drag is reverted by `finish_edit_drag`; only drop the target when leaving.
+        if change.from == Some(ToolId::Edit) && target.drag.is_none() {
+            target.entity = None;
+        }
+        // Entering the tool with a single object selected edits that object.
+        if change.to == Some(ToolId::Edit) && target.entity.is_none() && selection.0.len() == 1 {
+            target.entity = Some(selection.0[0]);
+        }
+    }
+}
+
+fn handle_edit_click(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    toolbar: Res<ToolbarState>,
+    hovered: Res<HoveredObject>,
+    hit: Res<CursorHit>,
+    ui_capture: Res<UiInputCapture>,
+    types: Option<Res<ObjectTypes>>,
+    q_objects: Query<(&Transform, &ObjectKind, &ObjectScale)>,
+    mut target: ResMut<EditTarget>,
+) {
+    if ui_capture.pointer {
+        return;
+    }
+
+    if !mouse_buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    if toolbar.active_tool != Some(ToolId::Edit) {
+        return;
+    }
+
+    let Some(types) = types else {
+        return;
+    };
+
+    // Grab a handle of the current target if the press landed on one.
+    if let (Some(entity), Some(world)) = (target.entity, hit.world)
+        && let Ok((transform, kind, scale)) = q_objects.get(entity)
+        && let Some(spec) = types.registry.get(kind.0)
+    {
+        let center = Vec2::new(transform.translation.x, transform.translation.z);
+        let cursor = Vec2::new(world.x, world.z);
+        let (ring, handle) = gizmo_layout(spec, transform, scale);
+        let dist = cursor.distance(center);
+
+        let grabbed = if cursor.distance(handle) < HANDLE_PICK_RADIUS {
+            Some((EditHandle::Scale, dist))
+        } else if (dist - ring).abs() < HANDLE_PICK_RADIUS {
+            Some((EditHandle::Rotate, heading_towards(center, cursor)))
+        } else {
+            None
+        };
+
+        if let Some((handle, start)) = grabbed {
+            target.drag = Some(EditDrag {
+                handle,
+                start,
+                original: *transform,
+                original_scale: scale.0,
+            });
+            return;
+        }
+    }
+
+    // Otherwise the click picks what to edit; empty ground clears it.
+    target.entity = hovered.0;
+}
+
+fn update_edit_drag(
+    target: Res<EditTarget>,
+    hit: Res<CursorHit>,
+    terrain: Res<TerrainWorld>,
+    types: Option<Res<ObjectTypes>>,
+    mut grid: ResMut<SpatialHashGrid>,
+    mut q_objects: Query<(&mut Transform, &ObjectKind, &mut ObjectScale)>,
+    q_stacked: Query<(), With<Stacked>>,
+) {
+    let (Some(entity), Some(drag)) = (target.entity, &target.drag) else {
+        return;
+    };
+    let Some(types) = types else {
+        return;
+    };
+    // No hit (e.g. cursor over UI): keep last frame's result.
+    let Some(world) = hit.world else {
+        return;
+    };
+    let Ok((mut transform, kind, mut scale)) = q_objects.get_mut(entity) else {
+        return;
+    };
+    let Some(spec) = types.registry.get(kind.0) else {
+        return;
+    };
+
+    let center = Vec2::new(drag.original.translation.x, drag.original.translation.z);
+    let cursor = Vec2::new(world.x, world.z);
+    let stacked = q_stacked.contains(entity);
+
+    match drag.handle {
+        EditHandle::Rotate => {
+            let yaw =
+                heading_of(drag.original.rotation) + heading_towards(center, cursor) - drag.start;
+            let (position, rotation) =
+                pose_in_place(spec, &terrain, &drag.original, yaw, scale.0, stacked);
+            objects::system::move_object(&mut grid, entity, &mut transform, position, rotation);
+        }
+        EditHandle::Scale => {
+            let factor = cursor.distance(center) / drag.start.max(0.01);
+            let new_scale = (drag.original_scale * factor)
+                .clamp(Vec3::splat(MIN_SCALE), Vec3::splat(MAX_SCALE));
+            objects::system::set_object_scale(spec, &mut transform, &mut scale, new_scale);
+            // A bigger footprint can rest differently on uneven ground.
+            let yaw = heading_of(drag.original.rotation);
+            let (position, rotation) =
+                pose_in_place(spec, &terrain, &drag.original, yaw, new_scale, stacked);
+            objects::system::move_object(&mut grid, entity, &mut transform, position, rotation);
+        }
+    }
+}
+
+fn finish_edit_drag(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    toolbar: Res<ToolbarState>,
+    types: Option<Res<ObjectTypes>>,
+    mut grid: ResMut<SpatialHashGrid>,
+    mut target: ResMut<EditTarget>,
+    mut q_objects: Query<(&mut Transform, &ObjectKind, &mut ObjectScale)>,
+    q_stacked: Query<(), With<Stacked>>,
+    mut history: ResMut<EditHistory>,
+) {
+    if target.drag.is_none() {
+        return;
+    }
+
+    let cancelled = toolbar.active_tool != Some(ToolId::Edit);
+    if !cancelled && mouse_buttons.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (Some(entity), Some(drag)) = (target.entity, target.drag.take()) else {
+        return;
+    };
+    if cancelled {
+        target.entity = None;
+    }
+    let Some(types) = types else {
+        return;
+    };
+    let Ok((transform, kind, scale)) = q_objects.get(entity) else {
+        return;
+    };
+    let (type_id, position, scale) = (kind.0, transform.translation, scale.0);
+    let to = ObjectPose {
+        position,
+        rotation: transform.rotation,
+        scale,
+    };
+
+    // Stacked objects overlap what they sit on, as they did when placed.
+    let valid = !cancelled
+        && (q_stacked.contains(entity) || {
+            let mut lens = q_objects.transmute_lens::<(&Transform, &ObjectKind, &ObjectScale)>();
+            objects::system::can_place_non_overlapping_spatial(
+                &types.registry,
+                type_id,
+                position,
+                scale,
+                &grid,
+                &lens.query(),
+                &[entity],
+            )
+        });
+
+    if valid {
+        let from = ObjectPose {
+            position: drag.original.translation,
+            rotation: drag.original.rotation,
+            scale: drag.original_scale,
+        };
+        if to != from {
+            history.push(vec![EditAction::Adjust { type_id, from, to }]);
+        }
+        return;
+    }
+
+    let Some(spec) = types.registry.get(type_id) else {
+        return;
+    };
+    let Ok((mut transform, _, mut scale)) = q_objects.get_mut(entity) else {
+        return;
+    };
+    objects::system::move_object(
+        &mut grid,
+        entity,
+        &mut transform,
+        drag.original.translation,
+        drag.original.rotation,
+    );
+    objects::system::set_object_scale(spec, &mut transform, &mut scale, drag.original_scale);
+}
+
+fn draw_edit_gizmos(
+    mut gizmos: Gizmos,
+    toolbar: Res<ToolbarState>,
+    target: Res<EditTarget>,
+    hit: Res<CursorHit>,
+    types: Option<Res<ObjectTypes>>,
+    terrain: Res<TerrainWorld>,
+    q_objects: Query<(&Transform, &ObjectKind, &ObjectScale)>,
+) {
+    if toolbar.active_tool != Some(ToolId::Edit) {
+        return;
+    }
+    let Some(entity) = target.entity else {
+        return;
+    };
+    let Some(types) = types else {
+        return;
+    };
+    let Ok((transform, kind, scale)) = q_objects.get(entity) else {
+        return;
+    };
+    let Some(spec) = types.registry.get(kind.0) else {
+        return;
+    };
+
+    let (ring, handle) = gizmo_layout(spec, transform, scale);
+    let center = Vec2::new(transform.translation.x, transform.translation.z);
+    let base_h = terrain.sample_height_at(center.x, center.y) + 0.1;
+
+    // Light up whichever handle is being dragged, or would be grabbed by a click.
+    let active = match &target.drag {
+        Some(drag) => Some(drag.handle),
+        None => hit.world.and_then(|w| {
+            let cursor = Vec2::new(w.x, w.z);
+            if cursor.distance(handle) < HANDLE_PICK_RADIUS {
+                Some(EditHandle::Scale)
+            } else if (cursor.distance(center) - ring).abs() < HANDLE_PICK_RADIUS {
+                Some(EditHandle::Rotate)
+            } else {
+                None
+            }
+        }),
+    };
+    let color = |h: EditHandle| {
+        if active == Some(h) {
+            Color::srgb(1.0, 0.85, 0.2)
+        } else {
+            Color::srgb(0.85, 0.85, 0.85)
+        }
+    };
+
+    let flat = Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
+    gizmos.linestrip(
+        conform_circle(&terrain, center, ring, 48, 0.1),
+        color(EditHandle::Rotate),
+    );
+
+    let handle_h = terrain.sample_height_at(handle.x, handle.y) + 0.1;
+    let handle_world = Vec3::new(handle.x, handle_h, handle.y);
+    let dir = (handle - center).normalize_or_zero();
+    let ring_edge = center + dir * ring;
+    gizmos.line(
+        Vec3::new(ring_edge.x, base_h, ring_edge.y),
+        handle_world,
+        color(EditHandle::Scale),
+    );
+    gizmos.circle(
+        Isometry3d::new(handle_world, flat),
+        HANDLE_PICK_RADIUS * 0.5,
+        color(EditHandle::Scale),
+    );
+}
+
+fn draw_edit_ui(
+    toolbar: Res<ToolbarState>,
+    target: Res<EditTarget>,
+    types: Option<Res<ObjectTypes>>,
+    q_objects: Query<(&ObjectKind, &ObjectScale)>,
+    mut action_text: ResMut<ToolbarActionText>,
+) {
+    if toolbar.active_tool != Some(ToolId::Edit) {
+        return;
+    }
+
+    let mut s = String::new();
+    s.push_str("Mode: Edit\n");
+    s.push_str("LMB: Pick object\n");
+    s.push_str("Drag ring: Rotate, drag handle: Scale\n");
+    match target.entity.and_then(|e| q_objects.get(e).ok()) {
+        Some((kind, scale)) => {
+            let name = types
+                .as_ref()
+                .and_then(|t| t.registry.get(kind.0))
+                .map(|s| s.name.as_str())
+                .unwrap_or("Object");
+            s.push_str(&format!("{name} (scale {:.2})", scale.0.max_element()));
+        }
+        None => s.push_str("Nothing picked"),
+    }
+    action_text.0 = s;
+}
+
+/// Type, position and an editable yaw for the picked object, or the selected one if only one
+/// is selected and nothing is picked.
+fn draw_edit_properties(
+    mut contexts: EguiContexts,
+    toolbar: Res<ToolbarState>,
+    mut target: ResMut<EditTarget>,
+    selection: Res<Selection>,
+    types: Option<Res<ObjectTypes>>,
+    terrain: Res<TerrainWorld>,
+    mut grid: ResMut<SpatialHashGrid>,
+    mut q_objects: Query<(&mut Transform, &ObjectKind, &ObjectScale)>,
+    q_stacked: Query<(), With<Stacked>>,
+    mut history: ResMut<EditHistory>,
+) {
+    if toolbar.active_tool != Some(ToolId::Edit) {
+        return;
+    }
+    let entity = match (target.entity, selection.0.as_slice()) {
+        (Some(entity), _) => entity,
+        (None, [only]) => *only,
+        _ => return,
+    };
+    let Some(types) = types else {
+        return;
+    };
+    let Ok((mut transform, kind, scale)) = q_objects.get_mut(entity) else {
+        return;
+    };
+    let Some(spec) = types.registry.get(kind.0) else {
+        return;
+    };
+
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+
+    let position = transform.translation;
+    let yaw = heading_of(transform.rotation)
+        .to_degrees()
+        .rem_euclid(360.0);
+    let mut edited_yaw = yaw;
+    let mut settled = true;
+
+    egui::Window::new("Properties")
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 10.0))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("edit_properties").show(ui, |ui| {
+                ui.label("Type");
+                ui.label(&spec.name);
+                ui.end_row();
+
+                ui.label("Position");
+                ui.label(format!(
+                    "{:.2}, {:.2}, {:.2}",
+                    position.x, position.y, position.z
+                ));
+                ui.end_row();
+
+                ui.label("Yaw");
+                // Disabled mid-drag, where the ring owns the rotation.
+                let response = ui.add_enabled(
+                    target.drag.is_none(),
+                    egui::DragValue::new(&mut edited_yaw).speed(1.0).suffix("°"),
+                );
+                settled = !response.dragged() && !response.has_focus();
+                ui.end_row();
+
+                ui.label("Scale");
+                ui.label(format!("{:.2}", scale.0.max_element()));
+                ui.end_row();
+            });
+        });
+
+    if target.yaw_edit.is_some_and(|(e, _)| e != entity) {
+        target.yaw_edit = None;
+    }
+    if edited_yaw != yaw {
+        if target.yaw_edit.is_none() {
+            target.yaw_edit = Some((entity, ObjectPose::of(&transform, scale)));
+        }
+        // Same as turning it with the rotate ring.
+        let yaw = edited_yaw.rem_euclid(360.0).to_radians();
+        let stacked = q_stacked.contains(entity);
+        let (new_position, rotation) =
+            pose_in_place(spec, &terrain, &transform, yaw, scale.0, stacked);
+        objects::system::move_object(&mut grid, entity, &mut transform, new_position, rotation);
+    }
+    if settled && let Some((_, from)) = target.yaw_edit.take() {
+        let to = ObjectPose::of(&transform, scale);
+        if to != from {
+            history.push(vec![EditAction::Adjust {
+                type_id: kind.0,
+                from,
+                to,
+            }]);
+        }
+    }
+}
@@ -1,2 +1,6 @@
 pub mod construction;
 pub mod destruction;
+pub mod edit;
+pub mod paint;
+pub mod selection;
+pub mod terraform;
@@ -0,0 +1,174 @@
+use bevy::prelude::*;
+use bevy::window::SystemCursorIcon;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use objects::system::CursorHit;
+use terrain::conform::conform_circle;
+use terrain::{BiomeConfig, TerrainWorld};
+use ui::{ToolId, ToolbarActionText, ToolbarRegistry, ToolbarState, ToolbarTool, UiInputCapture};
+
+#[derive(Resource)]
+pub struct PaintState {
+    /// Atlas column to paint (with one biome, the tile index); `None` erases paint.
+    pub column: Option<u32>,
+    pub radius: f32,
+}
+
+impl Default for PaintState {
+    fn default() -> Self {
+        Self {
+            column: Some(0),
+            radius: 4.0,
+        }
+    }
+}
+
+pub struct PaintModePlugin;
+
+impl Plugin for PaintModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PaintState>()
+            .add_systems(Startup, setup_paint_toolbar)
+            .add_systems(Update, (handle_paint_drag, draw_paint_outline))
+            .add_systems(EguiPrimaryContextPass, draw_paint_ui);
+    }
+}
+
+fn setup_paint_toolbar(mut registry: ResMut<ToolbarRegistry>) {
+    registry.register(ToolbarTool {
+        id: ToolId::Paint,
+        label: "Paint".to_string(),
+        order: 35,
+        group: Some("Terrain".to_string()),
+        key: None,
+        secondary_key: None,
+        cursor: Some(SystemCursorIcon::Crosshair.into()),
+    });
+}
+
+fn handle_paint_drag(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    toolbar: Res<ToolbarState>,
+    hit: Res<CursorHit>,
+    ui_capture: Res<UiInputCapture>,
+    state: Res<PaintState>,
+    mut terrain: ResMut<TerrainWorld>,
+) {
+    if toolbar.active_tool != Some(ToolId::Paint)
+        || !mouse_buttons.pressed(MouseButton::Left)
+        || ui_capture.pointer
+    {
+        return;
+    }
+
+    let Some(world) = hit.world else {
+        return;
+    };
+    // Repainting the same tiles is a no-op, so holding still doesn't keep remeshing.
+    terrain.paint_tiles(Vec2::new(world.x, world.z), state.radius, state.column);
+}
+
+fn draw_paint_outline(
+    mut gizmos: Gizmos,
+    toolbar: Res<ToolbarState>,
+    hit: Res<CursorHit>,
+    terrain: Res<TerrainWorld>,
+    state: Res<PaintState>,
+) {
+    if toolbar.active_tool != Some(ToolId::Paint) {
+        return;
+    }
+
+    let Some(world) = hit.world else {
+        return;
+    };
+
+    let color = match state.column {
+        Some(_) => Color::srgb(1.0, 0.85, 0.3),
+        None => Color::srgb(0.8, 0.8, 0.8),
+    };
+
+    gizmos.linestrip(
+        conform_circle(&terrain, world.xz(), state.radius, 48, 0.1),
+        color,
+    );
+}
+
+fn draw_paint_ui(
+    mut contexts: EguiContexts,
+    toolbar: Res<ToolbarState>,
+    biomes: Option<Res<BiomeConfig>>,
+    mut state: ResMut<PaintState>,
+    mut action_text: ResMut<ToolbarActionText>,
+) {
+    if toolbar.active_tool != Some(ToolId::Paint) {
+        return;
+    }
+
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+    let toolbar_width = 360.0;
+    let toolbar_height = 40.0;
+    let margin = 10.0;
+
+    let viewport = ctx.viewport_rect();
+
+    egui::Area::new("paint_secondary".into())
+        .pivot(egui::Align2::CENTER_BOTTOM)
+        .fixed_pos(egui::pos2(
+            viewport.center().x,
+            viewport.height() - toolbar_height - margin * 2.0,
+        ))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(egui::Color32::from_rgb(45, 45, 45))
+                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(95, 95, 95)))
+                .corner_radius(6)
+                .show(ui, |ui| {
+                    ui.set_width(toolbar_width);
+
+                    ui.horizontal_wrapped(|ui| {
+                        // One swatch per atlas column, coloured as the first biome shows it.
+                        if let Some(biomes) = biomes.as_deref() {
+                            for column in 0..biomes.column_count() {
+                                let (r, g, b) =
+                                    biomes.color_srgb(biomes.column_height(column), 0.0);
+                                let fill = egui::Color32::from_rgb(
+                                    (r * 255.0) as u8,
+                                    (g * 255.0) as u8,
+                                    (b * 255.0) as u8,
+                                );
+                                let column = column as u32;
+                                let button = egui::Button::new(
+                                    egui::RichText::new(format!("{column}"))
+                                        .color(egui::Color32::BLACK),
+                                )
+                                .fill(fill)
+                                .selected(state.column == Some(column));
+                                if ui.add(button).clicked() {
+                                    state.column = Some(column);
+                                }
+                            }
+                        }
+                        if ui
+                            .add(egui::Button::new("Erase").selected(state.column.is_none()))
+                            .clicked()
+                        {
+                            state.column = None;
+                        }
+                    });
+                    ui.add(egui::Slider::new(&mut state.radius, 1.0..=40.0).text("Radius"));
+                });
+        });
+
+    let mut s = String::new();
+    match state.column {
+        Some(column) => s.push_str(&format!("Mode: Paint (tile {column})\n")),
+        None => s.push_str("Mode: Paint (erase)\n"),
+    }
+    s.push_str("LMB drag: Paint tiles");
+    action_text.0 = s;
+}
@@ -0,0 +1,410 @@
+use bevy::prelude::*;
+
+use objects::spatial::SpatialHashGrid;
+use objects::system::{CursorHit, HoveredObject, ObjectKind, ObjectScale, ObjectTypes, Selection};
+use terrain::TerrainWorld;
+use terrain::conform::conform_circle;
+use ui::{ToolId, ToolbarActionText, ToolbarRegistry, ToolbarState, ToolbarTool, UiInputCapture};
+
+use crate::game::modes::construction::Stacked;
+
+/// An in-progress drag of the whole selection.
+struct SelectionDrag {
+    /// (entity, offset from the drag anchor, transform before the drag started)
+    members: Vec<(Entity, Vec3, Transform)>,
+}
+
+#[derive(Resource, Default)]
+pub struct SelectionMove {
+    drag: Option<SelectionDrag>,
+}
+
+#[derive(Resource)]
+pub struct SelectionSettings {
+    /// Longest gap, in seconds, between two clicks on one tile for them to count as a
+    /// double-click.
+    pub double_click_secs: f32,
+}
+
+impl Default for SelectionSettings {
+    fn default() -> Self {
+        Self {
+            double_click_secs: 0.75,
+        }
+    }
+}
+
+/// Sent when the same tile is clicked twice in quick succession with the select tool.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct TileDoubleClicked {
+    pub tile: IVec2,
+    pub world: Vec3,
+}
+
+/// The last click, for spotting double-clicks.
+#[derive(Resource, Default)]
+struct DoubleClickState {
+    last: Option<(IVec2, f32)>,
+}
+
+pub struct SelectionModePlugin;
+
+impl Plugin for SelectionModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectionMove>()
+            .init_resource::<SelectionSettings>()
+            .init_resource::<DoubleClickState>()
+            .add_message::<TileDoubleClicked>()
+            .add_systems(Startup, setup_selection_toolbar)
+            .add_systems(
+                Update,
+                (
+                    (
+                        handle_selection_click,
+                        update_selection_drag,
+                        finish_selection_drag,
+                    )
+                        .chain(),
+                    draw_selection_highlight,
+                    draw_selection_ui,
+                    log_tile_double_clicks,
+                ),
+            );
+    }
+}
+
+fn setup_selection_toolbar(mut registry: ResMut<ToolbarRegistry>) {
+    registry.register(ToolbarTool {
+        id: ToolId::Select,
+        label: "Select".to_string(),
+        order: 20,
+        group: Some("Edit".to_string()),
+        key: None,
+        secondary_key: None,
+        cursor: None,
+    });
+}
+
+fn draw_selection_ui(
+    toolbar: Res<ToolbarState>,
+    selection: Res<Selection>,
+    mut action_text: ResMut<ToolbarActionText>,
+) {
+    if toolbar.active_tool != Some(ToolId::Select) {
+        return;
+    }
+
+    let mut s = String::new();
+    s.push_str("Mode: Select\n");
+    s.push_str("LMB: Select hovered (Shift: add/remove)\n");
+    s.push_str("LMB drag on selection: Move\n");
+    if selection.is_empty() {
+        s.push_str("Nothing selected");
+    } else {
+        s.push_str(&format!("{} selected", selection.0.len()));
+    }
+    action_text.0 = s;
+}
+
+fn handle_selection_click(
+    time: Res<Time>,
+    terrain: Res<TerrainWorld>,
+    settings: Res<SelectionSettings>,
+    mut double_click: ResMut<DoubleClickState>,
+    mut double_clicked: MessageWriter<TileDoubleClicked>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    toolbar: Res<ToolbarState>,
+    hovered: Res<HoveredObject>,
+    hit: Res<CursorHit>,
+    ui_capture: Res<UiInputCapture>,
+    q_objects: Query<&Transform, With<ObjectKind>>,
+    mut selection: ResMut<Selection>,
+    mut moving: ResMut<SelectionMove>,
+) {
+    if ui_capture.pointer {
+        return;
+    }
+
+    if !mouse_buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    if toolbar.active_tool != Some(ToolId::Select) {
+        return;
+    }
+
+    if let Some(world) = hit.world {
+        let tile = terrain.world_to_tile_coord(world.x, world.z);
+        let now = time.elapsed_secs();
+        let repeat = double_click
+            .last
+            .is_some_and(|(t, at)| t == tile && now - at <= settings.double_click_secs);
+        if repeat {
+            double_clicked.write(TileDoubleClicked { tile, world });
+            // A third click starts a new pair rather than firing again.
+            double_click.last = None;
+        } else {
+            double_click.last = Some((tile, now));
+        }
+    }
+
+    let shift = keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight);
+
+    let Some(entity) = hovered.0 else {
+        // Clicking empty ground clears the selection (unless extending it).
+        if !shift && !selection.is_empty() {
+            selection.0.clear();
+        }
+        return;
+    };
+
+    if shift {
+        selection.toggle(entity);
+        return;
+    }
+
+    // Pressing an unselected object only selects it, so a click can't also nudge it.
+    if !selection.contains(entity) {
+        selection.0.clear();
+        selection.0.push(entity);
+        return;
+    }
+
+    // Pressing on a selected object starts dragging the whole selection.
+    let Some(anchor) = hit.world else {
+        return;
+    };
+    let members: Vec<(Entity, Vec3, Transform)> = selection
+        .0
+        .iter()
+        .filter_map(|&e| {
+            let t = q_objects.get(e).ok()?;
+            Some((e, t.translation - anchor, *t))
+        })
+        .collect();
+    if members.is_empty() {
+        return;
+    }
+
+    moving.drag = Some(SelectionDrag { members });
+}
+
+fn log_tile_double_clicks(mut double_clicked: MessageReader<TileDoubleClicked>) {
+    for ev in double_clicked.read() {
+        debug!("tile {} double-clicked at {}", ev.tile, ev.world);
+    }
+}
+
+fn update_selection_drag(
+    moving: Res<SelectionMove>,
+    hit: Res<CursorHit>,
+    terrain: Res<TerrainWorld>,
+    types: Option<Res<ObjectTypes>>,
+    mut grid: ResMut<SpatialHashGrid>,
+    mut q_transforms: Query<(&mut Transform, &ObjectKind, &ObjectScale)>,
+) {
+    let Some(drag) = &moving.drag else {
+        return;
+    };
+    let Some(types) = types else {
+        return;
+    };
+    // No hit (e.g. cursor over UI): leave the selection where it was last frame.
+    let Some(world) = hit.world else {
+        return;
+    };
+
+    for (entity, offset, _) in &drag.members {
+        let Ok((mut transform, kind, scale)) = q_transforms.get_mut(*entity) else {
+            continue;
+        };
+        let Some(spec) = types.registry.get(kind.0) else {
+            continue;
+        };
+        let (position, rotation) = objects::system::ground_pose(
+            spec,
+            Vec2::new(world.x + offset.x, world.z + offset.z),
+            objects::system::heading_of(transform.rotation),
+            scale.0,
+            |x, z| terrain.sample_height_at(x, z),
+        );
+        objects::system::move_object(&mut grid, *entity, &mut transform, position, rotation);
+    }
+}
+
+fn finish_selection_drag(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    toolbar: Res<ToolbarState>,
+    types: Option<Res<ObjectTypes>>,
+    mut grid: ResMut<SpatialHashGrid>,
+    mut moving: ResMut<SelectionMove>,
+    mut q_objects: Query<(&mut Transform, &ObjectKind, &ObjectScale)>,
+) {
+    if moving.drag.is_none() {
+        return;
+    }
+
+    let cancelled = toolbar.active_tool != Some(ToolId::Select);
+    if !cancelled && mouse_buttons.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(drag) = moving.drag.take() else {
+        return;
+    };
+
+    // The selection moves rigidly, so its members can't newly overlap each other; only check
+    // against everything else.
+    let excluded: Vec<Entity> = drag.members.iter().map(|(e, _, _)| *e).collect();
+    let valid = match &types {
+        Some(types) if !cancelled => {
+            let q_objects = q_objects.as_readonly();
+            drag.members.iter().all(|(entity, _, _)| {
+                let Ok((transform, kind, scale)) = q_objects.get(*entity) else {
+                    return true;
+                };
+                objects::system::can_place_non_overlapping_spatial(
+                    &types.registry,
+                    kind.0,
+                    transform.translation,
+                    scale.0,
+                    &grid,
+                    &q_objects,
+                    &excluded,
+                )
+            })
+        }
+        _ => false,
+    };
+
+    if valid {
+        // Moved objects were put down on the ground, off whatever they were stacked on.
+        for (entity, _, _) in &drag.members {
+            commands.entity(*entity).try_remove::<Stacked>();
+        }
+        return;
+    }
+
+    // Snap everything back to where the drag started.
+    for (entity, _, original) in &drag.members {
+        if let Ok((mut transform, _, _)) = q_objects.get_mut(*entity) {
+            objects::system::move_object(
+                &mut grid,
+                *entity,
+                &mut transform,
+                original.translation,
+                original.rotation,
+            );
+        }
+    }
+}
+
+fn draw_selection_highlight(
+    mut gizmos: Gizmos,
+    selection: Res<Selection>,
+    types: Option<Res<ObjectTypes>>,
+    terrain: Res<TerrainWorld>,
+    q_objects: Query<(&Transform, &ObjectKind)>,
+) {
+    let Some(types) = types else {
+        return;
+    };
+
+    for &entity in &selection.0 {
+        let Ok((transform, kind)) = q_objects.get(entity) else {
+            continue;
+        };
+        let Some(spec) = types.registry.get(kind.0) else {
+            continue;
+        };
+
+        gizmos.linestrip(
+            conform_circle(
+                &terrain,
+                transform.translation.xz(),
+                spec.hover_radius,
+                32,
+                0.1,
+            ),
+            Color::srgb(1.0, 0.85, 0.2),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::message::Messages;
+    use std::time::Duration;
+    use terrain::TerrainConfig;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(TerrainWorld::new(TerrainConfig::default()))
+            .init_resource::<SelectionSettings>()
+            .init_resource::<DoubleClickState>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(ToolbarState {
+                active_tool: Some(ToolId::Select),
+            })
+            .init_resource::<HoveredObject>()
+            .init_resource::<CursorHit>()
+            .init_resource::<UiInputCapture>()
+            .init_resource::<Selection>()
+            .init_resource::<SelectionMove>()
+            .add_message::<TileDoubleClicked>()
+            .add_systems(Update, handle_selection_click);
+        app
+    }
+
+    /// Clicks at `world` `after` seconds after the previous click, returning the double-clicks
+    /// it sent.
+    fn click(app: &mut App, world: Vec3, after: f32) -> Vec<TileDoubleClicked> {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(after));
+        app.world_mut().resource_mut::<CursorHit>().world = Some(world);
+        let mut buttons = app.world_mut().resource_mut::<ButtonInput<MouseButton>>();
+        buttons.reset_all();
+        buttons.press(MouseButton::Left);
+        app.update();
+        app.world()
+            .resource::<Messages<TileDoubleClicked>>()
+            .iter_current_update_messages()
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn double_click_fires_only_within_the_window() {
+        let mut app = app();
+        let here = Vec3::new(0.5, 0.0, 0.5);
+        let next_tile = Vec3::new(10.5, 0.0, 0.5);
+
+        assert!(click(&mut app, here, 1.0).is_empty());
+        let fired = click(&mut app, here, 0.5);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].tile, IVec2::ZERO);
+        assert_eq!(fired[0].world, here);
+        // A third quick click starts a new pair.
+        assert!(click(&mut app, here, 0.1).is_empty());
+
+        // Too slow.
+        assert!(click(&mut app, here, 2.0).is_empty());
+        assert!(click(&mut app, here, 1.0).is_empty());
+
+        // Quick, but on another tile.
+        assert!(click(&mut app, next_tile, 2.0).is_empty());
+        assert!(click(&mut app, here, 0.2).is_empty());
+
+        app.world_mut()
+            .resource_mut::<SelectionSettings>()
+            .double_click_secs = 1.5;
+        assert!(click(&mut app, here, 2.0).is_empty());
+        assert_eq!(click(&mut app, here, 1.0).len(), 1);
+    }
+}
@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+use bevy::window::SystemCursorIcon;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use objects::system::CursorHit;
+use terrain::TerrainWorld;
+use terrain::conform::conform_circle;
+use ui::{ToolId, ToolbarActionText, ToolbarRegistry, ToolbarState, ToolbarTool, UiInputCapture};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BrushMode {
+    #[default]
+    Raise,
+    Lower,
+    Flatten,
+}
+
+impl BrushMode {
+    fn label(self) -> &'static str {
+        match self {
+            BrushMode::Raise => "Raise",
+            BrushMode::Lower => "Lower",
+            BrushMode::Flatten => "Flatten",
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct TerraformState {
+    pub mode: BrushMode,
+    pub radius: f32,
+    /// Height change per second at the brush centre (raise/lower only).
+    pub strength: f32,
+    /// Height under the brush centre when the current flatten stroke started.
+    flatten_target: Option<f32>,
+}
+
+impl Default for TerraformState {
+    fn default() -> Self {
+        Self {
+            mode: BrushMode::Raise,
+            radius: 8.0,
+            strength: 4.0,
+            flatten_target: None,
+        }
+    }
+}
+
+pub struct TerraformModePlugin;
+
+impl Plugin for TerraformModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerraformState>()
+            .add_systems(Startup, setup_terraform_toolbar)
+            .add_systems(Update, (handle_terraform_drag, draw_brush_outline))
+            .add_systems(EguiPrimaryContextPass, draw_terraform_ui);
+    }
+}
+
+fn setup_terraform_toolbar(mut registry: ResMut<ToolbarRegistry>) {
+    registry.register(ToolbarTool {
+        id: ToolId::Terraform,
+        label: "Terraform".to_string(),
+        order: 30,
+        group: Some("Terrain".to_string()),
+        key: None,
+        secondary_key: None,
+        cursor: Some(SystemCursorIcon::Crosshair.into()),
+    });
+}
+
+fn handle_terraform_drag(
+    time: Res<Time>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    toolbar: Res<ToolbarState>,
+    hit: Res<CursorHit>,
+    ui_capture: Res<UiInputCapture>,
+    mut state: ResMut<TerraformState>,
+    mut terrain: ResMut<TerrainWorld>,
+) {
+    if toolbar.active_tool != Some(ToolId::Terraform) || !mouse_buttons.pressed(MouseButton::Left) {
+        // Stroke ended; the next flatten samples a fresh target height.
+        if state.flatten_target.is_some() {
+            state.flatten_target = None;
+        }
+        return;
+    }
+
+    if ui_capture.pointer {
+        return;
+    }
+
+    let Some(world) = hit.world else {
+        return;
+    };
+    let center = Vec2::new(world.x, world.z);
+    let radius = state.radius;
+
+    match state.mode {
+        BrushMode::Raise => {
+            terrain.raise_vertices(center, radius, state.strength * time.delta_secs());
+        }
+        BrushMode::Lower => {
+            terrain.raise_vertices(center, radius, -state.strength * time.delta_secs());
+        }
+        BrushMode::Flatten => {
+            let target = *state
+                .flatten_target
+                .get_or_insert_with(|| terrain.sample_height_at(world.x, world.z));
+            terrain.flatten_vertices(center, radius, target);
+        }
+    }
+}
+
+fn draw_brush_outline(
+    mut gizmos: Gizmos,
+    toolbar: Res<ToolbarState>,
+    hit: Res<CursorHit>,
+    terrain: Res<TerrainWorld>,
+    state: Res<TerraformState>,
+) {
+    if toolbar.active_tool != Some(ToolId::Terraform) {
+        return;
+    }
+
+    let Some(world) = hit.world else {
+        return;
+    };
+
+    let color = match state.mode {
+        BrushMode::Raise => Color::srgb(0.3, 1.0, 0.3),
+        BrushMode::Lower => Color::srgb(1.0, 0.4, 0.3),
+        BrushMode::Flatten => Color::srgb(0.4, 0.7, 1.0),
+    };
+
+    gizmos.linestrip(
+        conform_circle(&terrain, world.xz(), state.radius, 48, 0.1),
+        color,
+    );
+}
+
+fn draw_terraform_ui(
+    mut contexts: EguiContexts,
+    toolbar: Res<ToolbarState>,
+    mut state: ResMut<TerraformState>,
+    mut action_text: ResMut<ToolbarActionText>,
+) {
+    if toolbar.active_tool != Some(ToolId::Terraform) {
+        return;
+    }
+
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+    let toolbar_width = 360.0;
+    let toolbar_height = 40.0;
+    let margin = 10.0;
+
+    let viewport = ctx.viewport_rect();
+
+    egui::Area::new("terraform_secondary".into())
+        .pivot(egui::Align2::CENTER_BOTTOM)
+        .fixed_pos(egui::pos2(
+            viewport.center().x,
+            viewport.height() - toolbar_height - margin * 2.0,
+        ))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(egui::Color32::from_rgb(45, 45, 45))
+                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(95, 95, 95)))
+                .corner_radius(6)
+                .show(ui, |ui| {
+                    ui.set_width(toolbar_width);
+
+                    ui.horizontal(|ui| {
+                        for mode in [BrushMode::Raise, BrushMode::Lower, BrushMode::Flatten] {
+                            if ui
+                                .add(egui::Button::new(mode.label()).selected(state.mode == mode))
+                                .clicked()
+                            {
+                                state.mode = mode;
+                            }
+                        }
+                    });
+                    ui.add(egui::Slider::new(&mut state.radius, 2.0..=40.0).text("Radius"));
+                    ui.add(egui::Slider::new(&mut state.strength, 0.5..=20.0).text("Strength"));
+                });
+        });
+
+    let mut s = String::new();
+    s.push_str(&format!("Mode: Terraform ({})\n", state.mode.label()));
+    s.push_str("LMB drag: Apply brush");
+    action_text.0 = s;
+}
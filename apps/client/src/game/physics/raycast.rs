@@ -1,28 +1,59 @@
 use bevy::prelude::*;
 
 pub fn raycast_to_heightfield(terrain: &terrain::world::TerrainWorld, ray: Ray3d) -> Option<Vec3> {
+    let hit = raycast_to_ground(terrain, ray)?;
+
+    // With blocking water the cursor lands on the water surface rather than the lake bed.
+    let sea_level = terrain.config.sea_level;
+    if terrain.config.water_blocks_placement && hit.y < sea_level {
+        let t = (ray.origin.y - sea_level) / (-ray.direction.y);
+        if t >= 0.0 {
+            let p = ray.origin + *ray.direction * t;
+            return Some(Vec3::new(p.x, sea_level, p.z));
+        }
+    }
+
+    Some(hit)
+}
+
+fn raycast_to_ground(terrain: &terrain::world::TerrainWorld, ray: Ray3d) -> Option<Vec3> {
     // Only handle rays pointing downwards.
     if ray.direction.y >= -1e-4 {
         return None;
     }
 
+    let (min_h, max_h) = terrain.height_range();
+
+    // Flat terrain: the heightfield is just a plane, so intersect it directly.
+    if terrain.config.raycast_flat_fast_path && terrain.is_flat() {
+        let t = (ray.origin.y - min_h) / (-ray.direction.y);
+        if t < 0.0 {
+            return None;
+        }
+        let p = ray.origin + *ray.direction * t;
+        return Some(Vec3::new(p.x, min_h, p.z));
+    }
+
     // We step along the ray until we go below the heightfield, then refine with binary search.
     // This avoids needing physics/collision meshes.
-    let max_depth_y = -200.0;
-    let t_max = ((ray.origin.y - max_depth_y) / (-ray.direction.y)).clamp(0.0, 10_000.0);
+    // The march only needs to cover the slab between the lowest and highest possible terrain.
+    let t_max = ((ray.origin.y - min_h) / (-ray.direction.y)).clamp(0.0, 10_000.0);
     if t_max <= 0.0 {
         return None;
     }
+    let t_min = ((ray.origin.y - max_h) / (-ray.direction.y)).clamp(0.0, t_max);
 
     let step_y = (terrain.config.tile_size * 0.5).clamp(0.25, 2.0);
     let step_t = (step_y / (-ray.direction.y)).clamp(0.01, 5.0);
 
-    let mut prev_t = 0.0;
-    let mut prev_p = ray.origin;
+    let mut prev_t = t_min;
+    let mut prev_p = ray.origin + *ray.direction * t_min;
     let mut prev_h = terrain.sample_height_at(prev_p.x, prev_p.z);
 
-    let mut t = step_t;
-    while t <= t_max {
+    // The last step is clamped to `t_max`, which lies at or below the lowest terrain point, so
+    // a thin height range can't be stepped over entirely.
+    let mut t = (t_min + step_t).min(t_max);
+    loop {
         let p = ray.origin + *ray.direction * t;
         let h = terrain.sample_height_at(p.x, p.z);
 
@@ -48,10 +79,14 @@ pub fn raycast_to_heightfield(terrain: &terrain::world::TerrainWorld, ray: Ray3d
             return Some(Vec3::new(hit_p.x, hit_h, hit_p.z));
         }
 
+        if t >= t_max {
+            break;
+        }
+
         prev_t = t;
         prev_p = p;
         prev_h = h;
-        t += step_t;
+        t = (t + step_t).min(t_max);
     }
 
     // If we started below the terrain (rare), treat it as a hit at origin projection.
@@ -61,3 +96,60 @@ pub fn raycast_to_heightfield(terrain: &terrain::world::TerrainWorld, ray: Ray3d
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use terrain::TerrainConfig;
+    use terrain::world::{HeightSource, TerrainSource, TerrainWorld};
+
+    struct Level(f32);
+
+    impl HeightSource for Level {
+        fn sample(&self, _x: f64, _z: f64) -> f64 {
+            self.0 as f64
+        }
+
+        fn height_range(&self) -> (f32, f32) {
+            (self.0, self.0)
+        }
+    }
+
+    fn flat_terrain(height: f32, fast_path: bool) -> TerrainWorld {
+        let mut terrain = TerrainWorld::new(TerrainConfig {
+            raycast_flat_fast_path: fast_path,
+            ..TerrainConfig::default()
+        });
+        let _ = terrain.set_source(TerrainSource::Custom(Box::new(Level(height))));
+        terrain
+    }
+
+    #[test]
+    fn flat_fast_path_hits_the_plane_exactly() {
+        let terrain = flat_terrain(2.5, true);
+        assert!(terrain.is_flat());
+
+        let origin = Vec3::new(3.0, 50.0, -7.0);
+        let direction = Vec3::new(1.0, -2.0, 0.5).normalize();
+        let ray = Ray3d::new(origin, Dir3::new(direction).unwrap());
+        let hit = raycast_to_heightfield(&terrain, ray).unwrap();
+
+        assert_eq!(hit.y, 2.5);
+        let t = (origin.y - 2.5) / -direction.y;
+        let expected = origin + direction * t;
+        assert!(
+            hit.xz().abs_diff_eq(expected.xz(), 1e-4),
+            "{hit} vs {expected}"
+        );
+
+        // Marching gets to the same place, only less precisely.
+        let marched = raycast_to_heightfield(&flat_terrain(2.5, false), ray).unwrap();
+        assert!(marched.abs_diff_eq(hit, 0.05), "{marched} vs {hit}");
+
+        // Rays that can't come down onto the plane miss.
+        let up = Ray3d::new(origin, Dir3::Y);
+        assert_eq!(raycast_to_heightfield(&terrain, up), None);
+        let below = Ray3d::new(Vec3::new(0.0, 1.0, 0.0), Dir3::NEG_Y);
+        assert_eq!(raycast_to_heightfield(&terrain, below), None);
+    }
+}
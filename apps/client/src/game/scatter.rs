@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+use objects::instancing::InstancedMeshes;
+use objects::scatter::ScatterRulesAsset;
+use objects::system::ObjectTypes;
+use terrain::TerrainWorld;
+use terrain::render::ChunkCoord;
+
+/// The decoration rules in use; see [`objects::scatter`].
+#[derive(Resource)]
+pub struct ScatterRulesHandle(pub Handle<ScatterRulesAsset>);
+
+/// Marks decoration spawned by the scatter pass. They're children of their chunk, so they go
+/// away with it.
+#[derive(Component)]
+pub struct ScatterDecoration;
+
+pub struct ScatterPlugin;
+
+impl Plugin for ScatterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_scatter_rules)
+            .add_systems(Update, scatter_new_chunks);
+    }
+}
+
+fn load_scatter_rules(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let handle = asset_server.load("decoration.scatter.ron");
+    commands.insert_resource(ScatterRulesHandle(handle));
+}
+
+/// Decorates chunks as they stream in (or are rebuilt after terraforming).
+///
+/// Chunks that loaded before the rules and object types were ready are caught up once they are.
+fn scatter_new_chunks(
+    mut commands: Commands,
+    mut caught_up: Local<bool>,
+    handle: Option<Res<ScatterRulesHandle>>,
+    rules: Res<Assets<ScatterRulesAsset>>,
+    types: Option<Res<ObjectTypes>>,
+    terrain: Res<TerrainWorld>,
+    asset_server: Res<AssetServer>,
+    instanced: Res<InstancedMeshes>,
+    q_added: Query<(Entity, &ChunkCoord, &Transform), Added<ChunkCoord>>,
+    q_all: Query<(Entity, &ChunkCoord, &Transform)>,
+) {
+    let (Some(handle), Some(types)) = (handle, types) else {
+        return;
+    };
+    let Some(rules) = rules.get(&handle.0) else {
+        return;
+    };
+
+    let chunks: Vec<(Entity, &ChunkCoord, &Transform)> = if *caught_up {
+        q_added.iter().collect()
+    } else {
+        *caught_up = true;
+        q_all.iter().collect()
+    };
+    if chunks.is_empty() {
+        return;
+    }
+
+    let type_ids: Vec<_> = rules
+        .0
+        .iter()
+        .map(|rule| {
+            let id = types.by_name(&rule.object);
+            if id.is_none() {
+                warn_once!("scatter rule names unknown object type '{}'", rule.object);
+            }
+            id
+        })
+        .collect();
+
+    let chunk_world_size = terrain.config.chunk_size as f32 * terrain.config.tile_size;
+    let height_at = |x: f32, z: f32| terrain.sample_height_at(x, z);
+
+    for (chunk, coord, chunk_transform) in chunks {
+        let origin = chunk_transform.translation;
+        let points = objects::scatter::scatter_chunk(
+            &rules.0,
+            terrain.config.seed,
+            coord.0,
+            Vec2::new(origin.x, origin.z),
+            chunk_world_size,
+            height_at,
+        );
+
+        for point in points {
+            let Some(type_id) = type_ids[point.rule] else {
+                continue;
+            };
+            let Some(spec) = types.registry.get(type_id) else {
+                continue;
+            };
+            let (position, rotation) =
+                objects::system::ground_pose(spec, point.xz, point.yaw, Vec3::ONE, height_at);
+            let Some(decoration) = objects::system::spawn_decoration(
+                &mut commands,
+                &types.registry,
+                &asset_server,
+                &instanced,
+                type_id,
+                position - origin,
+                rotation,
+            ) else {
+                continue;
+            };
+            commands.entity(decoration).insert(ScatterDecoration);
+            commands.entity(chunk).add_child(decoration);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use objects::system::{ObjectKind, ObjectScale, ObjectTypes};
+use terrain::{NoiseMode, ReconfigureTerrain, TerrainConfig, TerrainReconfigured, TerrainWorld};
+use ui::{InputAction, KeyBindings, UiInputCapture};
+
+use crate::game::modes::construction::Stacked;
+
+/// The terrain settings window. `draft` holds the edited values until they're applied.
+#[derive(Resource, Default)]
+pub struct TerrainSettingsPanel {
+    pub open: bool,
+    draft: Option<TerrainConfig>,
+}
+
+pub struct TerrainSettingsPlugin;
+
+impl Plugin for TerrainSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TerrainSettingsPanel>()
+            .add_systems(Update, (toggle_terrain_settings, resnap_objects_to_terrain))
+            .add_systems(EguiPrimaryContextPass, draw_terrain_settings);
+    }
+}
+
+fn toggle_terrain_settings(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    ui_capture: Res<UiInputCapture>,
+    mut panel: ResMut<TerrainSettingsPanel>,
+) {
+    if ui_capture.keyboard {
+        return;
+    }
+    if bindings.just_pressed(&keys, InputAction::ToggleTerrainSettings) {
+        panel.open = !panel.open;
+        panel.draft = None;
+    }
+}
+
+fn draw_terrain_settings(
+    mut contexts: EguiContexts,
+    config: Res<TerrainConfig>,
+    mut panel: ResMut<TerrainSettingsPanel>,
+    mut reconfigure: MessageWriter<ReconfigureTerrain>,
+) {
+    if !panel.open {
+        return;
+    }
+
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+
+    let panel = &mut *panel;
+    let draft = panel.draft.get_or_insert_with(|| config.clone());
+    let mut apply = false;
+    let mut reset = false;
+
+    egui::Window::new("Terrain Settings")
+        .open(&mut panel.open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Seed");
+                if ui.add(egui::DragValue::new(&mut draft.seed)).changed() {
+                    draft.seed_text = None;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Seed text");
+                let mut text = draft.seed_text.clone().unwrap_or_default();
+                let edit = egui::TextEdit::singleline(&mut text).hint_text("Any text");
+                if ui.add(edit).changed() {
+                    if text.trim().is_empty() {
+                        draft.seed_text = None;
+                    } else {
+                        draft.seed = TerrainConfig::seed_from_str(&text);
+                        draft.seed_text = Some(text);
+                    }
+                }
+            });
+            ui.add(
+                egui::Slider::new(&mut draft.noise_base_frequency, 0.001..=0.1)
+                    .logarithmic(true)
+                    .text("Frequency"),
+            );
+            ui.add(egui::Slider::new(&mut draft.noise_octaves, 1..=8).text("Octaves"));
+            ui.horizontal(|ui| {
+                ui.label("Noise");
+                ui.radio_value(&mut draft.noise_mode, NoiseMode::Fbm, "fBm");
+                ui.radio_value(&mut draft.noise_mode, NoiseMode::Ridged, "Ridged");
+                ui.radio_value(&mut draft.noise_mode, NoiseMode::Billow, "Billow");
+            });
+            ui.add(egui::Slider::new(&mut draft.noise_persistence, 0.1..=0.9).text("Persistence"));
+            ui.add(egui::Slider::new(&mut draft.height_scale, 0.0..=40.0).text("Height scale"));
+            ui.add(egui::Slider::new(&mut draft.warp_strength, 0.0..=100.0).text("Warp strength"));
+            ui.add(
+                egui::Slider::new(&mut draft.warp_frequency, 0.0005..=0.05)
+                    .logarithmic(true)
+                    .text("Warp frequency"),
+            );
+            ui.add(egui::Slider::new(&mut draft.sea_level, -20.0..=20.0).text("Sea level"));
+
+            ui.horizontal(|ui| {
+                apply = ui.button("Apply").clicked();
+                reset = ui.button("Revert").clicked();
+            });
+        });
+
+    if apply {
+        reconfigure.write(ReconfigureTerrain(draft.clone()));
+    }
+    if reset || !panel.open {
+        panel.draft = None;
+    }
+}
+
+/// Objects keep their world XZ across a reconfigure; move them onto the new ground height.
+///
+/// [`Stacked`] objects rest on other objects rather than the ground, so they're left where they
+/// are.
+fn resnap_objects_to_terrain(
+    mut reconfigured: MessageReader<TerrainReconfigured>,
+    terrain: Res<TerrainWorld>,
+    types: Option<Res<ObjectTypes>>,
+    mut q_objects: Query<(&mut Transform, &ObjectKind, &ObjectScale), Without<Stacked>>,
+) {
+    if reconfigured.read().last().is_none() {
+        return;
+    }
+    let Some(types) = types else {
+        return;
+    };
+
+    for (mut transform, kind, scale) in q_objects.iter_mut() {
+        let Some(spec) = types.registry.get(kind.0) else {
+            continue;
+        };
+        let (position, rotation) = objects::system::ground_pose(
+            spec,
+            Vec2::new(transform.translation.x, transform.translation.z),
+            objects::system::heading_of(transform.rotation),
+            scale.0,
+            |x, z| terrain.sample_height_at(x, z),
+        );
+        if transform.translation != position || transform.rotation != rotation {
+            transform.translation = position;
+            transform.rotation = rotation;
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use objects::system::{HoveredObject, ObjectKind, ObjectTypes};
+use ui::UiInputCapture;
+
+/// Show the hovered object's type name next to the cursor. Off unless the app opts in.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct ShowHoverTooltips(pub bool);
+
+pub struct TooltipPlugin;
+
+impl Plugin for TooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowHoverTooltips>()
+            .add_systems(EguiPrimaryContextPass, draw_hover_tooltip);
+    }
+}
+
+fn draw_hover_tooltip(
+    mut contexts: EguiContexts,
+    show: Res<ShowHoverTooltips>,
+    hovered: Res<HoveredObject>,
+    ui_capture: Res<UiInputCapture>,
+    types: Option<Res<ObjectTypes>>,
+    windows: Query<&Window>,
+    q_objects: Query<&ObjectKind>,
+) {
+    // egui is immediate mode: returning without drawing is what hides the label, so there's
+    // nothing to clear when the hover goes away.
+    if !show.0 || ui_capture.pointer {
+        return;
+    }
+
+    let Some(entity) = hovered.0 else {
+        return;
+    };
+    let Some(types) = types else {
+        return;
+    };
+    let Ok(kind) = q_objects.get(entity) else {
+        return;
+    };
+    let Some(spec) = types.registry.get(kind.0) else {
+        return;
+    };
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+
+    // Offset from the cursor so the label never sits under the pointer.
+    let offset = 16.0;
+
+    egui::Area::new("hover_tooltip".into())
+        .fixed_pos(egui::pos2(cursor.x + offset, cursor.y + offset))
+        .order(egui::Order::Tooltip)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(egui::Color32::from_rgb(45, 45, 45))
+                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(95, 95, 95)))
+                .corner_radius(4)
+                .inner_margin(4)
+                .show(ui, |ui| {
+                    ui.label(&spec.name);
+                });
+        });
+}
@@ -15,18 +15,10 @@ fn main() {
         })
         .add_plugins(DefaultPlugins)
         .add_plugins(EguiPlugin::default())
+        .insert_resource(game::tooltip::ShowHoverTooltips(true))
         .add_plugins(GamePlugin {
-            terrain_config: terrain::TerrainConfig {
-                seed: 12345,
-                chunk_size: 32,
-                tile_size: 2.0,
-                view_distance_chunks: 8,
-                chunk_spawn_budget_per_frame: 32,
-                noise_base_frequency: 0.02,
-                noise_octaves: 4,
-                noise_persistence: 0.5,
-                height_scale: 8.0,
-            },
+            // Overridden by assets/terrain.ron once it loads.
+            terrain_config: terrain::TerrainConfig::default(),
         })
         .run();
 }
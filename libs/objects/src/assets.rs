@@ -4,13 +4,27 @@ use bevy::prelude::*;
 use bevy::reflect::TypePath;
 use serde::Deserialize;
 
+use crate::types::GroundSnap;
+
 #[derive(Asset, TypePath, Debug, Clone)]
 pub struct ObjectTypeDefAsset {
     pub name: String,
     pub gltf: String,
+    pub gltf_variants: Vec<String>,
+    pub gltf_lod1: Option<String>,
     pub render_scale: Vec3,
     pub hover_radius: f32,
+    pub collision_radius: Option<f32>,
     pub scene_offset_local: Vec3,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub instanced: bool,
+    pub ground_snap: GroundSnap,
+    pub align_to_normal: bool,
+    pub protected: bool,
+    pub max_hp: Option<f32>,
+    pub max_placement_slope: Option<f32>,
+    pub icon: Option<String>,
 }
 
 #[derive(Asset, TypePath, Debug, Clone)]
@@ -72,26 +86,81 @@ impl AssetLoader for ObjectTypeDefAssetLoader {
         if def.name.trim().is_empty() {
             return Err("object def has empty name".to_string());
         }
-        if def.gltf.trim().is_empty() {
+        // `gltf` may be left out when `gltf_variants` lists the models; the first one then
+        // stands in for it.
+        let mut models = std::iter::once(def.gltf)
+            .chain(def.gltf_variants)
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty());
+        let Some(gltf) = models.next() else {
             return Err("object def has empty gltf path".to_string());
-        }
+        };
+        let gltf_variants: Vec<String> = models.collect();
         if !def.hover_radius.is_finite() || def.hover_radius <= 0.0 {
             return Err(format!(
                 "object def '{}' has invalid hover_radius={}",
                 def.name, def.hover_radius
             ));
         }
+        if let Some(r) = def.collision_radius
+            && (!r.is_finite() || r <= 0.0)
+        {
+            return Err(format!(
+                "object def '{}' has invalid collision_radius={}",
+                def.name, r
+            ));
+        }
+
+        if let Some(hp) = def.max_hp
+            && (!hp.is_finite() || hp <= 0.0)
+        {
+            return Err(format!(
+                "object def '{}' has invalid max_hp={}",
+                def.name, hp
+            ));
+        }
+        if let Some(slope) = def.max_placement_slope
+            && (!slope.is_finite() || slope < 0.0)
+        {
+            return Err(format!(
+                "object def '{}' has invalid max_placement_slope={}",
+                def.name, slope
+            ));
+        }
 
         Ok(ObjectTypeDefAsset {
             name: def.name,
-            gltf: def.gltf,
+            gltf,
+            gltf_variants,
+            gltf_lod1: def
+                .gltf_lod1
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty()),
             render_scale: Vec3::new(def.scale.0, def.scale.1, def.scale.2),
             hover_radius: def.hover_radius,
+            collision_radius: def.collision_radius,
             scene_offset_local: Vec3::new(
                 def.scene_offset_local.0,
                 def.scene_offset_local.1,
                 def.scene_offset_local.2,
             ),
+            category: def.category.trim().to_string(),
+            tags: def
+                .tags
+                .into_iter()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+            instanced: def.instanced,
+            ground_snap: def.ground_snap,
+            align_to_normal: def.align_to_normal,
+            protected: def.protected,
+            max_hp: def.max_hp,
+            max_placement_slope: def.max_placement_slope,
+            icon: def
+                .icon
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty()),
         })
     }
 
@@ -103,11 +172,36 @@ impl AssetLoader for ObjectTypeDefAssetLoader {
 #[derive(Debug, Deserialize)]
 struct ObjectTypeDefFile {
     name: String,
+    #[serde(default)]
     gltf: String,
+    #[serde(default)]
+    gltf_variants: Vec<String>,
+    #[serde(default)]
+    gltf_lod1: Option<String>,
     #[serde(default = "default_object_scale")]
     scale: Scale3,
     hover_radius: f32,
+    #[serde(default)]
+    collision_radius: Option<f32>,
     scene_offset_local: Vec3File,
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    instanced: bool,
+    #[serde(default)]
+    ground_snap: GroundSnap,
+    #[serde(default)]
+    align_to_normal: bool,
+    #[serde(default)]
+    protected: bool,
+    #[serde(default)]
+    max_hp: Option<f32>,
+    #[serde(default)]
+    max_placement_slope: Option<f32>,
+    #[serde(default)]
+    icon: Option<String>,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize)]
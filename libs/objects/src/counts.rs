@@ -0,0 +1,146 @@
+//! Live objects grouped by type.
+//!
+//! Kept up to date as objects are spawned, despawned or change type, so "how many houses" or
+//! "every farm" doesn't need a scan over every object.
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::ObjectTypeId;
+use crate::system::ObjectKind;
+
+#[derive(Resource, Debug, Default)]
+pub struct ObjectTypeCounts {
+    by_type: HashMap<ObjectTypeId, HashSet<Entity>>,
+    type_of: HashMap<Entity, ObjectTypeId>,
+}
+
+impl ObjectTypeCounts {
+    /// How many live objects have this type.
+    pub fn count(&self, type_id: ObjectTypeId) -> usize {
+        self.by_type.get(&type_id).map_or(0, |set| set.len())
+    }
+
+    /// The live objects of this type, in no particular order.
+    pub fn entities(&self, type_id: ObjectTypeId) -> impl Iterator<Item = Entity> + '_ {
+        self.by_type.get(&type_id).into_iter().flatten().copied()
+    }
+
+    /// Every type with at least one live object, and how many.
+    pub fn iter(&self) -> impl Iterator<Item = (ObjectTypeId, usize)> + '_ {
+        self.by_type
+            .iter()
+            .map(|(type_id, set)| (*type_id, set.len()))
+    }
+
+    fn insert(&mut self, entity: Entity, type_id: ObjectTypeId) {
+        if let Some(previous) = self.type_of.insert(entity, type_id) {
+            if previous == type_id {
+                return;
+            }
+            self.remove_from_type(entity, previous);
+        }
+        self.by_type.entry(type_id).or_default().insert(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(type_id) = self.type_of.remove(&entity) {
+            self.remove_from_type(entity, type_id);
+        }
+    }
+
+    fn remove_from_type(&mut self, entity: Entity, type_id: ObjectTypeId) {
+        if let Some(set) = self.by_type.get_mut(&type_id) {
+            set.remove(&entity);
+            if set.is_empty() {
+                self.by_type.remove(&type_id);
+            }
+        }
+    }
+}
+
+/// Also picks up objects whose `ObjectKind` was replaced with another type.
+pub fn object_counts_changed(
+    mut counts: ResMut<ObjectTypeCounts>,
+    q: Query<(Entity, &ObjectKind), Changed<ObjectKind>>,
+) {
+    for (entity, kind) in &q {
+        counts.insert(entity, kind.0);
+    }
+}
+
+pub fn object_counts_removed(
+    mut counts: ResMut<ObjectTypeCounts>,
+    mut removed: RemovedComponents<ObjectKind>,
+) {
+    for entity in removed.read() {
+        counts.remove(entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TYPES: u16 = 4;
+
+    fn next(state: &mut u32) -> u32 {
+        *state ^= *state << 13;
+        *state ^= *state >> 17;
+        *state ^= *state << 5;
+        *state
+    }
+
+    /// The counts the hard way, by looking at every object.
+    fn scan(app: &mut App, type_id: ObjectTypeId) -> HashSet<Entity> {
+        app.world_mut()
+            .query::<(Entity, &ObjectKind)>()
+            .iter(app.world())
+            .filter(|(_, kind)| kind.0 == type_id)
+            .map(|(e, _)| e)
+            .collect()
+    }
+
+    #[test]
+    fn counts_match_a_full_scan() {
+        let mut app = App::new();
+        app.init_resource::<ObjectTypeCounts>().add_systems(
+            Update,
+            (object_counts_removed, object_counts_changed).chain(),
+        );
+
+        let mut rng = 99;
+        let mut live: Vec<Entity> = Vec::new();
+        for _ in 0..150 {
+            for _ in 0..next(&mut rng) % 4 {
+                let type_id = ObjectTypeId((next(&mut rng) % TYPES as u32) as u16);
+                live.push(app.world_mut().spawn(ObjectKind(type_id)).id());
+            }
+            live.retain(|entity| match next(&mut rng) % 6 {
+                0 => {
+                    app.world_mut().despawn(*entity);
+                    false
+                }
+                1 => {
+                    let type_id = ObjectTypeId((next(&mut rng) % TYPES as u32) as u16);
+                    app.world_mut()
+                        .entity_mut(*entity)
+                        .insert(ObjectKind(type_id));
+                    true
+                }
+                _ => true,
+            });
+            app.update();
+
+            for t in 0..TYPES {
+                let type_id = ObjectTypeId(t);
+                let expected = scan(&mut app, type_id);
+                let counts = app.world().resource::<ObjectTypeCounts>();
+                assert_eq!(counts.count(type_id), expected.len());
+                assert_eq!(counts.entities(type_id).collect::<HashSet<_>>(), expected);
+            }
+            let counts = app.world().resource::<ObjectTypeCounts>();
+            assert_eq!(counts.iter().map(|(_, n)| n).sum::<usize>(), live.len());
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Notifications for objects coming and going.
+//!
+//! These are sent for every placed object however it was spawned or despawned (tools, undo,
+//! paste, damage), so consumers don't need to hook each call site.
+
+use bevy::prelude::*;
+
+use crate::ObjectTypeId;
+use crate::ids::ObjectId;
+use crate::system::{ObjectKind, heading_of};
+
+#[derive(Message, Clone, Copy, Debug)]
+pub struct ObjectPlaced {
+    pub id: ObjectId,
+    pub entity: Entity,
+    pub type_id: ObjectTypeId,
+    pub position: Vec3,
+    pub yaw: f32,
+}
+
+#[derive(Message, Clone, Copy, Debug)]
+pub struct ObjectRemoved {
+    pub id: ObjectId,
+    pub type_id: ObjectTypeId,
+    pub position: Vec3,
+}
+
+/// Sent once an object has its id, so the frame after it was spawned.
+pub fn emit_object_placed(
+    q: Query<(Entity, &ObjectId, &ObjectKind, &Transform), Added<ObjectId>>,
+    mut placed: MessageWriter<ObjectPlaced>,
+) {
+    for (entity, id, kind, transform) in &q {
+        placed.write(ObjectPlaced {
+            id: *id,
+            entity,
+            type_id: kind.0,
+            position: transform.translation,
+            yaw: heading_of(transform.rotation),
+        });
+    }
+}
+
+/// Observer, so the object's components can still be read as it goes. Objects removed before
+/// they were given an id were never announced as placed either, and are skipped.
+pub fn emit_object_removed(
+    remove: On<Remove, ObjectKind>,
+    q: Query<(&ObjectId, &ObjectKind, &Transform)>,
+    mut removed: MessageWriter<ObjectRemoved>,
+) {
+    let Ok((id, kind, transform)) = q.get(remove.entity) else {
+        return;
+    };
+    removed.write(ObjectRemoved {
+        id: *id,
+        type_id: kind.0,
+        position: transform.translation,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::{ObjectIds, assign_object_ids, object_ids_removed};
+
+    #[derive(Resource, Default)]
+    struct Log {
+        placed: Vec<ObjectPlaced>,
+        removed: Vec<ObjectRemoved>,
+    }
+
+    fn collect(
+        mut log: ResMut<Log>,
+        mut placed: MessageReader<ObjectPlaced>,
+        mut removed: MessageReader<ObjectRemoved>,
+    ) {
+        log.placed.extend(placed.read().copied());
+        log.removed.extend(removed.read().copied());
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<ObjectIds>()
+            .init_resource::<Log>()
+            .add_message::<ObjectPlaced>()
+            .add_message::<ObjectRemoved>()
+            .add_observer(emit_object_removed)
+            .add_systems(
+                Update,
+                (
+                    assign_object_ids,
+                    emit_object_placed,
+                    object_ids_removed,
+                    collect,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    fn spawn(app: &mut App) -> Entity {
+        app.world_mut()
+            .spawn((
+                ObjectKind(ObjectTypeId(2)),
+                Transform::from_xyz(3.0, 1.0, -4.0),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn spawn_and_despawn_send_one_message_each() {
+        let mut app = app();
+        let entity = spawn(&mut app);
+        for _ in 0..3 {
+            app.update();
+        }
+        let log = app.world().resource::<Log>();
+        assert_eq!(log.placed.len(), 1);
+        assert!(log.removed.is_empty());
+        let placed = log.placed[0];
+        assert_eq!(placed.entity, entity);
+        assert_eq!(placed.type_id, ObjectTypeId(2));
+        assert_eq!(placed.position, Vec3::new(3.0, 1.0, -4.0));
+
+        app.world_mut().despawn(entity);
+        for _ in 0..3 {
+            app.update();
+        }
+        let log = app.world().resource::<Log>();
+        assert_eq!(log.placed.len(), 1);
+        assert_eq!(log.removed.len(), 1);
+        assert_eq!(log.removed[0].id, placed.id);
+        assert_eq!(log.removed[0].type_id, ObjectTypeId(2));
+        assert_eq!(log.removed[0].position, placed.position);
+    }
+
+    #[test]
+    fn despawned_before_its_id_is_never_announced() {
+        let mut app = app();
+        let entity = spawn(&mut app);
+        app.world_mut().despawn(entity);
+        for _ in 0..3 {
+            app.update();
+        }
+        let log = app.world().resource::<Log>();
+        assert!(log.placed.is_empty());
+        assert!(log.removed.is_empty());
+    }
+}
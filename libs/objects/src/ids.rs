@@ -0,0 +1,242 @@
+//! Durable object ids.
+//!
+//! `Entity` indices are recycled, so they can't identify an object to anything outside this
+//! run (save files, scripts, the network). Every object gets an [`ObjectId`] from a counter
+//! that only goes up, and [`ObjectIds`] maps it back to the live entity.
+//!
+//! Ids are never reused, so the same sequence of placements and removals always hands out the
+//! same ids, whatever Bevy does with entity indices underneath. Not recycling costs only id
+//! space: the maps hold live objects alone, and a `u64` counter won't run out.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::system::ObjectKind;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId(pub u64);
+
+#[derive(Resource, Debug)]
+pub struct ObjectIds {
+    next: u64,
+    by_id: HashMap<ObjectId, Entity>,
+    by_entity: HashMap<Entity, ObjectId>,
+}
+
+impl Default for ObjectIds {
+    fn default() -> Self {
+        Self {
+            // 0 is left unused so it can stand for "no object" in external formats.
+            next: 1,
+            by_id: HashMap::new(),
+            by_entity: HashMap::new(),
+        }
+    }
+}
+
+impl ObjectIds {
+    /// The live entity with this id, if it hasn't been removed.
+    pub fn get(&self, id: ObjectId) -> Option<Entity> {
+        self.by_id.get(&id).copied()
+    }
+
+    pub fn id_of(&self, entity: Entity) -> Option<ObjectId> {
+        self.by_entity.get(&entity).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_id.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+
+    fn insert(&mut self, id: ObjectId, entity: Entity) {
+        self.next = self.next.max(id.0 + 1);
+        if let Some(previous) = self.by_id.insert(id, entity)
+            && previous != entity
+        {
+            warn!("object id {} reused by {entity}; {previous} lost it", id.0);
+            self.by_entity.remove(&previous);
+        }
+        self.by_entity.insert(entity, id);
+    }
+
+    fn allocate(&mut self) -> ObjectId {
+        let id = ObjectId(self.next);
+        self.next += 1;
+        id
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(id) = self.by_entity.remove(&entity) {
+            self.by_id.remove(&id);
+        }
+    }
+}
+
+/// Gives new objects the next id. An object spawned with an [`ObjectId`] already on it (e.g.
+/// restored from a save) keeps it, and the counter moves past it.
+pub fn assign_object_ids(
+    mut commands: Commands,
+    mut ids: ResMut<ObjectIds>,
+    q: Query<(Entity, &ObjectKind, &Transform, Option<&ObjectId>), Added<ObjectKind>>,
+) {
+    // Query order isn't defined, and sorting by entity would tie ids to which entity indices
+    // happened to be free. Objects spawned in the same frame are ordered by type and position
+    // instead, so replaying the same edits gives every object the same id.
+    let mut added: Vec<_> = q.iter().collect();
+    added.sort_by(|(ea, ka, ta, _), (eb, kb, tb, _)| {
+        ka.0.0
+            .cmp(&kb.0.0)
+            .then(ta.translation.x.total_cmp(&tb.translation.x))
+            .then(ta.translation.z.total_cmp(&tb.translation.z))
+            .then(ta.translation.y.total_cmp(&tb.translation.y))
+            .then(ea.cmp(eb))
+    });
+    let added: Vec<(Entity, Option<ObjectId>)> = added
+        .into_iter()
+        .map(|(e, _, _, id)| (e, id.copied()))
+        .collect();
+
+    for (entity, id) in &added {
+        if let Some(id) = id {
+            ids.insert(*id, *entity);
+        }
+    }
+    for (entity, id) in added {
+        if id.is_none() {
+            let id = ids.allocate();
+            ids.insert(id, entity);
+            commands.entity(entity).insert(id);
+        }
+    }
+}
+
+pub fn object_ids_removed(mut ids: ResMut<ObjectIds>, mut removed: RemovedComponents<ObjectKind>) {
+    for e in removed.read() {
+        ids.remove(e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectTypeId;
+    use std::collections::BTreeMap;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<ObjectIds>()
+            .add_systems(Update, (assign_object_ids, object_ids_removed).chain());
+        app
+    }
+
+    /// xorshift32, so the script is the same on every run.
+    fn next(state: &mut u32) -> u32 {
+        *state ^= *state << 13;
+        *state ^= *state >> 17;
+        *state ^= *state << 5;
+        *state
+    }
+
+    /// Places and removes objects for `frames` frames. Returns every object that was given an
+    /// id, keyed by it.
+    fn run_script(app: &mut App, frames: u32) -> BTreeMap<ObjectId, (ObjectTypeId, Vec3)> {
+        let mut rng = 0x2545_f491;
+        let mut seen = BTreeMap::new();
+        for frame in 0..frames {
+            // Several per frame, often sharing a type, so same-frame ordering matters.
+            for _ in 0..frame % 4 + 1 {
+                let type_id = ObjectTypeId((next(&mut rng) % 3) as u16);
+                let x = (next(&mut rng) % 64) as f32;
+                let z = (next(&mut rng) % 64) as f32;
+                app.world_mut()
+                    .spawn((ObjectKind(type_id), Transform::from_xyz(x, 0.0, z)));
+            }
+            // Removals are picked by id, so the choice doesn't depend on entity indices.
+            let mut live: Vec<(ObjectId, Entity)> = app
+                .world()
+                .resource::<ObjectIds>()
+                .by_id
+                .iter()
+                .map(|(id, e)| (*id, *e))
+                .collect();
+            live.sort();
+            for (_, entity) in live {
+                if next(&mut rng).is_multiple_of(3) {
+                    app.world_mut().despawn(entity);
+                }
+            }
+            app.update();
+
+            let mut q = app
+                .world_mut()
+                .query::<(&ObjectId, &ObjectKind, &Transform)>();
+            for (id, kind, transform) in q.iter(app.world()) {
+                let entry = (kind.0, transform.translation);
+                assert_eq!(*seen.entry(*id).or_insert(entry), entry, "{id:?} changed");
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn ids_are_unique_and_map_back() {
+        let mut app = app();
+        let mut owners: HashMap<ObjectId, Entity> = HashMap::new();
+        let mut rng = 7;
+        for _ in 0..200 {
+            for _ in 0..next(&mut rng) % 5 {
+                app.world_mut()
+                    .spawn((ObjectKind(ObjectTypeId(0)), Transform::default()));
+            }
+            let live: Vec<Entity> = app
+                .world_mut()
+                .query_filtered::<Entity, With<ObjectKind>>()
+                .iter(app.world())
+                .collect();
+            for entity in live {
+                if next(&mut rng).is_multiple_of(2) {
+                    app.world_mut().despawn(entity);
+                }
+            }
+            app.update();
+
+            let live: Vec<(Entity, ObjectId)> = app
+                .world_mut()
+                .query::<(Entity, &ObjectId)>()
+                .iter(app.world())
+                .map(|(e, id)| (e, *id))
+                .collect();
+            let ids = app.world().resource::<ObjectIds>();
+            assert_eq!(ids.len(), live.len());
+            for (entity, id) in live {
+                // Entity indices get recycled; ids must not follow them.
+                assert_eq!(*owners.entry(id).or_insert(entity), entity);
+                assert_eq!(ids.get(id), Some(entity));
+                assert_eq!(ids.id_of(entity), Some(id));
+            }
+        }
+        assert!(owners.len() > 100);
+    }
+
+    #[test]
+    fn replaying_a_script_gives_the_same_ids() {
+        let first = run_script(&mut app(), 60);
+
+        // Shift which entity indices are free, so only the script decides the ids.
+        let mut second_app = app();
+        let spares: Vec<Entity> = (0..37)
+            .map(|_| second_app.world_mut().spawn_empty().id())
+            .collect();
+        for entity in spares.into_iter().step_by(2) {
+            second_app.world_mut().despawn(entity);
+        }
+        let second = run_script(&mut second_app, 60);
+
+        assert!(first.len() > 50);
+        assert_eq!(first, second);
+    }
+}
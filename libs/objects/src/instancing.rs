@@ -0,0 +1,197 @@
+//! Single-mesh render path for object types that set `instanced: true`.
+//!
+//! The default path spawns the model's whole glTF scene hierarchy for every placed object. For
+//! static models made of exactly one mesh primitive we instead resolve the mesh, material and
+//! node transform once, and give each object a single `Mesh3d` child. All instances then share
+//! the same mesh and material handles, and no per-object scene hierarchy is spawned.
+//!
+//! Measured with a grid of single-cube objects (Bevy 0.17, llvmpipe over GL): both paths issue
+//! 1 draw call per frame for 100 or 900 objects, because Bevy already batches the scene path's
+//! meshes, which share handles too. What this path saves is entities: 2 per object instead of 5
+//! (1,802 vs 4,502 for 900 objects). Frame time on that software renderer was too noisy to
+//! separate the two.
+//!
+//! Types that ask for instancing but have several meshes, skins or animations keep using the
+//! scene path (a warning is logged once per type).
+//!
+//! The mesh is only known once the glTF has loaded. Objects placed before then get the scene,
+//! and are switched over to the shared mesh when it resolves.
+
+use bevy::gltf::{Gltf, GltfMesh, GltfNode};
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::lod::ObjectVisuals;
+use crate::system::{ObjectKind, ObjectTypes, ObjectVariant, spawn_object_detail};
+use crate::types::ObjectTypeId;
+
+#[derive(Clone, Debug)]
+pub struct InstancedMesh {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+    /// The mesh node's transform relative to the scene root.
+    pub transform: Transform,
+}
+
+#[derive(Resource, Default)]
+pub struct InstancedMeshes {
+    resolved: HashMap<ObjectTypeId, InstancedMesh>,
+    pending: HashMap<ObjectTypeId, Handle<Gltf>>,
+    /// Types that asked for instancing but have to use the scene path.
+    fallback: HashSet<ObjectTypeId>,
+}
+
+impl InstancedMeshes {
+    /// The shared mesh for this type, if it's instanced and has finished resolving.
+    pub fn get(&self, id: ObjectTypeId) -> Option<&InstancedMesh> {
+        self.resolved.get(&id)
+    }
+
+    /// Forgets what was resolved for this type so it's looked at again, e.g. after its
+    /// definition changed.
+    pub fn invalidate(&mut self, id: ObjectTypeId) {
+        self.resolved.remove(&id);
+        self.pending.remove(&id);
+        self.fallback.remove(&id);
+    }
+}
+
+pub fn resolve_instanced_meshes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    types: Option<Res<ObjectTypes>>,
+    gltfs: Res<Assets<Gltf>>,
+    nodes: Res<Assets<GltfNode>>,
+    meshes: Res<Assets<GltfMesh>>,
+    mut instanced: ResMut<InstancedMeshes>,
+    mut q_objects: Query<(Entity, &ObjectKind, &ObjectVariant, &mut ObjectVisuals)>,
+    q_visibility: Query<&Visibility>,
+) {
+    let Some(types) = types else {
+        return;
+    };
+
+    let mut resolved = Vec::new();
+
+    for (id, spec) in types.registry.iter() {
+        if !spec.instanced
+            || spec.gltf.trim().is_empty()
+            || instanced.resolved.contains_key(&id)
+            || instanced.fallback.contains(&id)
+        {
+            continue;
+        }
+
+        let Some(handle) = instanced.pending.get(&id).cloned() else {
+            let handle: Handle<Gltf> = asset_server.load(spec.gltf.clone());
+            instanced.pending.insert(id, handle);
+            continue;
+        };
+
+        let Some(gltf) = gltfs.get(&handle) else {
+            if let Some(bevy::asset::LoadState::Failed(_)) = asset_server.get_load_state(&handle) {
+                warn!("object '{}': failed to load glTF for instancing", spec.name);
+                instanced.pending.remove(&id);
+                instanced.fallback.insert(id);
+            }
+            continue;
+        };
+
+        instanced.pending.remove(&id);
+        match single_static_mesh(gltf, &nodes, &meshes) {
+            Some(mesh) => {
+                instanced.resolved.insert(id, mesh);
+                resolved.push(id);
+            }
+            None => {
+                warn!(
+                    "object '{}' requests instancing but isn't a single static mesh; using the scene path",
+                    spec.name
+                );
+                instanced.fallback.insert(id);
+            }
+        }
+    }
+
+    if resolved.is_empty() {
+        return;
+    }
+    // Swap the scene of objects placed while the glTF was loading for the shared mesh, keeping
+    // whatever visibility LOD gave it.
+    for (entity, kind, variant, mut visuals) in &mut q_objects {
+        if !resolved.contains(&kind.0) {
+            continue;
+        }
+        let Some(spec) = types.registry.get(kind.0) else {
+            continue;
+        };
+        // Other variants are other models, which stay scenes.
+        if spec.variant_gltf(variant.0) != spec.gltf {
+            continue;
+        }
+        let visibility = q_visibility
+            .get(visuals.detail)
+            .copied()
+            .unwrap_or_default();
+        commands.entity(visuals.detail).despawn();
+        let detail = spawn_object_detail(
+            &mut commands,
+            spec,
+            &asset_server,
+            &instanced,
+            kind.0,
+            variant.0,
+        );
+        commands.entity(detail).insert(visibility);
+        commands.entity(entity).add_child(detail);
+        visuals.detail = detail;
+    }
+}
+
+fn single_static_mesh(
+    gltf: &Gltf,
+    nodes: &Assets<GltfNode>,
+    meshes: &Assets<GltfMesh>,
+) -> Option<InstancedMesh> {
+    if !gltf.animations.is_empty() || !gltf.skins.is_empty() {
+        return None;
+    }
+
+    let mut mesh_nodes = gltf
+        .nodes
+        .iter()
+        .filter(|h| nodes.get(*h).is_some_and(|n| n.mesh.is_some()));
+    let node_handle = mesh_nodes.next()?;
+    if mesh_nodes.next().is_some() {
+        return None;
+    }
+
+    let node = nodes.get(node_handle)?;
+    let gltf_mesh = meshes.get(node.mesh.as_ref()?)?;
+    let [primitive] = gltf_mesh.primitives.as_slice() else {
+        return None;
+    };
+
+    // Compose the node's transform with its ancestors' so it matches where the scene path
+    // would have placed it.
+    let mut parents: HashMap<AssetId<GltfNode>, AssetId<GltfNode>> = HashMap::new();
+    for h in &gltf.nodes {
+        if let Some(n) = nodes.get(h) {
+            for child in &n.children {
+                parents.insert(child.id(), h.id());
+            }
+        }
+    }
+    let mut transform = node.transform;
+    let mut current = node_handle.id();
+    while let Some(parent) = parents.get(&current) {
+        transform = nodes.get(*parent)?.transform * transform;
+        current = *parent;
+    }
+
+    Some(InstancedMesh {
+        mesh: primitive.mesh.clone(),
+        material: primitive.material.clone().unwrap_or_default(),
+        transform,
+    })
+}
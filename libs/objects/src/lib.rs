@@ -1,23 +1,58 @@
 pub mod assets;
+pub mod counts;
+pub mod events;
 pub mod highlight;
+pub mod ids;
+pub mod instancing;
+pub mod lod;
+pub mod placeholder;
+pub mod reload;
+pub mod scatter;
 pub mod spatial;
 pub mod system;
 pub mod types;
+pub mod validate;
 
 pub use types::*;
 
 use bevy::prelude::*;
 
-pub struct ObjectsPlugin;
+pub struct ObjectsPlugin {
+    /// Cell size of the [`spatial::SpatialHashGrid`]; see [`spatial::DEFAULT_CELL_SIZE`].
+    pub spatial_cell_size: f32,
+}
+
+impl Default for ObjectsPlugin {
+    fn default() -> Self {
+        Self {
+            spatial_cell_size: spatial::DEFAULT_CELL_SIZE,
+        }
+    }
+}
 
 impl Plugin for ObjectsPlugin {
     fn build(&self, app: &mut App) {
-        app.init_asset::<assets::ObjectTypeDefAsset>()
+        app.init_asset::<scatter::ScatterRulesAsset>()
+            .init_asset_loader::<scatter::ScatterRulesAssetLoader>()
+            .init_asset::<assets::ObjectTypeDefAsset>()
             .init_asset_loader::<assets::ObjectTypeDefAssetLoader>()
             .init_asset::<assets::BinaryAsset>()
             .init_asset_loader::<assets::BinaryAssetLoader>()
+            .add_message::<events::ObjectPlaced>()
+            .add_message::<events::ObjectRemoved>()
+            .add_observer(events::emit_object_removed)
             .init_resource::<system::CursorHit>()
-            .init_resource::<spatial::SpatialHashGrid>()
+            .init_resource::<system::Selection>()
+            .init_resource::<ids::ObjectIds>()
+            .init_resource::<counts::ObjectTypeCounts>()
+            .init_resource::<instancing::InstancedMeshes>()
+            .init_resource::<lod::ObjectRenderSettings>()
+            // Also added by the terrain plugin; LOD follows the same viewer.
+            .init_resource::<terrain::TerrainViewerWorldXz>()
+            .insert_resource(spatial::SpatialHashGrid::with_cell_size(
+                self.spatial_cell_size,
+            ))
+            .init_resource::<validate::ObjectModelChecks>()
             .add_systems(
                 Startup,
                 (system::setup_object_types, system::setup_object_hovered),
@@ -29,7 +64,20 @@ impl Plugin for ObjectsPlugin {
                     spatial::spatial_index_added,
                     spatial::spatial_index_changed,
                     spatial::spatial_index_removed,
+                    (
+                        ids::assign_object_ids,
+                        events::emit_object_placed,
+                        ids::object_ids_removed,
+                    )
+                        .chain(),
+                    (counts::object_counts_removed, counts::object_counts_changed).chain(),
                     system::update_hovered_object,
+                    system::prune_selection,
+                    instancing::resolve_instanced_meshes,
+                    lod::update_object_lod,
+                    placeholder::update_model_placeholders,
+                    reload::reload_object_defs,
+                    validate::check_object_models,
                 ),
             );
     }
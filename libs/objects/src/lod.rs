@@ -0,0 +1,235 @@
+//! Distance-based level of detail for placed objects.
+//!
+//! Every object root carries an [`ObjectVisuals`] naming its model child. Past
+//! `lod1_distance` the model is swapped for the type's `gltf_lod1` scene (spawned lazily the
+//! first time it's needed), and past `object_cull_distance` the whole root is hidden.
+//!
+//! This only ever writes `Visibility`: transforms are untouched, so the spatial index isn't
+//! updated, and coming back into range shows the existing children instead of respawning them.
+//!
+//! Objects are top-level entities rather than children of per-chunk roots, so culling is done
+//! per root here; hiding a root already skips every scene beneath it.
+//!
+//! The viewer is the one terrain streaming follows ([`TerrainViewerWorldXz`]). Every object is
+//! only looked at again once it has moved [`LOD_REFRESH_DISTANCE`]; in between, just objects
+//! that are new or were moved or remodelled are.
+
+use bevy::prelude::*;
+use glam::Vec2;
+use terrain::TerrainViewerWorldXz;
+
+use crate::system::{ObjectKind, ObjectTypes};
+
+/// How far the viewer moves before every object's LOD is checked again. Switches can happen up
+/// to this far past their distance.
+pub const LOD_REFRESH_DISTANCE: f32 = 4.0;
+
+#[derive(Resource, Clone, Debug)]
+pub struct ObjectRenderSettings {
+    /// Beyond this XZ distance from the viewer, types with a `gltf_lod1` show that instead.
+    pub lod1_distance: f32,
+    /// Beyond this XZ distance from the viewer, objects aren't drawn at all.
+    pub object_cull_distance: f32,
+    /// Show a magenta box for objects whose model is missing; see [`crate::placeholder`].
+    /// On by default in debug builds.
+    pub show_missing_placeholders: bool,
+}
+
+impl Default for ObjectRenderSettings {
+    fn default() -> Self {
+        Self {
+            lod1_distance: 150.0,
+            object_cull_distance: 400.0,
+            show_missing_placeholders: cfg!(debug_assertions),
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ObjectVisuals {
+    /// The full-detail model child (scene or instanced mesh).
+    pub detail: Entity,
+    /// The `gltf_lod1` scene child, once it has been spawned.
+    pub lod1: Option<Entity>,
+}
+
+/// An object root as LOD sees it.
+type LodRoot = (
+    Entity,
+    &'static Transform,
+    &'static ObjectKind,
+    &'static mut ObjectVisuals,
+    &'static mut Visibility,
+);
+
+/// Roots that moved or had their visuals rebuilt since the last run.
+type LodRootChanged = Or<(Changed<Transform>, Changed<ObjectVisuals>)>;
+
+pub fn update_object_lod(
+    mut commands: Commands,
+    settings: Res<ObjectRenderSettings>,
+    viewer: Res<TerrainViewerWorldXz>,
+    types: Option<Res<ObjectTypes>>,
+    asset_server: Res<AssetServer>,
+    mut checked_from: Local<Option<Vec2>>,
+    mut q_roots: ParamSet<(Query<LodRoot>, Query<LodRoot, LodRootChanged>)>,
+    mut q_children: Query<&mut Visibility, Without<ObjectKind>>,
+) {
+    let Some(types) = types else {
+        return;
+    };
+
+    let refresh_all = settings.is_changed()
+        || types.is_changed()
+        || checked_from.is_none_or(|at| at.distance(viewer.0) > LOD_REFRESH_DISTANCE);
+    if refresh_all {
+        *checked_from = Some(viewer.0);
+    }
+
+    let cull_sq = settings.object_cull_distance * settings.object_cull_distance;
+    let lod1_sq = settings.lod1_distance * settings.lod1_distance;
+
+    let mut update = |(entity, transform, kind, mut visuals, mut visibility): (
+        Entity,
+        &Transform,
+        &ObjectKind,
+        Mut<ObjectVisuals>,
+        Mut<Visibility>,
+    )| {
+        let d_sq =
+            Vec2::new(transform.translation.x, transform.translation.z).distance_squared(viewer.0);
+
+        let in_range = d_sq <= cull_sq;
+        set_visibility(
+            &mut visibility,
+            if in_range {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+        );
+        if !in_range {
+            return;
+        }
+
+        let lod1_path = types
+            .registry
+            .get(kind.0)
+            .and_then(|spec| Some((spec.gltf_lod1.as_ref()?, spec.scene_offset_local)));
+        let use_lod1 = d_sq > lod1_sq && lod1_path.is_some();
+
+        if use_lod1 && visuals.lod1.is_none() {
+            let Some((path, scene_offset)) = lod1_path else {
+                return;
+            };
+            let scene_handle = asset_server.load(GltfAssetLabel::Scene(0).from_asset(path.clone()));
+            let lod1 = commands
+                .spawn((
+                    SceneRoot(scene_handle),
+                    Transform::from_translation(scene_offset),
+                    Visibility::Inherited,
+                ))
+                .id();
+            commands.entity(entity).add_child(lod1);
+            visuals.lod1 = Some(lod1);
+        }
+
+        if let Ok(mut v) = q_children.get_mut(visuals.detail) {
+            set_visibility(
+                &mut v,
+                if use_lod1 {
+                    Visibility::Hidden
+                } else {
+                    Visibility::Inherited
+                },
+            );
+        }
+        if let Some(lod1) = visuals.lod1
+            && let Ok(mut v) = q_children.get_mut(lod1)
+        {
+            set_visibility(
+                &mut v,
+                if use_lod1 {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                },
+            );
+        }
+    };
+
+    if refresh_all {
+        q_roots.p0().iter_mut().for_each(&mut update);
+    } else {
+        q_roots.p1().iter_mut().for_each(&mut update);
+    }
+}
+
+/// Writes only on change, so idle objects don't trip `Changed<Visibility>` every frame.
+fn set_visibility(visibility: &mut Mut<Visibility>, value: Visibility) {
+    if **visibility != value {
+        **visibility = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ObjectTypeRegistry, ObjectTypeSpec};
+
+    fn visibility(app: &App, entity: Entity) -> Visibility {
+        *app.world().get::<Visibility>(entity).unwrap()
+    }
+
+    fn view_from(app: &mut App, x: f32) {
+        app.world_mut().resource_mut::<TerrainViewerWorldXz>().0 = Vec2::new(x, 0.0);
+        app.update();
+    }
+
+    #[test]
+    fn objects_are_rechecked_when_the_viewer_or_they_move() {
+        let mut registry = ObjectTypeRegistry::default();
+        // No model, so nothing is loaded from disk.
+        let mut spec = ObjectTypeSpec::for_test("hut", 1.0);
+        spec.gltf.clear();
+        let hut = registry.register(spec);
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .insert_resource(ObjectTypes::new(registry, vec![hut], 1.0))
+            .insert_resource(ObjectRenderSettings {
+                object_cull_distance: 100.0,
+                ..default()
+            })
+            .init_resource::<TerrainViewerWorldXz>()
+            .add_systems(Update, update_object_lod);
+        let detail = app.world_mut().spawn(Visibility::Inherited).id();
+        let object = app
+            .world_mut()
+            .spawn((
+                ObjectKind(hut),
+                ObjectVisuals { detail, lod1: None },
+                Transform::from_xyz(101.0, 0.0, 0.0),
+                Visibility::Inherited,
+            ))
+            .id();
+
+        view_from(&mut app, 0.0);
+        assert_eq!(visibility(&app, object), Visibility::Hidden);
+
+        // In range now, but the viewer hasn't gone far enough for a recheck.
+        view_from(&mut app, LOD_REFRESH_DISTANCE * 0.5);
+        assert_eq!(visibility(&app, object), Visibility::Hidden);
+        view_from(&mut app, LOD_REFRESH_DISTANCE * 1.5);
+        assert_eq!(visibility(&app, object), Visibility::Inherited);
+
+        // Moving the object is seen straight away.
+        app.world_mut()
+            .get_mut::<Transform>(object)
+            .unwrap()
+            .translation
+            .x = 200.0;
+        view_from(&mut app, LOD_REFRESH_DISTANCE * 1.5);
+        assert_eq!(visibility(&app, object), Visibility::Hidden);
+    }
+}
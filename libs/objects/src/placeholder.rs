@@ -0,0 +1,83 @@
+//! Stand-in boxes for objects whose model is missing.
+//!
+//! An object type with no glTF path, or one that [`crate::validate`] found broken, would
+//! otherwise be placed invisibly. With `ObjectRenderSettings::show_missing_placeholders` set,
+//! each such object gets a magenta box the size of its collision footprint, so it's obvious
+//! both that something occupies the spot and that its asset needs fixing. The box goes away
+//! again if a hot reload fixes the model.
+
+use bevy::prelude::*;
+
+use crate::lod::ObjectRenderSettings;
+use crate::system::{ObjectKind, ObjectTypes};
+
+/// On an object root showing a placeholder; holds the box child.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ModelPlaceholder(pub Entity);
+
+/// The mesh and material every placeholder shares, created on first use.
+pub struct PlaceholderAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub fn update_model_placeholders(
+    mut commands: Commands,
+    settings: Res<ObjectRenderSettings>,
+    types: Option<Res<ObjectTypes>>,
+    mut assets: Local<Option<PlaceholderAssets>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    q_added: Query<(), Added<ObjectKind>>,
+    q_roots: Query<(Entity, &ObjectKind, Option<&ModelPlaceholder>)>,
+) {
+    let Some(types) = types else {
+        return;
+    };
+    // Only a new object, a type change or a settings change can alter the outcome.
+    if !types.is_changed() && !settings.is_changed() && q_added.is_empty() {
+        return;
+    }
+
+    for (entity, kind, placeholder) in &q_roots {
+        let spec = types.registry.get(kind.0);
+        let missing = spec.is_none_or(|s| s.gltf.trim().is_empty()) || types.is_broken(kind.0);
+        let wanted = missing && settings.show_missing_placeholders;
+
+        match (wanted, placeholder, spec) {
+            (true, None, Some(spec)) => {
+                let shared = assets.get_or_insert_with(|| PlaceholderAssets {
+                    mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+                    material: materials.add(StandardMaterial {
+                        base_color: Color::srgb(1.0, 0.0, 1.0),
+                        unlit: true,
+                        ..default()
+                    }),
+                });
+
+                // Sized in world units, so undo the render scale the root carries.
+                let width = spec.effective_collision_radius() * 2.0;
+                let height = spec.hover_radius.max(0.5) * 2.0;
+                let size =
+                    Vec3::new(width, height, width) / spec.render_scale.max(Vec3::splat(0.001));
+                let placeholder = commands
+                    .spawn((
+                        Mesh3d(shared.mesh.clone()),
+                        MeshMaterial3d(shared.material.clone()),
+                        Transform::from_xyz(0.0, size.y * 0.5, 0.0).with_scale(size),
+                        Visibility::default(),
+                    ))
+                    .id();
+                commands
+                    .entity(entity)
+                    .add_child(placeholder)
+                    .insert(ModelPlaceholder(placeholder));
+            }
+            (false, Some(placeholder), _) => {
+                commands.entity(placeholder.0).despawn();
+                commands.entity(entity).remove::<ModelPlaceholder>();
+            }
+            _ => {}
+        }
+    }
+}
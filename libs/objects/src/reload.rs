@@ -0,0 +1,337 @@
+//! Hot reloading of object definition files.
+//!
+//! Changes are only seen when Bevy is watching the asset folder, e.g. when running with
+//! `--features bevy/file_watcher`. An edited definition replaces its spec in place (same
+//! `ObjectTypeId`) and every placed object of that type is updated, with its model child rebuilt
+//! only if the model itself changed; a file added to `objects/` is registered as a new type.
+
+use bevy::asset::LoadedFolder;
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::assets::ObjectTypeDefAsset;
+use crate::instancing::InstancedMeshes;
+use crate::lod::ObjectVisuals;
+use crate::system::{
+    ObjectKind, ObjectScale, ObjectState, ObjectTypes, ObjectVariant, spawn_object_detail,
+    spec_from_def,
+};
+use crate::types::{ObjectTypeId, ObjectTypeSpec};
+
+/// Definition handles kept alive after startup, and the type each one was registered as.
+#[derive(Resource, Default)]
+pub struct ObjectDefWatch {
+    folder: Option<Handle<LoadedFolder>>,
+    ids: HashMap<AssetId<ObjectTypeDefAsset>, ObjectTypeId>,
+    handles: Vec<Handle<ObjectTypeDefAsset>>,
+    /// Newly discovered files still loading.
+    pending: Vec<Handle<ObjectTypeDefAsset>>,
+}
+
+impl ObjectDefWatch {
+    pub(crate) fn new(folder: Option<Handle<LoadedFolder>>) -> Self {
+        Self {
+            folder,
+            ..default()
+        }
+    }
+
+    pub(crate) fn track(&mut self, handle: Handle<ObjectTypeDefAsset>, id: ObjectTypeId) {
+        self.ids.insert(handle.id(), id);
+        self.handles.push(handle);
+    }
+}
+
+/// The parts of a placed object a reload may rebuild.
+type ReloadedObject = (
+    Entity,
+    &'static ObjectKind,
+    Option<&'static ObjectScale>,
+    Option<&'static ObjectVariant>,
+    &'static mut Transform,
+    &'static mut ObjectVisuals,
+    Option<&'static mut ObjectState>,
+);
+
+pub fn reload_object_defs(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut def_events: MessageReader<AssetEvent<ObjectTypeDefAsset>>,
+    mut folder_events: MessageReader<AssetEvent<LoadedFolder>>,
+    defs: Res<Assets<ObjectTypeDefAsset>>,
+    folders: Res<Assets<LoadedFolder>>,
+    types: Option<ResMut<ObjectTypes>>,
+    watch: Option<ResMut<ObjectDefWatch>>,
+    mut instanced: ResMut<InstancedMeshes>,
+    mut q_objects: Query<ReloadedObject>,
+) {
+    let (Some(mut types), Some(mut watch)) = (types, watch) else {
+        // Drain so startup loading doesn't look like a batch of edits once we're ready.
+        def_events.clear();
+        folder_events.clear();
+        return;
+    };
+
+    // Files added to the folder show up as a modified folder listing.
+    let folder_changed = folder_events.read().any(|ev| {
+        watch
+            .folder
+            .as_ref()
+            .is_some_and(|f| ev.is_modified(f.id()))
+    });
+    if folder_changed && let Some(loaded) = watch.folder.as_ref().and_then(|f| folders.get(f)) {
+        let new: Vec<Handle<ObjectTypeDefAsset>> = loaded
+            .handles
+            .iter()
+            .filter_map(|h| h.clone().try_typed::<ObjectTypeDefAsset>().ok())
+            .filter(|h| !watch.ids.contains_key(&h.id()))
+            .filter(|h| !watch.pending.iter().any(|p| p.id() == h.id()))
+            .collect();
+        watch.pending.extend(new);
+    }
+
+    // Register new files once they've loaded; drop ones that failed.
+    let mut added = false;
+    let pending = std::mem::take(&mut watch.pending);
+    for h in pending {
+        if let Some(def) = defs.get(&h) {
+            let id = types.registry.register(spec_from_def(def));
+            types.available.push(id);
+            types.load_icon(id, &asset_server);
+            info!("object def '{}' added", def.name);
+            watch.track(h, id);
+            added = true;
+        } else if !matches!(
+            asset_server.get_load_state(h.id()),
+            Some(bevy::asset::LoadState::Failed(_))
+        ) {
+            watch.pending.push(h);
+        }
+    }
+
+    let mut changed: HashSet<ObjectTypeId> = HashSet::new();
+    let mut remodel: HashSet<ObjectTypeId> = HashSet::new();
+    for ev in def_events.read() {
+        let AssetEvent::Modified { id: asset_id } = ev else {
+            continue;
+        };
+        let Some(&id) = watch.ids.get(asset_id) else {
+            continue;
+        };
+        let Some(def) = defs.get(*asset_id) else {
+            continue;
+        };
+        let spec = spec_from_def(def);
+        // Edits that don't touch the model (radii, tags, hp...) leave placed models alone, so
+        // they don't pop or reload their scenes.
+        if types
+            .registry
+            .get(id)
+            .is_none_or(|old| model_differs(old, &spec))
+        {
+            instanced.invalidate(id);
+            remodel.insert(id);
+        }
+        types.registry.replace(id, spec);
+        types.load_icon(id, &asset_server);
+        info!("object def '{}' reloaded", def.name);
+        changed.insert(id);
+    }
+
+    if changed.is_empty() && !added {
+        return;
+    }
+
+    types.rebuild_name_index();
+    types.max_hover_radius = types
+        .registry
+        .iter()
+        .map(|(_, s)| s.hover_radius.max(0.1))
+        .fold(0.1, f32::max);
+
+    // Update every placed object of a changed type, rebuilding its model child only if the model
+    // changed. The root entity is kept, so selection, history and the spatial index keep
+    // pointing at it.
+    for (entity, kind, object_scale, variant, mut transform, mut visuals, state) in
+        q_objects.iter_mut()
+    {
+        if !changed.contains(&kind.0) {
+            continue;
+        }
+        let Some(spec) = types.registry.get(kind.0) else {
+            continue;
+        };
+
+        let scale = spec.render_scale * object_scale.copied().unwrap_or_default().0;
+        if transform.scale != scale {
+            transform.scale = scale;
+        }
+
+        // Follow `max_hp` changes, keeping any damage already taken.
+        match (spec.max_hp, state) {
+            (Some(max_hp), Some(mut state)) if state.max_hp != max_hp => {
+                state.max_hp = max_hp;
+                state.hp = state.hp.min(max_hp);
+            }
+            (Some(max_hp), None) => {
+                commands.entity(entity).insert(ObjectState::full(max_hp));
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<ObjectState>();
+            }
+            _ => {}
+        }
+
+        if !remodel.contains(&kind.0) {
+            continue;
+        }
+        commands.entity(visuals.detail).despawn();
+        if let Some(lod1) = visuals.lod1 {
+            commands.entity(lod1).despawn();
+        }
+        // Objects keep the variant they were placed with; it wraps if the list got shorter.
+        let variant = variant.copied().unwrap_or_default().0;
+        let detail = spawn_object_detail(
+            &mut commands,
+            spec,
+            &asset_server,
+            &instanced,
+            kind.0,
+            variant,
+        );
+        commands.entity(entity).add_child(detail);
+        *visuals = ObjectVisuals { detail, lod1: None };
+    }
+}
+
+/// Whether going from `old` to `new` changes what a placed object's model child looks like.
+fn model_differs(old: &ObjectTypeSpec, new: &ObjectTypeSpec) -> bool {
+    old.gltf != new.gltf
+        || old.gltf_variants != new.gltf_variants
+        || old.gltf_lod1 != new.gltf_lod1
+        || old.scene_offset_local != new.scene_offset_local
+        || old.instanced != new.instanced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::spawn_object;
+    use crate::types::{GroundSnap, ObjectTypeRegistry};
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn def() -> ObjectTypeDefAsset {
+        ObjectTypeDefAsset {
+            name: "hut".to_string(),
+            // No model, so nothing is loaded from disk.
+            gltf: String::new(),
+            gltf_variants: Vec::new(),
+            gltf_lod1: None,
+            render_scale: Vec3::ONE,
+            hover_radius: 1.0,
+            collision_radius: None,
+            scene_offset_local: Vec3::ZERO,
+            category: String::new(),
+            tags: Vec::new(),
+            instanced: false,
+            ground_snap: GroundSnap::Center,
+            align_to_normal: false,
+            protected: false,
+            max_hp: None,
+            max_placement_slope: None,
+            icon: None,
+        }
+    }
+
+    fn edit(
+        app: &mut App,
+        handle: &Handle<ObjectTypeDefAsset>,
+        f: impl FnOnce(&mut ObjectTypeDefAsset),
+    ) {
+        let mut defs = app.world_mut().resource_mut::<Assets<ObjectTypeDefAsset>>();
+        f(defs.get_mut(handle).unwrap());
+        // One frame for the asset event to be sent, one for the reload to read it.
+        app.update();
+        app.update();
+    }
+
+    #[test]
+    fn reload_keeps_the_model_unless_it_changed() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<ObjectTypeDefAsset>()
+            .init_resource::<InstancedMeshes>()
+            .add_systems(Update, reload_object_defs);
+
+        let def = def();
+        let mut registry = ObjectTypeRegistry::default();
+        let id = registry.register(spec_from_def(&def));
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<ObjectTypeDefAsset>>()
+            .add(def);
+        let mut watch = ObjectDefWatch::new(None);
+        watch.track(handle.clone(), id);
+        app.insert_resource(ObjectTypes::new(registry, vec![id], 1.0))
+            .insert_resource(watch);
+
+        let object = app
+            .world_mut()
+            .run_system_once(
+                move |mut commands: Commands,
+                      types: Res<ObjectTypes>,
+                      asset_server: Res<AssetServer>,
+                      instanced: Res<InstancedMeshes>| {
+                    spawn_object(
+                        &mut commands,
+                        &types.registry,
+                        &asset_server,
+                        &instanced,
+                        id,
+                        Vec3::new(2.0, 0.0, 3.0),
+                        Quat::IDENTITY,
+                        Vec3::ONE,
+                    )
+                },
+            )
+            .unwrap()
+            .unwrap();
+        app.update();
+        let detail = app.world().get::<ObjectVisuals>(object).unwrap().detail;
+
+        edit(&mut app, &handle, |def| {
+            def.hover_radius = 3.0;
+            def.max_hp = Some(20.0);
+        });
+        let types = app.world().resource::<ObjectTypes>();
+        assert_eq!(types.registry.get(id).unwrap().hover_radius, 3.0);
+        assert_eq!(types.max_hover_radius, 3.0);
+        assert_eq!(
+            app.world().get::<ObjectState>(object),
+            Some(&ObjectState::full(20.0))
+        );
+        assert_eq!(
+            app.world().get::<ObjectVisuals>(object).unwrap().detail,
+            detail
+        );
+        assert_eq!(app.world().get::<ChildOf>(detail).unwrap().parent(), object);
+
+        edit(&mut app, &handle, |def| {
+            def.scene_offset_local = Vec3::Y;
+        });
+        let remodelled = app.world().get::<ObjectVisuals>(object).unwrap().detail;
+        assert_ne!(remodelled, detail);
+        assert!(app.world().get_entity(detail).is_err());
+        assert_eq!(
+            app.world().get::<ChildOf>(remodelled).unwrap().parent(),
+            object
+        );
+        assert_eq!(
+            app.world()
+                .get::<Transform>(remodelled)
+                .unwrap()
+                .translation,
+            Vec3::Y
+        );
+    }
+}
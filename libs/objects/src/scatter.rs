@@ -0,0 +1,204 @@
+//! Procedural decoration scattered over terrain chunks.
+//!
+//! Rules come from a `*.scatter.ron` file. [`scatter_chunk`] turns them into positions for one
+//! chunk; it seeds its RNG from the world seed and the chunk coordinate only, so a chunk gets the
+//! same layout every time it streams in.
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use glam::{IVec2, Vec2};
+use serde::Deserialize;
+
+/// One kind of decoration and where it may go.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ScatterRule {
+    /// Name of the object type to place.
+    pub object: String,
+    /// Expected number of candidates per chunk; the fractional part is a chance of one more.
+    pub density: f32,
+    /// Terrain heights the object may be placed at (inclusive).
+    #[serde(default = "ScatterRule::any_height")]
+    pub height_range: (f32, f32),
+    /// Ground slopes, in degrees, the object may be placed on (inclusive).
+    #[serde(default = "ScatterRule::any_slope")]
+    pub slope_range: (f32, f32),
+    /// Minimum distance between two objects from this rule within a chunk.
+    #[serde(default)]
+    pub min_spacing: f32,
+}
+
+impl ScatterRule {
+    fn any_height() -> (f32, f32) {
+        (f32::MIN, f32::MAX)
+    }
+
+    fn any_slope() -> (f32, f32) {
+        (0.0, 90.0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ScatterRulesFile {
+    rules: Vec<ScatterRule>,
+}
+
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct ScatterRulesAsset(pub Vec<ScatterRule>);
+
+#[derive(Default)]
+pub struct ScatterRulesAssetLoader;
+
+impl AssetLoader for ScatterRulesAssetLoader {
+    type Asset = ScatterRulesAsset;
+    type Settings = ();
+    type Error = String;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| format!("failed to read asset bytes: {e}"))?;
+
+        let text = std::str::from_utf8(&bytes)
+            .map_err(|e| format!("scatter rules were not valid utf-8: {e}"))?;
+
+        let file: ScatterRulesFile =
+            ron::from_str(text).map_err(|e| format!("failed to parse scatter rules ron: {e}"))?;
+
+        for rule in &file.rules {
+            if rule.object.trim().is_empty() {
+                return Err("scatter rule has empty object name".to_string());
+            }
+            if !rule.density.is_finite() || rule.density < 0.0 {
+                return Err(format!(
+                    "scatter rule for '{}' has invalid density={}",
+                    rule.object, rule.density
+                ));
+            }
+            if !rule.min_spacing.is_finite() || rule.min_spacing < 0.0 {
+                return Err(format!(
+                    "scatter rule for '{}' has invalid min_spacing={}",
+                    rule.object, rule.min_spacing
+                ));
+            }
+        }
+
+        Ok(ScatterRulesAsset(file.rules))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        // More specific than plain "ron" so folder loads of object defs don't pick this loader.
+        &["scatter.ron"]
+    }
+}
+
+/// One generated placement.
+#[derive(Clone, Copy, Debug)]
+pub struct ScatterPoint {
+    /// Index of the rule in the rule list.
+    pub rule: usize,
+    pub xz: Vec2,
+    pub yaw: f32,
+}
+
+/// Generates the scatter layout for the chunk at `coord`, covering `chunk_min` to
+/// `chunk_min + chunk_world_size` on XZ.
+///
+/// `height_at` samples the terrain height at a world XZ; it's used for the height and slope
+/// filters. The result depends only on the inputs, so the same chunk always yields the same
+/// points (as long as its terrain hasn't been edited).
+pub fn scatter_chunk(
+    rules: &[ScatterRule],
+    seed: u64,
+    coord: IVec2,
+    chunk_min: Vec2,
+    chunk_world_size: f32,
+    height_at: impl Fn(f32, f32) -> f32,
+) -> Vec<ScatterPoint> {
+    let mut out = Vec::new();
+
+    for (rule_index, rule) in rules.iter().enumerate() {
+        let mut rng = SplitMix64::new(chunk_seed(seed, coord, rule_index));
+
+        let whole = rule.density.floor();
+        let count = whole as usize + usize::from(rng.next_f32() < rule.density - whole);
+        let first = out.len();
+
+        for _ in 0..count {
+            // Always draw all three values so one rejected candidate doesn't shift the rest.
+            let xz = chunk_min + Vec2::new(rng.next_f32(), rng.next_f32()) * chunk_world_size;
+            let yaw = rng.next_f32() * std::f32::consts::TAU;
+
+            let h = height_at(xz.x, xz.y);
+            if h < rule.height_range.0 || h > rule.height_range.1 {
+                continue;
+            }
+
+            let slope = slope_degrees(xz, &height_at);
+            if slope < rule.slope_range.0 || slope > rule.slope_range.1 {
+                continue;
+            }
+
+            let spacing_sq = rule.min_spacing * rule.min_spacing;
+            if out[first..]
+                .iter()
+                .any(|p: &ScatterPoint| p.xz.distance_squared(xz) < spacing_sq)
+            {
+                continue;
+            }
+
+            out.push(ScatterPoint {
+                rule: rule_index,
+                xz,
+                yaw,
+            });
+        }
+    }
+
+    out
+}
+
+fn slope_degrees(xz: Vec2, height_at: &impl Fn(f32, f32) -> f32) -> f32 {
+    let e = 0.5;
+    let dx = (height_at(xz.x + e, xz.y) - height_at(xz.x - e, xz.y)) / (2.0 * e);
+    let dz = (height_at(xz.x, xz.y + e) - height_at(xz.x, xz.y - e)) / (2.0 * e);
+    Vec2::new(dx, dz).length().atan().to_degrees()
+}
+
+fn chunk_seed(seed: u64, coord: IVec2, rule: usize) -> u64 {
+    let packed = (coord.x as u32 as u64) | ((coord.y as u32 as u64) << 32);
+    let chunk = SplitMix64::new(seed ^ packed).next_u64();
+    SplitMix64::new(chunk ^ rule as u64).next_u64()
+}
+
+/// Small, fast and stable across platforms and crate versions, which is what matters here.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
@@ -1,30 +1,63 @@
+//! XZ broadphase for placed objects.
+//!
+//! Placed objects exist only as entities with [`ObjectKind`]; there is no separate object store,
+//! so the grid is kept in sync purely from the ECS: objects are indexed when `ObjectKind` is
+//! added, re-bucketed when their `Transform` changes and dropped when it's removed. Hover,
+//! selection and placement overlap checks all query this grid, so anything spawned through
+//! [`crate::system::spawn_object`] takes part in all of them. Objects must stay top-level
+//! entities, since the index reads their local `Transform` as a world position.
+
 use bevy::prelude::*;
 use glam::{IVec2, Vec2, Vec3};
 use std::collections::{HashMap, HashSet};
 
 use crate::system::ObjectKind;
 
+/// Cell size used when the plugin isn't given one.
+///
+/// Large enough to keep buckets small, small enough for local queries, for objects a few
+/// units across. Much bigger or smaller objects want a size a few times their hover radius.
+pub const DEFAULT_CELL_SIZE: f32 = 8.0;
+
 #[derive(Resource, Debug)]
 pub struct SpatialHashGrid {
-    pub cell_size: f32,
+    cell_size: f32,
     cells: HashMap<IVec2, Vec<Entity>>,
     entity_cell: HashMap<Entity, IVec2>,
 }
 
 impl Default for SpatialHashGrid {
     fn default() -> Self {
+        Self::with_cell_size(DEFAULT_CELL_SIZE)
+    }
+}
+
+impl SpatialHashGrid {
+    pub fn with_cell_size(cell_size: f32) -> Self {
         Self {
-            // Large enough to keep buckets small, small enough for local queries.
-            cell_size: 8.0,
+            cell_size: cell_size.max(0.001),
             cells: HashMap::new(),
             entity_cell: HashMap::new(),
         }
     }
-}
 
-impl SpatialHashGrid {
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Switches to `cell_size` and re-buckets `objects` from scratch. Every indexed object has to
+    /// be passed in, since the grid only remembers cells, not positions.
+    pub fn rebuild(&mut self, cell_size: f32, objects: impl IntoIterator<Item = (Entity, Vec3)>) {
+        self.cell_size = cell_size.max(0.001);
+        self.cells.clear();
+        self.entity_cell.clear();
+        for (entity, pos) in objects {
+            self.insert_or_move(entity, pos);
+        }
+    }
+
     pub fn cell_of_world(&self, world_xz: Vec2) -> IVec2 {
-        let cs = self.cell_size.max(0.001);
+        let cs = self.cell_size;
         IVec2::new(
             (world_xz.x / cs).floor() as i32,
             (world_xz.y / cs).floor() as i32,
@@ -56,7 +89,7 @@ impl SpatialHashGrid {
     }
 
     pub fn query_candidates(&self, world_xz: Vec2, radius: f32) -> Vec<Entity> {
-        let cs = self.cell_size.max(0.001);
+        let cs = self.cell_size;
         let r = radius.max(0.0);
         let center = self.cell_of_world(world_xz);
         let reach = (r / cs).ceil() as i32;
@@ -79,6 +112,31 @@ impl SpatialHashGrid {
 
         out
     }
+
+    /// Returns the entities in every cell overlapping the XZ rectangle `min_xz..=max_xz`.
+    ///
+    /// Like [`Self::query_candidates`] this is a broadphase: callers still need an exact test.
+    pub fn query_aabb(&self, min_xz: Vec2, max_xz: Vec2) -> Vec<Entity> {
+        let lo = self.cell_of_world(min_xz.min(max_xz));
+        let hi = self.cell_of_world(min_xz.max(max_xz));
+
+        let mut out = Vec::new();
+        let mut seen: HashSet<Entity> = HashSet::new();
+
+        for cz in lo.y..=hi.y {
+            for cx in lo.x..=hi.x {
+                if let Some(list) = self.cells.get(&IVec2::new(cx, cz)) {
+                    for &e in list {
+                        if seen.insert(e) {
+                            out.push(e);
+                        }
+                    }
+                }
+            }
+        }
+
+        out
+    }
 }
 
 pub fn spatial_index_added(
@@ -107,3 +165,188 @@ pub fn spatial_index_removed(
         grid.remove(e);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::{
+        CursorHit, HoveredObject, ObjectScale, ObjectTypes, can_place_non_overlapping_spatial,
+        update_hovered_object,
+    };
+    use crate::types::{ObjectTypeId, ObjectTypeRegistry, ObjectTypeSpec};
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// Objects every 1.5 units over a square straddling the origin, so cells on both sides of
+    /// zero are populated.
+    fn lattice(world: &mut World) -> Vec<(Entity, Vec3)> {
+        let mut out = Vec::new();
+        for iz in -14..=14 {
+            for ix in -14..=14 {
+                let pos = Vec3::new(ix as f32 * 1.5, 0.0, iz as f32 * 1.5);
+                out.push((world.spawn_empty().id(), pos));
+            }
+        }
+        out
+    }
+
+    fn unique(found: &[Entity]) -> HashSet<Entity> {
+        let set: HashSet<Entity> = found.iter().copied().collect();
+        assert_eq!(set.len(), found.len(), "duplicates in {found:?}");
+        set
+    }
+
+    #[test]
+    fn aabb_returns_exactly_the_overlapped_cells() {
+        let mut world = World::new();
+        let objects = lattice(&mut world);
+        let mut grid = SpatialHashGrid::with_cell_size(4.0);
+        for &(e, pos) in &objects {
+            grid.insert_or_move(e, pos);
+        }
+
+        let rects = [
+            (Vec2::new(-9.0, -3.0), Vec2::new(5.5, 10.0)),
+            // Corners given the wrong way round.
+            (Vec2::new(7.0, 2.0), Vec2::new(-2.0, -6.5)),
+            (Vec2::new(-20.0, -20.0), Vec2::new(-12.1, -16.0)),
+            (Vec2::new(0.0, 0.0), Vec2::new(0.0, 0.0)),
+            (Vec2::new(-30.0, -30.0), Vec2::new(30.0, 30.0)),
+        ];
+        for (a, b) in rects {
+            let (lo, hi) = (a.min(b), b.max(a));
+            let (lo_cell, hi_cell) = ((lo / 4.0).floor(), (hi / 4.0).floor());
+            let expected: HashSet<Entity> = objects
+                .iter()
+                .filter(|(_, p)| {
+                    let cell = (Vec2::new(p.x, p.z) / 4.0).floor();
+                    cell.cmpge(lo_cell).all() && cell.cmple(hi_cell).all()
+                })
+                .map(|(e, _)| *e)
+                .collect();
+
+            let found = unique(&grid.query_aabb(a, b));
+            assert_eq!(found, expected, "rect {a} .. {b}");
+            for (e, p) in &objects {
+                let xz = Vec2::new(p.x, p.z);
+                if xz.cmpge(lo).all() && xz.cmple(hi).all() {
+                    assert!(found.contains(e), "{xz} inside {a} .. {b} missed");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn candidates_cover_the_radius_at_any_cell_size() {
+        let mut world = World::new();
+        let objects = lattice(&mut world);
+        let mut grid = SpatialHashGrid::default();
+        for &(e, pos) in &objects {
+            grid.insert_or_move(e, pos);
+        }
+
+        let queries = [
+            (Vec2::ZERO, 0.0),
+            (Vec2::new(3.2, -7.9), 2.5),
+            (Vec2::new(-11.0, 4.0), 6.0),
+            (Vec2::new(19.0, 19.0), 13.0),
+        ];
+        for cell_size in [0.5, 1.0, 3.0, 8.0, 25.0] {
+            grid.rebuild(cell_size, objects.iter().copied());
+            assert_eq!(grid.cell_size(), cell_size);
+
+            for (center, radius) in queries {
+                let found = unique(&grid.query_candidates(center, radius));
+                for (e, p) in &objects {
+                    if Vec2::new(p.x, p.z).distance(center) <= radius {
+                        assert!(
+                            found.contains(e),
+                            "cell {cell_size}: {p} within {radius} of {center} missed"
+                        );
+                    }
+                }
+            }
+        }
+
+        // Rebuilding forgets anything that isn't passed back in.
+        let (dropped, kept) = objects.split_at(objects.len() / 2);
+        grid.rebuild(4.0, kept.iter().copied());
+        let all = unique(&grid.query_aabb(Vec2::splat(-30.0), Vec2::splat(30.0)));
+        assert_eq!(all.len(), kept.len());
+        assert!(dropped.iter().all(|(e, _)| !all.contains(e)));
+
+        assert!(SpatialHashGrid::with_cell_size(0.0).cell_size() > 0.0);
+    }
+
+    fn hovered_at(app: &mut App, world_pos: Vec3) -> Option<Entity> {
+        app.world_mut().resource_mut::<CursorHit>().world = Some(world_pos);
+        app.update();
+        app.world().resource::<HoveredObject>().0
+    }
+
+    fn can_place_at(app: &mut App, type_id: ObjectTypeId, position: Vec3) -> bool {
+        app.world_mut()
+            .run_system_once(
+                move |types: Res<ObjectTypes>,
+                      grid: Res<SpatialHashGrid>,
+                      q: Query<(&Transform, &ObjectKind, &ObjectScale)>| {
+                    can_place_non_overlapping_spatial(
+                        &types.registry,
+                        type_id,
+                        position,
+                        Vec3::ONE,
+                        &grid,
+                        &q,
+                        &[],
+                    )
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn moved_objects_are_hovered_and_block_placement_where_they_are() {
+        let mut registry = ObjectTypeRegistry::default();
+        let hut = registry.register(ObjectTypeSpec::for_test("hut", 1.5));
+        let mut app = App::new();
+        app.insert_resource(ObjectTypes::new(registry, vec![hut], 1.5))
+            .insert_resource(SpatialHashGrid::with_cell_size(4.0))
+            .init_resource::<CursorHit>()
+            .init_resource::<HoveredObject>()
+            .add_systems(
+                Update,
+                (
+                    spatial_index_added,
+                    spatial_index_changed,
+                    spatial_index_removed,
+                    update_hovered_object,
+                )
+                    .chain(),
+            );
+
+        let start = Vec3::new(1.0, 0.0, 1.0);
+        let moved = Vec3::new(-13.0, 2.0, 9.0);
+        let object = app
+            .world_mut()
+            .spawn((ObjectKind(hut), Transform::from_translation(start)))
+            .id();
+        assert_eq!(hovered_at(&mut app, start), Some(object));
+        assert!(!can_place_at(&mut app, hut, start + Vec3::X));
+
+        // Several cells away; only `spatial_index_changed` can tell the grid.
+        app.world_mut()
+            .get_mut::<Transform>(object)
+            .unwrap()
+            .translation = moved;
+        assert_eq!(
+            hovered_at(&mut app, moved + Vec3::new(0.5, 0.0, -0.5)),
+            Some(object)
+        );
+        assert_eq!(hovered_at(&mut app, start), None);
+        assert!(!can_place_at(&mut app, hut, moved + Vec3::Z * 2.0));
+        assert!(can_place_at(&mut app, hut, start));
+
+        app.world_mut().despawn(object);
+        assert_eq!(hovered_at(&mut app, moved), None);
+        assert!(can_place_at(&mut app, hut, moved));
+    }
+}
@@ -1,9 +1,14 @@
-use crate::types::{ObjectTypeId, ObjectTypeRegistry, ObjectTypeSpec};
+use crate::types::{GroundSnap, ObjectTypeId, ObjectTypeRegistry, ObjectTypeSpec};
 use bevy::asset::LoadedFolder;
 use bevy::prelude::*;
 use glam::Vec3;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 
 use crate::assets::ObjectTypeDefAsset;
+use crate::instancing::InstancedMeshes;
+use crate::lod::ObjectVisuals;
+use crate::reload::ObjectDefWatch;
 use crate::spatial::SpatialHashGrid;
 #[derive(Resource, Default, Clone, Copy, Debug)]
 pub struct CursorHit {
@@ -11,18 +16,159 @@ pub struct CursorHit {
 }
 
 #[derive(Component, Clone, Copy, Debug)]
+#[require(ObjectScale)]
 pub struct ObjectKind(pub ObjectTypeId);
 
+/// Per-instance scale multiplier, applied on top of the type's `render_scale`.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ObjectScale(pub Vec3);
+
+/// Largest per-instance scale an object may have; scale-aware overlap checks reach this far.
+pub const MAX_OBJECT_SCALE: f32 = 4.0;
+
+impl Default for ObjectScale {
+    fn default() -> Self {
+        Self(Vec3::ONE)
+    }
+}
+
+/// Which of its type's models an object shows; see [`ObjectTypeSpec::variant_gltf`].
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ObjectVariant(pub u32);
+
+/// Hit points of an object that can be damaged; spawned on objects whose type sets `max_hp`.
+///
+/// Change it through [`damage_object`]. Health bars or damage tints can watch
+/// `Changed<ObjectState>` and use [`ObjectState::fraction`].
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ObjectState {
+    pub hp: f32,
+    pub max_hp: f32,
+}
+
+impl ObjectState {
+    pub fn full(max_hp: f32) -> Self {
+        Self { hp: max_hp, max_hp }
+    }
+
+    /// Remaining health in `[0, 1]`.
+    pub fn fraction(&self) -> f32 {
+        if self.max_hp > 0.0 {
+            (self.hp / self.max_hp).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_damaged(&self) -> bool {
+        self.hp < self.max_hp
+    }
+}
+
 #[derive(Resource)]
 pub struct ObjectTypes {
     pub registry: ObjectTypeRegistry,
     pub available: Vec<ObjectTypeId>,
     pub max_hover_radius: f32,
+    /// Types whose model failed to load, with the reason. They're left out of `available`.
+    pub broken: Vec<(ObjectTypeId, String)>,
+    /// Name -> id, for anything that has to refer to a type by a stable string.
+    names: HashMap<String, ObjectTypeId>,
+    /// Palette icons, held here so they stay loaded while the type exists.
+    icons: HashMap<ObjectTypeId, Handle<Image>>,
+}
+
+impl ObjectTypes {
+    pub(crate) fn new(
+        registry: ObjectTypeRegistry,
+        available: Vec<ObjectTypeId>,
+        max_hover_radius: f32,
+    ) -> Self {
+        let mut types = Self {
+            registry,
+            available,
+            max_hover_radius,
+            broken: Vec::new(),
+            names: HashMap::new(),
+            icons: HashMap::new(),
+        };
+        types.rebuild_name_index();
+        types
+    }
+
+    /// The type with this name. Ids can change between runs; names don't.
+    pub fn by_name(&self, name: &str) -> Option<ObjectTypeId> {
+        self.names.get(name).copied()
+    }
+
+    /// The type's palette icon, if its definition names one. It may still be loading.
+    pub fn icon(&self, id: ObjectTypeId) -> Option<&Handle<Image>> {
+        self.icons.get(&id)
+    }
+
+    /// Starts loading the icon the type's spec names, or drops it if the spec no longer has one.
+    pub(crate) fn load_icon(&mut self, id: ObjectTypeId, asset_server: &AssetServer) {
+        match self.registry.get(id).and_then(|s| s.icon.as_deref()) {
+            Some(path) => {
+                let handle = asset_server.load(path.to_string());
+                self.icons.insert(id, handle);
+            }
+            None => {
+                self.icons.remove(&id);
+            }
+        }
+    }
+
+    pub fn is_broken(&self, id: ObjectTypeId) -> bool {
+        self.broken.iter().any(|(b, _)| *b == id)
+    }
+
+    /// Re-indexes names after types are added or replaced. If two types share a name, the
+    /// first registered one keeps it.
+    pub(crate) fn rebuild_name_index(&mut self) {
+        self.names.clear();
+        for (id, spec) in self.registry.iter() {
+            match self.names.entry(spec.name.clone()) {
+                Entry::Vacant(e) => {
+                    e.insert(id);
+                }
+                Entry::Occupied(e) => warn!(
+                    "duplicate object type name '{}'; keeping {:?}, ignoring {:?} for lookups",
+                    spec.name,
+                    e.get(),
+                    id
+                ),
+            }
+        }
+    }
 }
 
 #[derive(Resource, Clone, Copy, Debug, Default)]
 pub struct HoveredObject(pub Option<Entity>);
 
+/// Objects currently selected by the player, in the order they were selected.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Selection(pub Vec<Entity>);
+
+impl Selection {
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.0.contains(&entity)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Adds the entity if it isn't selected yet, otherwise removes it.
+    pub fn toggle(&mut self, entity: Entity) {
+        if let Some(i) = self.0.iter().position(|e| *e == entity) {
+            self.0.remove(i);
+        } else {
+            self.0.push(entity);
+        }
+    }
+}
+
 pub fn setup_object_hovered(mut commands: Commands) {
     commands.insert_resource(HoveredObject::default());
 }
@@ -30,6 +176,8 @@ pub fn setup_object_hovered(mut commands: Commands) {
 #[derive(Resource)]
 pub struct ObjectDefHandles {
     pub handles: Vec<Handle<ObjectTypeDefAsset>>,
+    /// Kept alive after loading so files added to the folder later can be picked up.
+    pub folder: Option<Handle<LoadedFolder>>,
 }
 
 #[derive(Resource)]
@@ -38,6 +186,7 @@ pub struct ObjectDefsFolder(pub Handle<LoadedFolder>);
 pub fn setup_object_types(mut commands: Commands) {
     commands.insert_resource(ObjectDefHandles {
         handles: Vec::new(),
+        folder: None,
     });
 }
 
@@ -85,7 +234,10 @@ pub fn finish_object_types_load(
         }
 
         commands.remove_resource::<ObjectDefsFolder>();
-        commands.insert_resource(ObjectDefHandles { handles: typed });
+        commands.insert_resource(ObjectDefHandles {
+            handles: typed,
+            folder: Some(folder.0.clone()),
+        });
         return;
     }
 
@@ -114,6 +266,7 @@ pub fn finish_object_types_load(
     let mut registry = ObjectTypeRegistry::default();
     let mut available = Vec::new();
     let mut max_hover_radius = 0.0f32;
+    let mut watch = ObjectDefWatch::new(handles.folder.clone());
 
     for h in &handles.handles {
         let Some(def) = defs.get(h) else {
@@ -121,22 +274,40 @@ pub fn finish_object_types_load(
         };
 
         max_hover_radius = max_hover_radius.max(def.hover_radius.max(0.1));
-        let id = registry.register(ObjectTypeSpec {
-            name: def.name.clone(),
-            gltf: def.gltf.clone(),
-            render_scale: def.render_scale,
-            hover_radius: def.hover_radius,
-            scene_offset_local: def.scene_offset_local,
-        });
+        let id = registry.register(spec_from_def(def));
         available.push(id);
+        watch.track(h.clone(), id);
     }
 
     commands.remove_resource::<ObjectDefHandles>();
-    commands.insert_resource(ObjectTypes {
-        registry,
-        available,
-        max_hover_radius,
-    });
+    commands.insert_resource(watch);
+    let mut types = ObjectTypes::new(registry, available, max_hover_radius);
+    for id in types.available.clone() {
+        types.load_icon(id, &asset_server);
+    }
+    commands.insert_resource(types);
+}
+
+pub(crate) fn spec_from_def(def: &ObjectTypeDefAsset) -> ObjectTypeSpec {
+    ObjectTypeSpec {
+        name: def.name.clone(),
+        gltf: def.gltf.clone(),
+        gltf_variants: def.gltf_variants.clone(),
+        gltf_lod1: def.gltf_lod1.clone(),
+        render_scale: def.render_scale,
+        hover_radius: def.hover_radius,
+        collision_radius: def.collision_radius,
+        scene_offset_local: def.scene_offset_local,
+        category: def.category.clone(),
+        tags: def.tags.clone(),
+        instanced: def.instanced,
+        ground_snap: def.ground_snap,
+        align_to_normal: def.align_to_normal,
+        protected: def.protected,
+        max_hp: def.max_hp,
+        max_placement_slope: def.max_placement_slope,
+        icon: def.icon.clone(),
+    }
 }
 
 fn make_missing_object_defs() -> ObjectTypes {
@@ -144,49 +315,247 @@ fn make_missing_object_defs() -> ObjectTypes {
     let id = registry.register(ObjectTypeSpec {
         name: "MissingObjectDefs".to_string(),
         gltf: "".to_string(),
+        gltf_variants: Vec::new(),
+        gltf_lod1: None,
         render_scale: Vec3::ONE,
         hover_radius: 1.0,
+        collision_radius: None,
         scene_offset_local: Vec3::ZERO,
+        category: String::new(),
+        tags: Vec::new(),
+        instanced: false,
+        ground_snap: GroundSnap::Center,
+        align_to_normal: false,
+        protected: false,
+        max_hp: None,
+        max_placement_slope: None,
+        icon: None,
     });
 
-    ObjectTypes {
-        registry,
-        available: vec![id],
-        max_hover_radius: 1.0,
-    }
+    ObjectTypes::new(registry, vec![id], 1.0)
 }
 
 pub fn spawn_object(
     commands: &mut Commands,
     types: &ObjectTypeRegistry,
     asset_server: &AssetServer,
+    instanced: &InstancedMeshes,
     type_id: ObjectTypeId,
     position_world: Vec3,
-    yaw: f32,
+    rotation: Quat,
+    scale: Vec3,
+) -> Option<Entity> {
+    // A type without a model still gets placed; `crate::placeholder` gives it a stand-in.
+    let spec = types.get(type_id)?;
+
+    let root_transform = Transform::from_translation(position_world)
+        .with_rotation(rotation)
+        .with_scale(spec.render_scale * scale);
+
+    let variant = spec.variant_at(position_world);
+    let detail = spawn_object_detail(commands, spec, asset_server, instanced, type_id, variant);
+
+    let mut root = commands.spawn((
+        ObjectKind(type_id),
+        ObjectScale(scale),
+        ObjectVariant(variant),
+        ObjectVisuals { detail, lod1: None },
+        root_transform,
+        Visibility::default(),
+    ));
+    root.add_child(detail);
+    if let Some(max_hp) = spec.max_hp {
+        root.insert(ObjectState::full(max_hp));
+    }
+
+    Some(root.id())
+}
+
+/// Takes `amount` hit points from an object; a negative amount heals, up to `max_hp`.
+///
+/// Returns true if this destroyed it. At 0 hp the object is despawned, and it leaves the spatial
+/// index the same way as any other removed object, so `entity` must not be used afterwards.
+pub fn damage_object(
+    commands: &mut Commands,
+    children: &Query<&Children>,
+    entity: Entity,
+    state: &mut ObjectState,
+    amount: f32,
+) -> bool {
+    if state.hp <= 0.0 {
+        // Already destroyed earlier this frame; the despawn just hasn't applied yet.
+        return false;
+    }
+    state.hp = (state.hp - amount).min(state.max_hp);
+    if state.hp > 0.0 {
+        return false;
+    }
+    state.hp = 0.0;
+    crate::highlight::despawn_recursive(commands, children, entity);
+    true
+}
+
+/// Despawns every placed object, e.g. when leaving the game or loading another world.
+///
+/// They leave the spatial index, the id map and the selection through the usual removal paths.
+pub fn despawn_all_objects(
+    commands: &mut Commands,
+    children: &Query<&Children>,
+    q_objects: &Query<Entity, With<ObjectKind>>,
+) {
+    for entity in q_objects {
+        crate::highlight::despawn_recursive(commands, children, entity);
+    }
+}
+
+/// Spawns just the model of an object, without `ObjectKind`: it can't be hovered, selected or
+/// collided with, and isn't in the spatial index. Used for decoration.
+///
+/// The transform is relative to whatever the caller parents it to.
+pub fn spawn_decoration(
+    commands: &mut Commands,
+    types: &ObjectTypeRegistry,
+    asset_server: &AssetServer,
+    instanced: &InstancedMeshes,
+    type_id: ObjectTypeId,
+    position: Vec3,
+    rotation: Quat,
 ) -> Option<Entity> {
     let spec = types.get(type_id)?;
     if spec.gltf.trim().is_empty() {
         return None;
     }
 
-    let scene_handle = asset_server.load(GltfAssetLabel::Scene(0).from_asset(spec.gltf.clone()));
-    let rot = Quat::from_rotation_y(yaw);
-    let root_transform = Transform::from_translation(position_world)
-        .with_rotation(rot)
+    let transform = Transform::from_translation(position)
+        .with_rotation(rotation)
         .with_scale(spec.render_scale);
+    let variant = spec.variant_at(position);
+    let detail = spawn_object_detail(commands, spec, asset_server, instanced, type_id, variant);
+
+    Some(
+        commands
+            .spawn((transform, Visibility::default()))
+            .add_child(detail)
+            .id(),
+    )
+}
 
-    let root = commands
-        .spawn((ObjectKind(type_id), root_transform, Visibility::default()))
-        .with_children(|parent| {
-            parent.spawn((
-                SceneRoot(scene_handle),
-                Transform::from_translation(spec.scene_offset_local),
-                Visibility::default(),
-            ));
-        })
-        .id();
+/// Moves a placed object without despawning it, so its `Entity` (and anything holding it, like
+/// the selection) stays valid.
+///
+/// The spatial index is updated right away instead of waiting for `spatial_index_changed`, so
+/// overlap checks later in the same frame see the new position.
+pub fn move_object(
+    grid: &mut SpatialHashGrid,
+    entity: Entity,
+    transform: &mut Transform,
+    new_position: Vec3,
+    new_rotation: Quat,
+) {
+    if transform.translation == new_position && transform.rotation == new_rotation {
+        return;
+    }
+    transform.translation = new_position;
+    transform.rotation = new_rotation;
+    grid.insert_or_move(entity, new_position);
+}
 
-    Some(root)
+/// Sets an object's per-instance scale, keeping its transform in step.
+pub fn set_object_scale(
+    spec: &ObjectTypeSpec,
+    transform: &mut Transform,
+    object_scale: &mut ObjectScale,
+    scale: Vec3,
+) {
+    if object_scale.0 == scale {
+        return;
+    }
+    object_scale.0 = scale;
+    transform.scale = spec.render_scale * scale;
+}
+
+/// Where an object of this type rests on the ground at `xz`, facing `yaw`, at `scale` (its
+/// [`ObjectScale`]).
+///
+/// `height_at` samples the terrain height at a world XZ. Returns the position and rotation to
+/// pass to [`spawn_object`].
+pub fn ground_pose(
+    spec: &ObjectTypeSpec,
+    xz: glam::Vec2,
+    yaw: f32,
+    scale: Vec3,
+    height_at: impl Fn(f32, f32) -> f32,
+) -> (Vec3, Quat) {
+    let yaw_rot = Quat::from_rotation_y(yaw);
+    let center_h = height_at(xz.x, xz.y);
+    if spec.ground_snap == GroundSnap::Center && !spec.align_to_normal {
+        return (Vec3::new(xz.x, center_h, xz.y), yaw_rot);
+    }
+
+    // Corners of a square footprint the size of the collision circle, turned with the object.
+    let r = spec.effective_collision_radius() * scale.max_element();
+    let corners = [(-r, -r), (r, -r), (r, r), (-r, r)].map(|(dx, dz)| {
+        let o = yaw_rot * Vec3::new(dx, 0.0, dz);
+        let (x, z) = (xz.x + o.x, xz.y + o.z);
+        Vec3::new(x, height_at(x, z), z)
+    });
+
+    let y = match spec.ground_snap {
+        GroundSnap::Center => center_h,
+        GroundSnap::Min => corners.iter().map(|c| c.y).fold(f32::INFINITY, f32::min),
+        GroundSnap::Average => corners.iter().map(|c| c.y).sum::<f32>() * 0.25,
+    };
+
+    let rotation = if spec.align_to_normal {
+        // The footprint's diagonals span the plane that best fits its corners.
+        let n = (corners[3] - corners[1]).cross(corners[2] - corners[0]);
+        let n = if n.y < 0.0 { -n } else { n }.normalize_or(Vec3::Y);
+        Quat::from_rotation_arc(Vec3::Y, n) * yaw_rot
+    } else {
+        yaw_rot
+    };
+
+    (Vec3::new(xz.x, y, xz.y), rotation)
+}
+
+/// The compass heading (rotation about +Y) of a possibly tilted rotation.
+pub fn heading_of(rotation: Quat) -> f32 {
+    let forward = rotation * Vec3::Z;
+    forward.x.atan2(forward.z)
+}
+
+/// Spawns the full-detail model child for an object: a shared mesh if the type is instanced,
+/// otherwise the glTF scene. The caller parents it to the object root.
+pub(crate) fn spawn_object_detail(
+    commands: &mut Commands,
+    spec: &ObjectTypeSpec,
+    asset_server: &AssetServer,
+    instanced: &InstancedMeshes,
+    type_id: ObjectTypeId,
+    variant: u32,
+) -> Entity {
+    let offset = Transform::from_translation(spec.scene_offset_local);
+    let gltf = spec.variant_gltf(variant);
+
+    if gltf.trim().is_empty() {
+        commands.spawn((offset, Visibility::default())).id()
+    } else if let Some(mesh) = instanced.get(type_id)
+        && gltf == spec.gltf
+    {
+        commands
+            .spawn((
+                Mesh3d(mesh.mesh.clone()),
+                MeshMaterial3d(mesh.material.clone()),
+                offset * mesh.transform,
+                Visibility::default(),
+            ))
+            .id()
+    } else {
+        let scene_handle = asset_server.load(GltfAssetLabel::Scene(0).from_asset(gltf.to_string()));
+        commands
+            .spawn((SceneRoot(scene_handle), offset, Visibility::default()))
+            .id()
+    }
 }
 
 pub fn can_place_non_overlapping(
@@ -199,14 +568,14 @@ pub fn can_place_non_overlapping(
         return false;
     };
 
-    let new_r = new_spec.hover_radius.max(0.1);
+    let new_r = new_spec.effective_collision_radius();
 
     for (other_type, other_pos) in existing {
         let Some(spec) = types.get(other_type) else {
             continue;
         };
 
-        let other_r = spec.hover_radius.max(0.1);
+        let other_r = spec.effective_collision_radius();
 
         if circles_overlap(position_world, new_r, other_pos, other_r) {
             return false;
@@ -216,29 +585,43 @@ pub fn can_place_non_overlapping(
     true
 }
 
+/// Whether an object of `new_type` at `position_world` and `scale` (its [`ObjectScale`]) stays
+/// clear of every object in the spatial index, each at its own scale. Radii grow with the
+/// largest axis.
+///
+/// Objects in `exclude` are ignored, e.g. ones being moved, so they don't collide with
+/// themselves.
 pub fn can_place_non_overlapping_spatial(
     types: &ObjectTypeRegistry,
     new_type: ObjectTypeId,
     position_world: Vec3,
+    scale: Vec3,
     grid: &SpatialHashGrid,
-    q_objects: &Query<(&Transform, &ObjectKind)>,
+    q_objects: &Query<(&Transform, &ObjectKind, &ObjectScale)>,
+    exclude: &[Entity],
 ) -> bool {
     let Some(new_spec) = types.get(new_type) else {
         return false;
     };
 
-    let new_r = new_spec.hover_radius.max(0.1);
+    let new_r = new_spec.effective_collision_radius() * scale.max_element();
+    // Reach far enough to find the largest possible neighbour at the largest scale, not just our
+    // own radius; otherwise a big object whose centre lies beyond `new_r` would be missed.
+    let reach = new_r + types.max_collision_radius() * MAX_OBJECT_SCALE;
     let candidates =
-        grid.query_candidates(glam::Vec2::new(position_world.x, position_world.z), new_r);
+        grid.query_candidates(glam::Vec2::new(position_world.x, position_world.z), reach);
 
     for e in candidates {
-        let Ok((t, k)) = q_objects.get(e) else {
+        if exclude.contains(&e) {
+            continue;
+        }
+        let Ok((t, k, s)) = q_objects.get(e) else {
             continue;
         };
         let Some(spec) = types.get(k.0) else {
             continue;
         };
-        let other_r = spec.hover_radius.max(0.1);
+        let other_r = spec.effective_collision_radius() * s.0.max_element();
         if circles_overlap(position_world, new_r, t.translation, other_r) {
             return false;
         }
@@ -247,6 +630,72 @@ pub fn can_place_non_overlapping_spatial(
     true
 }
 
+/// Why [`place_validated`] didn't place an object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaceError {
+    /// No type is registered under the id.
+    UnknownType,
+    /// It would overlap an object that's already placed.
+    Occupied,
+}
+
+/// Places an object for code that isn't driven by the cursor (scripts, tools): posed on the
+/// ground with [`ground_pose`], refused if it overlaps anything in the spatial index, then
+/// spawned with [`spawn_object`].
+///
+/// Only the checks every placement needs are made here. Game rules the client layers on top
+/// (water, slope limits, budgets) are up to the caller.
+pub fn place_validated(
+    commands: &mut Commands,
+    types: &ObjectTypeRegistry,
+    asset_server: &AssetServer,
+    instanced: &InstancedMeshes,
+    grid: &SpatialHashGrid,
+    q_objects: &Query<(&Transform, &ObjectKind, &ObjectScale)>,
+    type_id: ObjectTypeId,
+    xz: glam::Vec2,
+    yaw: f32,
+    height_at: impl Fn(f32, f32) -> f32,
+) -> Result<Entity, PlaceError> {
+    let spec = types.get(type_id).ok_or(PlaceError::UnknownType)?;
+    let (position, rotation) = ground_pose(spec, xz, yaw, Vec3::ONE, height_at);
+    if !can_place_non_overlapping_spatial(types, type_id, position, Vec3::ONE, grid, q_objects, &[])
+    {
+        return Err(PlaceError::Occupied);
+    }
+    spawn_object(
+        commands,
+        types,
+        asset_server,
+        instanced,
+        type_id,
+        position,
+        rotation,
+        Vec3::ONE,
+    )
+    .ok_or(PlaceError::UnknownType)
+}
+
+/// Returns every object whose position lies within `radius` of `center` on the XZ plane.
+///
+/// Intended for gameplay queries such as area effects and proximity triggers.
+pub fn query_radius(
+    grid: &SpatialHashGrid,
+    q_objects: &Query<(&Transform, &ObjectKind)>,
+    center: Vec3,
+    radius: f32,
+) -> Vec<Entity> {
+    let radius = radius.max(0.0);
+    grid.query_candidates(glam::Vec2::new(center.x, center.z), radius)
+        .into_iter()
+        .filter(|e| {
+            q_objects
+                .get(*e)
+                .is_ok_and(|(t, _)| point_in_circle(t.translation, center, radius))
+        })
+        .collect()
+}
+
 pub fn update_hovered_object(
     hit: Res<CursorHit>,
     types: Option<Res<ObjectTypes>>,
@@ -294,6 +743,18 @@ pub fn update_hovered_object(
     hovered.0 = best.map(|(e, _)| e);
 }
 
+/// Drops despawned objects from the selection.
+pub fn prune_selection(
+    mut selection: ResMut<Selection>,
+    mut removed: RemovedComponents<ObjectKind>,
+) {
+    for e in removed.read() {
+        if selection.contains(e) {
+            selection.0.retain(|s| *s != e);
+        }
+    }
+}
+
 fn point_in_circle(p: Vec3, center: Vec3, radius: f32) -> bool {
     let dx = p.x - center.x;
     let dz = p.z - center.z;
@@ -306,3 +767,206 @@ fn circles_overlap(a: Vec3, ar: f32, b: Vec3, br: f32) -> bool {
     let r = ar + br;
     dx * dx + dz * dz <= r * r
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::collections::HashSet;
+
+    /// Objects of `type_id` every 1.25 units over a square around the origin, indexed in a grid
+    /// of small cells.
+    fn scatter(world: &mut World, type_id: ObjectTypeId, cell_size: f32) -> Vec<(Entity, Vec3)> {
+        let mut grid = SpatialHashGrid::with_cell_size(cell_size);
+        let mut out = Vec::new();
+        for iz in -12..=12 {
+            for ix in -12..=12 {
+                let pos = Vec3::new(ix as f32 * 1.25, 0.0, iz as f32 * 1.25);
+                let e = world
+                    .spawn((ObjectKind(type_id), Transform::from_translation(pos)))
+                    .id();
+                grid.insert_or_move(e, pos);
+                out.push((e, pos));
+            }
+        }
+        world.insert_resource(grid);
+        out
+    }
+
+    #[test]
+    fn radius_query_reaches_past_one_cell() {
+        let mut world = World::new();
+        let objects = scatter(&mut world, ObjectTypeId(0), 2.0);
+
+        for (center, radius) in [
+            (Vec3::ZERO, 7.0),
+            (Vec3::new(3.3, 5.0, -4.1), 9.5),
+            (Vec3::new(-14.0, 0.0, 14.0), 4.9),
+        ] {
+            let found: Vec<Entity> = world
+                .run_system_once(
+                    move |grid: Res<SpatialHashGrid>, q: Query<(&Transform, &ObjectKind)>| {
+                        query_radius(&grid, &q, center, radius)
+                    },
+                )
+                .unwrap();
+            let expected: HashSet<Entity> = objects
+                .iter()
+                .filter(|(_, p)| point_in_circle(*p, center, radius))
+                .map(|(e, _)| *e)
+                .collect();
+            assert!(expected.len() > 4, "{center} r {radius} too sparse to test");
+            assert_eq!(found.len(), expected.len());
+            assert_eq!(found.into_iter().collect::<HashSet<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn moving_across_cells_keeps_the_entity() {
+        let mut world = World::new();
+        let objects = scatter(&mut world, ObjectTypeId(0), 2.0);
+        let (object, from) = objects[40];
+        let to = Vec3::new(31.0, 1.5, -27.0);
+        let turned = Quat::from_rotation_y(1.0);
+
+        world
+            .run_system_once(
+                move |mut grid: ResMut<SpatialHashGrid>, mut q: Query<&mut Transform>| {
+                    let mut transform = q.get_mut(object).unwrap();
+                    move_object(&mut grid, object, &mut transform, to, turned);
+                },
+            )
+            .unwrap();
+
+        let transform = world.get::<Transform>(object).unwrap();
+        assert_eq!(transform.translation, to);
+        assert_eq!(transform.rotation, turned);
+        assert!(world.get::<ObjectKind>(object).is_some());
+
+        let grid = world.resource::<SpatialHashGrid>();
+        assert_eq!(grid.query_candidates(Vec2::new(to.x, to.z), 0.5), [object]);
+        assert!(
+            !grid
+                .query_candidates(Vec2::new(from.x, from.z), 0.5)
+                .contains(&object)
+        );
+    }
+
+    fn damage(world: &mut World, entity: Entity, amount: f32) -> bool {
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      children: Query<&Children>,
+                      mut q: Query<&mut ObjectState>| {
+                    let mut state = q.get_mut(entity).unwrap();
+                    damage_object(&mut commands, &children, entity, &mut state, amount)
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn damage_to_zero_despawns_the_object() {
+        let mut world = World::new();
+        let detail = world.spawn(Transform::default()).id();
+        let object = world
+            .spawn((ObjectKind(ObjectTypeId(0)), ObjectState::full(10.0)))
+            .add_child(detail)
+            .id();
+
+        assert!(!damage(&mut world, object, 4.0));
+        assert_eq!(world.get::<ObjectState>(object).unwrap().hp, 6.0);
+
+        // Healing never goes past full.
+        assert!(!damage(&mut world, object, -20.0));
+        assert_eq!(world.get::<ObjectState>(object).unwrap().hp, 10.0);
+
+        assert!(damage(&mut world, object, 10.0));
+        assert!(world.get_entity(object).is_err());
+        assert!(world.get_entity(detail).is_err());
+    }
+
+    #[test]
+    fn a_destroyed_object_is_only_destroyed_once() {
+        let mut world = World::new();
+        let object = world
+            .spawn((ObjectKind(ObjectTypeId(0)), ObjectState::full(5.0)))
+            .id();
+        let hits: u32 = world
+            .run_system_once(
+                move |mut commands: Commands,
+                      children: Query<&Children>,
+                      mut q: Query<&mut ObjectState>| {
+                    let mut state = q.get_mut(object).unwrap();
+                    // Two hits in one frame: the second lands before the despawn applies.
+                    [8.0, 8.0]
+                        .into_iter()
+                        .filter(|&amount| {
+                            damage_object(&mut commands, &children, object, &mut state, amount)
+                        })
+                        .count() as u32
+                },
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+        assert!(world.get_entity(object).is_err());
+    }
+
+    fn place(app: &mut App, type_id: ObjectTypeId, xz: Vec2) -> Result<Entity, PlaceError> {
+        app.world_mut()
+            .run_system_once(
+                move |mut commands: Commands,
+                      types: Res<ObjectTypes>,
+                      asset_server: Res<AssetServer>,
+                      instanced: Res<InstancedMeshes>,
+                      grid: Res<SpatialHashGrid>,
+                      q: Query<(&Transform, &ObjectKind, &ObjectScale)>| {
+                    place_validated(
+                        &mut commands,
+                        &types.registry,
+                        &asset_server,
+                        &instanced,
+                        &grid,
+                        &q,
+                        type_id,
+                        xz,
+                        0.0,
+                        |_, z| z * 0.5,
+                    )
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn validated_placement_spawns_or_reports_the_overlap() {
+        let mut registry = ObjectTypeRegistry::default();
+        // No model, so nothing is loaded from disk.
+        let mut spec = ObjectTypeSpec::for_test("hut", 1.5);
+        spec.gltf.clear();
+        let hut = registry.register(spec);
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .insert_resource(ObjectTypes::new(registry, vec![hut], 1.5))
+            .init_resource::<InstancedMeshes>()
+            .init_resource::<SpatialHashGrid>()
+            .add_systems(Update, crate::spatial::spatial_index_added);
+
+        let placed = place(&mut app, hut, Vec2::new(4.0, 6.0)).unwrap();
+        app.update();
+        let transform = app.world().get::<Transform>(placed).unwrap();
+        assert_eq!(transform.translation, Vec3::new(4.0, 3.0, 6.0));
+        assert_eq!(app.world().get::<ObjectKind>(placed).unwrap().0, hut);
+
+        assert_eq!(
+            place(&mut app, hut, Vec2::new(5.0, 7.0)),
+            Err(PlaceError::Occupied)
+        );
+        assert_eq!(
+            place(&mut app, ObjectTypeId(9), Vec2::new(-20.0, 0.0)),
+            Err(PlaceError::UnknownType)
+        );
+        assert!(place(&mut app, hut, Vec2::new(8.0, 6.0)).is_ok());
+    }
+}
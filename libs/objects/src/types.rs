@@ -1,19 +1,127 @@
 use glam::Vec3;
+use serde::Deserialize;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ObjectTypeId(pub u16);
 
+/// How an object's height is chosen from the terrain under it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum GroundSnap {
+    /// Terrain height at the object's origin.
+    #[default]
+    Center,
+    /// Lowest of the four footprint corners, so no corner floats.
+    Min,
+    /// Mean of the four footprint corners.
+    Average,
+}
+
 #[derive(Clone, Debug)]
 pub struct ObjectTypeSpec {
     pub name: String,
     /// Path relative to the Bevy asset root (the `assets/` folder).
     pub gltf: String,
+    /// Alternative models; each placed object picks one of `gltf` and these, see
+    /// [`ObjectTypeSpec::variant_gltf`]. Only `gltf` itself can be instanced.
+    pub gltf_variants: Vec<String>,
+    /// Optional lower-detail model shown at a distance; see [`crate::lod`].
+    ///
+    /// Uses the same `scene_offset_local` as the full model, and is shared by all variants.
+    pub gltf_lod1: Option<String>,
     pub render_scale: Vec3,
     pub hover_radius: f32,
+    /// Footprint used for placement overlap checks, when it should differ from the hover
+    /// radius (e.g. a tree whose canopy shouldn't block building next to its trunk).
+    pub collision_radius: Option<f32>,
     /// Local translation applied to the rendered scene child.
     ///
     /// This must be authored in the object definition file; it is not computed at runtime.
     pub scene_offset_local: Vec3,
+    /// UI grouping, e.g. "Buildings". Empty if the definition doesn't set one.
+    pub category: String,
+    /// Free-form gameplay tags, e.g. "residential".
+    pub tags: Vec<String>,
+    /// Render placed objects as a single shared mesh instead of a full glTF scene.
+    ///
+    /// Only takes effect for static single-mesh models; see [`crate::instancing`].
+    pub instanced: bool,
+    pub ground_snap: GroundSnap,
+    /// Tilt the object to follow the terrain slope under its footprint (e.g. rocks).
+    pub align_to_normal: bool,
+    /// The destroy tool refuses to remove objects of this type.
+    pub protected: bool,
+    /// Hit points placed objects start with; `None` means they can't be damaged.
+    pub max_hp: Option<f32>,
+    /// Steepest ground this can be placed on, as rise over run (`TerrainWorld::slope_at`
+    /// units; 1.0 is 45 degrees). `None` allows any slope.
+    pub max_placement_slope: Option<f32>,
+    /// Optional palette thumbnail (a PNG), relative to the asset root like `gltf`.
+    pub icon: Option<String>,
+}
+
+impl ObjectTypeSpec {
+    /// The radius placement checks use: `collision_radius` if set, otherwise `hover_radius`.
+    pub fn effective_collision_radius(&self) -> f32 {
+        self.collision_radius.unwrap_or(self.hover_radius).max(0.1)
+    }
+
+    /// How many models objects of this type choose between; 1 without `gltf_variants`.
+    pub fn variant_count(&self) -> u32 {
+        1 + self.gltf_variants.len() as u32
+    }
+
+    /// The model for `variant`: 0 is `gltf`, 1.. index `gltf_variants`. Out of range variants
+    /// (e.g. after a definition lost some) wrap around.
+    pub fn variant_gltf(&self, variant: u32) -> &str {
+        match variant % self.variant_count() {
+            0 => &self.gltf,
+            n => &self.gltf_variants[n as usize - 1],
+        }
+    }
+
+    /// The variant an object placed at `position_world` gets.
+    ///
+    /// Hashed from the position rather than drawn at random, so the same placement always
+    /// looks the same (undo/redo, replayed edits) and neighbours still differ.
+    pub fn variant_at(&self, position_world: Vec3) -> u32 {
+        if self.gltf_variants.is_empty() {
+            return 0;
+        }
+        let mut h = (position_world.x.to_bits() as u64) << 32 | position_world.z.to_bits() as u64;
+        // splitmix64 finaliser, so nearby positions land on unrelated variants.
+        h ^= h >> 30;
+        h = h.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        h ^= h >> 27;
+        h = h.wrapping_mul(0x94d0_49bb_1331_11eb);
+        h ^= h >> 31;
+        (h % self.variant_count() as u64) as u32
+    }
+}
+
+#[cfg(test)]
+impl ObjectTypeSpec {
+    /// A plain type for tests: no model, no variants, footprint equal to `hover_radius`.
+    pub(crate) fn for_test(name: &str, hover_radius: f32) -> Self {
+        Self {
+            name: name.to_string(),
+            gltf: format!("{name}.glb"),
+            gltf_variants: Vec::new(),
+            gltf_lod1: None,
+            render_scale: Vec3::ONE,
+            hover_radius,
+            collision_radius: None,
+            scene_offset_local: Vec3::ZERO,
+            category: String::new(),
+            tags: Vec::new(),
+            instanced: false,
+            ground_snap: GroundSnap::Center,
+            align_to_normal: false,
+            protected: false,
+            max_hp: None,
+            max_placement_slope: None,
+            icon: None,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -34,7 +142,48 @@ impl ObjectTypeRegistry {
         ObjectTypeId(id)
     }
 
+    /// Overwrites the spec in an existing slot, keeping its id. Returns false if `id` is free.
+    pub fn replace(&mut self, id: ObjectTypeId, spec: ObjectTypeSpec) -> bool {
+        match self.specs.get_mut(id.0 as usize) {
+            Some(slot @ Some(_)) => {
+                *slot = Some(spec);
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn get(&self, id: ObjectTypeId) -> Option<&ObjectTypeSpec> {
         self.specs.get(id.0 as usize)?.as_ref()
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ObjectTypeId, &ObjectTypeSpec)> {
+        self.specs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| Some((ObjectTypeId(i as u16), s.as_ref()?)))
+    }
+
+    /// Largest collision radius of any registered type (at least 0.1, matching the overlap
+    /// checks).
+    pub fn max_collision_radius(&self) -> f32 {
+        self.iter()
+            .map(|(_, s)| s.effective_collision_radius())
+            .fold(0.1, f32::max)
+    }
+
+    pub fn iter_by_category<'a>(
+        &'a self,
+        category: &'a str,
+    ) -> impl Iterator<Item = (ObjectTypeId, &'a ObjectTypeSpec)> {
+        self.iter().filter(move |(_, s)| s.category == category)
+    }
+
+    pub fn iter_by_tag<'a>(
+        &'a self,
+        tag: &'a str,
+    ) -> impl Iterator<Item = (ObjectTypeId, &'a ObjectTypeSpec)> {
+        self.iter()
+            .filter(move |(_, s)| s.tags.iter().any(|t| t == tag))
+    }
 }
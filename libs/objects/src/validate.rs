@@ -0,0 +1,85 @@
+//! Checks that every object type's model actually loads.
+//!
+//! A glTF path that doesn't exist would otherwise give objects that are placed but invisible,
+//! with nothing in the log. Types whose model fails are moved from `ObjectTypes::available` to
+//! `ObjectTypes::broken`; if a hot reload fixes the path they're put back.
+
+use bevy::asset::LoadState;
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::system::ObjectTypes;
+use crate::types::ObjectTypeId;
+
+#[derive(Resource, Default)]
+pub struct ObjectModelChecks {
+    pending: Vec<(ObjectTypeId, Handle<Gltf>)>,
+    /// The glTF path last checked for each type, so only new or changed paths are re-checked.
+    checked: HashMap<ObjectTypeId, String>,
+}
+
+pub fn check_object_models(
+    asset_server: Res<AssetServer>,
+    types: Option<ResMut<ObjectTypes>>,
+    mut checks: ResMut<ObjectModelChecks>,
+) {
+    let Some(mut types) = types else {
+        return;
+    };
+
+    if types.is_changed() {
+        let checks = &mut *checks;
+        for (id, spec) in types.registry.iter() {
+            if spec.gltf.trim().is_empty() || checks.checked.get(&id) == Some(&spec.gltf) {
+                continue;
+            }
+            checks.checked.insert(id, spec.gltf.clone());
+            checks.pending.retain(|(p, _)| *p != id);
+            checks
+                .pending
+                .push((id, asset_server.load(spec.gltf.clone())));
+        }
+    }
+
+    if checks.pending.is_empty() {
+        return;
+    }
+
+    let mut resolved: Vec<(ObjectTypeId, Option<String>)> = Vec::new();
+    checks.pending.retain(
+        |(id, handle)| match asset_server.get_load_state(handle.id()) {
+            Some(LoadState::Loaded) => {
+                resolved.push((*id, None));
+                false
+            }
+            Some(LoadState::Failed(err)) => {
+                resolved.push((*id, Some(err.to_string())));
+                false
+            }
+            _ => true,
+        },
+    );
+
+    for (id, failure) in resolved {
+        let was_broken = types.is_broken(id);
+        match failure {
+            Some(reason) => {
+                let name = types
+                    .registry
+                    .get(id)
+                    .map(|s| s.name.clone())
+                    .unwrap_or_default();
+                warn!("object type '{name}' has no usable model: {reason}");
+                types.available.retain(|a| *a != id);
+                types.broken.retain(|(b, _)| *b != id);
+                types.broken.push((id, reason));
+            }
+            None if was_broken => {
+                types.broken.retain(|(b, _)| *b != id);
+                types.available.push(id);
+            }
+            None => {}
+        }
+    }
+}
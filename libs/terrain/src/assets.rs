@@ -2,11 +2,17 @@ use bevy::asset::io::Reader;
 use bevy::asset::{AssetLoader, LoadContext};
 use bevy::prelude::*;
 use bevy::reflect::TypePath;
+use std::collections::HashMap;
 
-use crate::types::{TileTypes, TileTypesFile};
+use crate::types::{
+    Biome, BiomeConfig, TILE_TEXTURE_SIZE, TerrainConfig, TileTypes, TileTypesFile,
+};
+use crate::world::HeightmapImage;
 
+/// The parsed tile types, and the textures they name decoded to [`TILE_TEXTURE_SIZE`] square
+/// RGBA8 (sRGB), keyed by path.
 #[derive(Asset, TypePath, Debug, Clone)]
-pub struct TileTypesAsset(pub TileTypes);
+pub struct TileTypesAsset(pub BiomeConfig, pub HashMap<String, Vec<u8>>);
 
 #[derive(Default)]
 pub struct TileTypesAssetLoader;
@@ -20,7 +26,7 @@ impl AssetLoader for TileTypesAssetLoader {
         &self,
         reader: &mut dyn Reader,
         _settings: &Self::Settings,
-        _load_context: &mut LoadContext<'_>,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader
@@ -34,15 +40,168 @@ impl AssetLoader for TileTypesAssetLoader {
         let parsed: TileTypesFile =
             ron::from_str(text).map_err(|e| format!("failed to parse tile types ron: {e}"))?;
 
-        let tile_types = TileTypes {
-            tiles: parsed.tiles,
+        let biomes = match (parsed.tiles.is_empty(), parsed.biomes.is_empty()) {
+            (false, false) => {
+                return Err("tile types file has both tiles and biomes; use one".to_string());
+            }
+            (_, true) => vec![Biome {
+                name: "default".to_string(),
+                weight: 1.0,
+                tiles: TileTypes {
+                    tiles: parsed.tiles,
+                },
+            }],
+            (true, false) => parsed
+                .biomes
+                .into_iter()
+                .map(|b| Biome {
+                    name: b.name,
+                    weight: b.weight,
+                    tiles: TileTypes { tiles: b.tiles },
+                })
+                .collect(),
         };
-        tile_types.validate()?;
 
-        Ok(TileTypesAsset(tile_types))
+        let biomes = BiomeConfig::new(biomes, parsed.biome_field)?;
+
+        // A texture that can't be loaded falls back to the tile's colour rather than failing
+        // the whole tile set.
+        let mut textures = HashMap::new();
+        let paths: Vec<String> = biomes
+            .biomes
+            .iter()
+            .flat_map(|b| b.tiles.tiles.iter().filter_map(|t| t.texture.clone()))
+            .collect();
+        for path in paths {
+            if textures.contains_key(&path) {
+                continue;
+            }
+            match load_tile_texture(load_context, &path).await {
+                Ok(pixels) => {
+                    textures.insert(path, pixels);
+                }
+                Err(e) => warn!("tile texture '{path}': {e}; using its colour instead"),
+            }
+        }
+
+        Ok(TileTypesAsset(biomes, textures))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct TerrainConfigAsset(pub TerrainConfig);
+
+#[derive(Default)]
+pub struct TerrainConfigAssetLoader;
+
+impl AssetLoader for TerrainConfigAssetLoader {
+    type Asset = TerrainConfigAsset;
+    type Settings = ();
+    type Error = String;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| format!("failed to read asset bytes: {e}"))?;
+
+        let text = std::str::from_utf8(&bytes)
+            .map_err(|e| format!("terrain config asset was not valid utf-8: {e}"))?;
+
+        parse_terrain_config(text).map(TerrainConfigAsset)
     }
 
     fn extensions(&self) -> &[&str] {
         &["ron"]
     }
 }
+
+/// Parses and validates the text of `terrain.ron`. A `seed_text` replaces `seed`.
+pub(crate) fn parse_terrain_config(text: &str) -> Result<TerrainConfig, String> {
+    let mut config: TerrainConfig =
+        ron::from_str(text).map_err(|e| format!("failed to parse terrain config ron: {e}"))?;
+    if let Some(seed_text) = &config.seed_text {
+        config.seed = TerrainConfig::seed_from_str(seed_text);
+    }
+    config
+        .validate()
+        .map_err(|e| format!("invalid terrain config: {e}"))?;
+    Ok(config)
+}
+
+/// Reads a tile texture PNG and scales it to one atlas cell.
+async fn load_tile_texture(
+    load_context: &mut LoadContext<'_>,
+    path: &str,
+) -> Result<Vec<u8>, String> {
+    let bytes = load_context
+        .read_asset_bytes(path.to_string())
+        .await
+        .map_err(|e| format!("failed to read: {e}"))?;
+    let decoded = image::load_from_memory_with_format(&bytes, image::ImageFormat::Png)
+        .map_err(|e| format!("failed to decode png: {e}"))?
+        .into_rgba8();
+    let size = TILE_TEXTURE_SIZE;
+    let cell = if decoded.dimensions() == (size, size) {
+        decoded
+    } else {
+        image::imageops::resize(&decoded, size, size, image::imageops::FilterType::Triangle)
+    };
+    Ok(cell.into_raw())
+}
+
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct HeightmapAsset(pub HeightmapImage);
+
+#[derive(Default)]
+pub struct HeightmapAssetLoader;
+
+impl AssetLoader for HeightmapAssetLoader {
+    type Asset = HeightmapAsset;
+    type Settings = ();
+    type Error = String;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| format!("failed to read asset bytes: {e}"))?;
+
+        let decoded = image::load_from_memory_with_format(&bytes, image::ImageFormat::Png)
+            .map_err(|e| format!("failed to decode heightmap png: {e}"))?
+            .into_luma16();
+
+        let size = UVec2::new(decoded.width(), decoded.height());
+        if size.x == 0 || size.y == 0 {
+            return Err("heightmap image is empty".to_string());
+        }
+        let values = decoded
+            .into_raw()
+            .into_iter()
+            .map(|v| v as f32 / u16::MAX as f32)
+            .collect();
+
+        Ok(HeightmapAsset(HeightmapImage { size, values }))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        // Not plain "png", which Bevy's image loader already claims.
+        &["heightmap.png"]
+    }
+}
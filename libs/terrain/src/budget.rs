@@ -0,0 +1,148 @@
+//! Frame-time driven chunk spawn budget.
+//!
+//! A fixed `chunk_spawn_budget_per_frame` either fills the world slowly on fast machines or
+//! hitches on slow ones. With [`TerrainConfig::adaptive_spawn_budget`] set, the budget backs off
+//! quickly when frames run over the target and creeps back up while there's headroom.
+
+use bevy::prelude::*;
+
+use crate::types::{AdaptiveSpawnBudget, TerrainConfig};
+use crate::world::TerrainWorld;
+
+#[derive(Resource, Debug, Default)]
+pub struct SpawnBudgetController {
+    budget: f32,
+    /// Recent frame time, smoothed so one slow frame doesn't cut the budget on its own.
+    smoothed_ms: Option<f32>,
+}
+
+impl SpawnBudgetController {
+    /// Folds in one frame's time and returns the budget for the next frame.
+    ///
+    /// Over the target the budget shrinks by a fifth; under 80% of it, it grows by one.
+    pub fn update(&mut self, frame_ms: f32, settings: &AdaptiveSpawnBudget) -> usize {
+        let min = settings.min.max(1) as f32;
+        let max = (settings.max as f32).max(min);
+
+        let ms = match self.smoothed_ms {
+            Some(prev) => prev + (frame_ms - prev) * 0.2,
+            None => frame_ms,
+        };
+        self.smoothed_ms = Some(ms);
+
+        if ms > settings.target_frame_ms {
+            self.budget *= 0.8;
+        } else if ms < settings.target_frame_ms * 0.8 {
+            self.budget += 1.0;
+        }
+        self.budget = self.budget.clamp(min, max);
+        self.budget.floor() as usize
+    }
+
+    /// The budget last returned by [`update`](Self::update).
+    pub fn budget(&self) -> usize {
+        self.budget.floor() as usize
+    }
+}
+
+pub fn adapt_spawn_budget(
+    time: Res<Time>,
+    config: Res<TerrainConfig>,
+    mut controller: ResMut<SpawnBudgetController>,
+    mut terrain: ResMut<TerrainWorld>,
+) {
+    let Some(settings) = &config.adaptive_spawn_budget else {
+        if terrain.spawn_budget() != config.chunk_spawn_budget_per_frame {
+            terrain.set_spawn_budget(None);
+        }
+        return;
+    };
+    let budget = controller.update(time.delta_secs() * 1000.0, settings);
+    terrain.set_spawn_budget(Some(budget));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> AdaptiveSpawnBudget {
+        AdaptiveSpawnBudget {
+            target_frame_ms: 16.0,
+            min: 2,
+            max: 40,
+        }
+    }
+
+    #[test]
+    fn slow_frames_shrink_and_fast_frames_grow() {
+        let settings = settings();
+        let mut controller = SpawnBudgetController::default();
+
+        // Plenty of headroom: grows by one a frame up to the cap.
+        let mut last = controller.update(5.0, &settings);
+        for _ in 0..10 {
+            let budget = controller.update(5.0, &settings);
+            assert_eq!(budget, last + 1);
+            last = budget;
+        }
+        for _ in 0..100 {
+            last = controller.update(5.0, &settings);
+        }
+        assert_eq!(last, settings.max);
+
+        // Sustained slow frames back off, much faster than it grew.
+        let mut frames = 0;
+        while controller.budget() > settings.min {
+            let budget = controller.update(40.0, &settings);
+            assert!(budget <= last);
+            last = budget;
+            frames += 1;
+        }
+        assert!(frames < 20, "took {frames} frames to back off");
+
+        // Between 80% of the target and the target it holds steady.
+        let steady = controller.update(40.0, &settings);
+        for _ in 0..50 {
+            controller.update(14.0, &settings);
+        }
+        let held = controller.update(14.0, &settings);
+        for _ in 0..10 {
+            assert_eq!(controller.update(14.0, &settings), held);
+        }
+        assert!(held >= steady);
+    }
+
+    #[test]
+    fn one_slow_frame_doesnt_cut_the_budget() {
+        let settings = settings();
+        let mut controller = SpawnBudgetController::default();
+        for _ in 0..100 {
+            controller.update(5.0, &settings);
+        }
+        let before = controller.budget();
+        assert_eq!(controller.update(30.0, &settings), before);
+    }
+
+    #[test]
+    fn budget_stays_within_its_bounds() {
+        let settings = settings();
+        let mut controller = SpawnBudgetController::default();
+        let mut state = 1u32;
+        for _ in 0..1000 {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let frame_ms = (state >> 8) as f32 / (1 << 24) as f32 * 60.0;
+            let budget = controller.update(frame_ms, &settings);
+            assert!((settings.min..=settings.max).contains(&budget));
+        }
+
+        // A min of 0 still spawns something, and a max below min is raised to it.
+        let odd = AdaptiveSpawnBudget {
+            target_frame_ms: 16.0,
+            min: 0,
+            max: 0,
+        };
+        let mut controller = SpawnBudgetController::default();
+        assert_eq!(controller.update(100.0, &odd), 1);
+        assert_eq!(controller.update(1.0, &odd), 1);
+    }
+}
@@ -0,0 +1,124 @@
+//! Outlines that follow the ground.
+//!
+//! Overlays drawn on the terrain (brushes, selection rings, footprints, grids) take their
+//! points from here, so they all hug hills the same way instead of sinking into them or
+//! floating over valleys. `lift` raises every point above the surface so lines aren't hidden
+//! in it.
+
+use glam::{Vec2, Vec3};
+
+use crate::world::TerrainWorld;
+
+/// `points` (world XZ) placed on the terrain, `lift` above it.
+pub fn conform_polyline(terrain: &TerrainWorld, points: &[Vec2], lift: f32) -> Vec<Vec3> {
+    let mut heights = Vec::with_capacity(points.len());
+    terrain.sample_heights(points, &mut heights);
+    points
+        .iter()
+        .zip(heights)
+        .map(|(p, h)| Vec3::new(p.x, h + lift, p.y))
+        .collect()
+}
+
+/// A straight line from `a` to `b` with a point at least every `step`, so long lines bend
+/// over the ground between their ends.
+pub fn conform_segment(
+    terrain: &TerrainWorld,
+    a: Vec2,
+    b: Vec2,
+    step: f32,
+    lift: f32,
+) -> Vec<Vec3> {
+    let steps = segment_steps(a, b, step);
+    let points: Vec<Vec2> = (0..=steps)
+        .map(|i| a.lerp(b, i as f32 / steps as f32))
+        .collect();
+    conform_polyline(terrain, &points, lift)
+}
+
+/// The closed outline through `corners`, with a point at least every `step` along each edge.
+/// The first point is repeated at the end, ready for a line strip.
+pub fn conform_polygon(
+    terrain: &TerrainWorld,
+    corners: &[Vec2],
+    step: f32,
+    lift: f32,
+) -> Vec<Vec3> {
+    let mut points = Vec::new();
+    for (i, &a) in corners.iter().enumerate() {
+        let b = corners[(i + 1) % corners.len()];
+        let steps = segment_steps(a, b, step);
+        points.extend((0..steps).map(|s| a.lerp(b, s as f32 / steps as f32)));
+    }
+    if let Some(&first) = points.first() {
+        points.push(first);
+    }
+    conform_polyline(terrain, &points, lift)
+}
+
+/// A circle of `segments` sides around `center`, closed like [`conform_polygon`].
+pub fn conform_circle(
+    terrain: &TerrainWorld,
+    center: Vec2,
+    radius: f32,
+    segments: usize,
+    lift: f32,
+) -> Vec<Vec3> {
+    let segments = segments.max(3);
+    let points: Vec<Vec2> = (0..=segments)
+        .map(|i| {
+            let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+            center + Vec2::from_angle(angle) * radius
+        })
+        .collect();
+    conform_polyline(terrain, &points, lift)
+}
+
+fn segment_steps(a: Vec2, b: Vec2, step: f32) -> usize {
+    ((b - a).length() / step.max(0.01)).ceil().max(1.0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TerrainConfig;
+
+    fn assert_on_ground(terrain: &TerrainWorld, points: &[Vec3], lift: f32) {
+        for p in points {
+            let expected = terrain.sample_height_at(p.x, p.z) + lift;
+            assert_eq!(p.y.to_bits(), expected.to_bits(), "at {p}");
+        }
+    }
+
+    #[test]
+    fn points_sit_lift_above_the_ground() {
+        let mut terrain = TerrainWorld::new(TerrainConfig::default());
+        terrain.raise_vertices(Vec2::new(4.0, 4.0), 10.0, 3.0);
+        let lift = 0.25;
+
+        let corners = [
+            Vec2::new(-6.0, -2.0),
+            Vec2::new(9.0, -1.0),
+            Vec2::new(3.0, 12.0),
+        ];
+        let polyline = conform_polyline(&terrain, &corners, lift);
+        assert_eq!(polyline.len(), corners.len());
+        for (p, c) in polyline.iter().zip(corners) {
+            assert_eq!(Vec2::new(p.x, p.z), c);
+        }
+        assert_on_ground(&terrain, &polyline, lift);
+
+        let segment = conform_segment(&terrain, corners[0], corners[1], 0.5, lift);
+        assert!(segment.len() >= 31);
+        assert_eq!(Vec2::new(segment[0].x, segment[0].z), corners[0]);
+        assert_on_ground(&terrain, &segment, lift);
+
+        let polygon = conform_polygon(&terrain, &corners, 1.0, lift);
+        assert_eq!(polygon.first(), polygon.last());
+        assert_on_ground(&terrain, &polygon, lift);
+
+        let circle = conform_circle(&terrain, Vec2::new(4.0, 4.0), 5.0, 24, lift);
+        assert_eq!(circle.len(), 25);
+        assert_on_ground(&terrain, &circle, lift);
+    }
+}
@@ -0,0 +1,137 @@
+//! Fog of war: chunks the viewer hasn't been near are drawn darkened.
+//!
+//! Off unless a [`FogMask`] is inserted. Exploration is tracked per chunk and only ever grows;
+//! unexplored chunks swap the shared terrain material for a dimmed copy, so there's no shader
+//! work and no per-chunk material.
+
+use bevy::prelude::*;
+use glam::{IVec2, Vec2};
+use std::collections::HashSet;
+
+use crate::render::ChunkCoord;
+use crate::types::{TerrainAtlas, TerrainViewerWorldXz};
+use crate::world::TerrainWorld;
+
+#[derive(Resource, Clone, Debug)]
+pub struct FogMask {
+    explored: HashSet<IVec2>,
+    /// World size of a chunk, the unit `explored` is kept in. Follows the terrain config.
+    chunk_world_size: f32,
+    /// How far around the viewer chunks are revealed each frame.
+    pub reveal_radius: f32,
+}
+
+impl FogMask {
+    pub fn new(terrain: &TerrainWorld, reveal_radius: f32) -> Self {
+        Self {
+            explored: HashSet::new(),
+            chunk_world_size: terrain.config.chunk_size as f32 * terrain.config.tile_size,
+            reveal_radius,
+        }
+    }
+
+    /// Marks every chunk touching the circle as explored.
+    pub fn reveal(&mut self, center_xz: Vec2, radius: f32) {
+        let touched: Vec<IVec2> = self.chunks_in_circle(center_xz, radius).collect();
+        self.explored.extend(touched);
+    }
+
+    fn chunks_in_circle(&self, center_xz: Vec2, radius: f32) -> impl Iterator<Item = IVec2> {
+        let cs = self.chunk_world_size.max(0.001);
+        let r = radius.max(0.0);
+        let lo = ((center_xz - Vec2::splat(r)) / cs).floor().as_ivec2();
+        let hi = ((center_xz + Vec2::splat(r)) / cs).floor().as_ivec2();
+        (lo.y..=hi.y)
+            .flat_map(move |z| (lo.x..=hi.x).map(move |x| IVec2::new(x, z)))
+            .filter(move |c| {
+                let min = c.as_vec2() * cs;
+                let nearest = center_xz.clamp(min, min + Vec2::splat(cs));
+                nearest.distance_squared(center_xz) <= r * r
+            })
+    }
+
+    pub fn is_revealed(&self, coord: IVec2) -> bool {
+        self.explored.contains(&coord)
+    }
+
+    pub fn explored_count(&self) -> usize {
+        self.explored.len()
+    }
+
+    /// Forgets everything explored so far.
+    pub fn clear(&mut self) {
+        self.explored.clear();
+    }
+}
+
+pub fn reveal_around_viewer(
+    terrain: Res<TerrainWorld>,
+    viewer: Res<TerrainViewerWorldXz>,
+    fog: Option<ResMut<FogMask>>,
+) {
+    let Some(mut fog) = fog else {
+        return;
+    };
+
+    // Chunk coords mean something else after a chunk or tile size change.
+    let chunk_world_size = terrain.config.chunk_size as f32 * terrain.config.tile_size;
+    if fog.chunk_world_size != chunk_world_size {
+        fog.chunk_world_size = chunk_world_size;
+        fog.clear();
+    }
+
+    // Only write (and trip change detection) when something new comes into view.
+    let center = viewer.0;
+    let unseen = fog
+        .chunks_in_circle(center, fog.reveal_radius)
+        .any(|c| !fog.is_revealed(c));
+    if unseen {
+        let radius = fog.reveal_radius;
+        fog.reveal(center, radius);
+    }
+}
+
+pub fn apply_fog_tint(
+    atlas: Option<Res<TerrainAtlas>>,
+    fog: Option<Res<FogMask>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    // The dimmed copy, and the atlas material it was made from.
+    mut cached: Local<Option<(AssetId<StandardMaterial>, Handle<StandardMaterial>)>>,
+    mut q_chunks: Query<(&ChunkCoord, &mut MeshMaterial3d<StandardMaterial>)>,
+) {
+    let Some(atlas) = atlas else {
+        return;
+    };
+
+    let fogged = match (&fog, cached.as_ref()) {
+        (None, _) => None,
+        (Some(_), Some((base, handle))) if *base == atlas.material.id() => Some(handle.clone()),
+        (Some(_), _) => {
+            let Some(base) = materials.get(&atlas.material) else {
+                return;
+            };
+            let dimmed = StandardMaterial {
+                base_color: base.base_color.darker(0.75),
+                ..base.clone()
+            };
+            let handle = materials.add(dimmed);
+            *cached = Some((atlas.material.id(), handle.clone()));
+            Some(handle)
+        }
+    };
+
+    for (coord, mut material) in &mut q_chunks {
+        // Leave chunks drawn with another material (debug colours) alone.
+        let dimmed = cached.as_ref().is_some_and(|(_, h)| *h == material.0);
+        if material.0 != atlas.material && !dimmed {
+            continue;
+        }
+        let wanted = match (&fog, &fogged) {
+            (Some(fog), Some(dimmed)) if !fog.is_revealed(coord.0) => dimmed,
+            _ => &atlas.material,
+        };
+        if material.0 != *wanted {
+            material.0 = wanted.clone();
+        }
+    }
+}
@@ -1,4 +1,7 @@
 pub mod assets;
+pub mod budget;
+pub mod conform;
+pub mod fog;
 pub mod render;
 pub mod types;
 pub mod world;
@@ -16,12 +19,30 @@ impl Plugin for TerrainPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(self.config.clone())
             .init_resource::<types::TerrainViewerWorldXz>()
+            .init_resource::<types::TerrainViewerFacing>()
+            .init_resource::<budget::SpawnBudgetController>()
+            .add_message::<types::ReconfigureTerrain>()
+            .add_message::<types::TerrainReconfigured>()
+            .add_message::<types::UnloadTerrain>()
+            .init_asset::<assets::TerrainConfigAsset>()
+            .init_asset_loader::<assets::TerrainConfigAssetLoader>()
             .init_asset::<assets::TileTypesAsset>()
             .init_asset_loader::<assets::TileTypesAssetLoader>()
+            .init_asset::<assets::HeightmapAsset>()
+            .init_asset_loader::<assets::HeightmapAssetLoader>()
             .add_systems(Startup, render::setup_terrain_renderer)
             .add_systems(
                 Update,
-                (render::finish_tile_types_load, render::stream_chunks),
+                (
+                    render::finish_terrain_config_load.before(render::stream_chunks),
+                    render::finish_tile_types_load.after(render::finish_terrain_config_load),
+                    render::finish_heightmap_load.before(render::stream_chunks),
+                    budget::adapt_spawn_budget.before(render::stream_chunks),
+                    render::stream_chunks,
+                    (fog::reveal_around_viewer, fog::apply_fog_tint)
+                        .chain()
+                        .after(render::stream_chunks),
+                ),
             );
     }
 }
@@ -3,18 +3,35 @@ use bevy::mesh::{Indices, PrimitiveTopology};
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 use glam::{IVec2, Vec3};
+use std::collections::HashMap;
 
-use crate::assets::TileTypesAsset;
-use crate::types::TileTypes;
-use crate::types::{LoadedChunkEntities, TerrainAtlas, TerrainConfig, TerrainViewerWorldXz};
-use crate::world::{ChunkMeshData, TerrainAction, TerrainWorld};
+use crate::assets::{HeightmapAsset, TerrainConfigAsset, TileTypesAsset};
+use crate::types::{
+    BiomeConfig, LoadedChunkEntities, ReconfigureTerrain, Shading, TILE_TEXTURE_SIZE, TerrainAtlas,
+    TerrainConfig, TerrainReconfigured, TerrainViewerFacing, TerrainViewerWorldXz, TileBlend,
+    UnloadTerrain,
+};
+use crate::world::{ChunkMeshData, TerrainAction, TerrainSource, TerrainWorld};
 
 #[derive(Component)]
 pub struct Chunk;
 
+/// The chunk coordinate of a terrain chunk entity.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ChunkCoord(pub IVec2);
+
 #[derive(Resource, Clone)]
 pub struct TileTypesHandle(pub Handle<TileTypesAsset>);
 
+/// `terrain.ron` while it loads; removed once it's applied (or failed). Chunks don't stream
+/// until then, so nothing is built with the wrong config.
+#[derive(Resource, Clone)]
+pub struct TerrainConfigHandle(pub Handle<TerrainConfigAsset>);
+
+/// The configured heightmap while it loads; removed once it's applied (or failed).
+#[derive(Resource, Clone)]
+pub struct HeightmapHandle(pub Handle<HeightmapAsset>);
+
 pub fn setup_terrain_renderer(
     mut commands: Commands,
     config: Res<TerrainConfig>,
@@ -23,60 +40,223 @@ pub fn setup_terrain_renderer(
     commands.insert_resource(TerrainWorld::new(config.clone()));
     commands.insert_resource(LoadedChunkEntities::default());
 
+    let handle: Handle<TerrainConfigAsset> = asset_server.load("terrain.ron");
+    commands.insert_resource(TerrainConfigHandle(handle));
+
     let handle: Handle<TileTypesAsset> = asset_server.load("tiles.ron");
     commands.insert_resource(TileTypesHandle(handle));
 }
 
+/// Replaces the plugin's config with `terrain.ron` once it has loaded, keeping the plugin's
+/// config if the file is missing or invalid, then starts loading the heightmap if one is set.
+pub fn finish_terrain_config_load(
+    mut commands: Commands,
+    handle: Option<Res<TerrainConfigHandle>>,
+    asset_server: Res<AssetServer>,
+    assets: Res<Assets<TerrainConfigAsset>>,
+    mut config: ResMut<TerrainConfig>,
+    mut terrain: ResMut<TerrainWorld>,
+    mut loaded: ResMut<LoadedChunkEntities>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+
+    if asset_server.load_state(&handle.0).is_failed() {
+        warn!("terrain.ron failed to load; using the built-in terrain config");
+    } else if let Some(asset) = assets.get(&handle.0) {
+        *config = asset.0.clone();
+        // Reconfigure rather than rebuild, so a source installed at startup (see
+        // `TerrainWorld::set_source`) and any edits made so far are kept.
+        for action in terrain.reconfigure(config.clone()) {
+            if let TerrainAction::DespawnChunk(coord) = action
+                && let Some(entity) = loaded.entities.remove(&coord)
+            {
+                commands.entity(entity).despawn();
+            }
+        }
+    } else {
+        return;
+    }
+    commands.remove_resource::<TerrainConfigHandle>();
+
+    if let Some(heightmap) = &config.heightmap {
+        let handle: Handle<HeightmapAsset> = asset_server.load(heightmap.path.clone());
+        commands.insert_resource(HeightmapHandle(handle));
+    }
+}
+
+/// Switches the terrain over to the configured heightmap once it has loaded.
+///
+/// Until then the terrain is procedural; any chunks streamed in meanwhile are respawned.
+pub fn finish_heightmap_load(
+    mut commands: Commands,
+    handle: Option<Res<HeightmapHandle>>,
+    asset_server: Res<AssetServer>,
+    assets: Res<Assets<HeightmapAsset>>,
+    config: Res<TerrainConfig>,
+    mut terrain: ResMut<TerrainWorld>,
+    mut loaded: ResMut<LoadedChunkEntities>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+
+    if asset_server.load_state(&handle.0).is_failed() {
+        warn!("terrain heightmap failed to load; keeping procedural terrain");
+        commands.remove_resource::<HeightmapHandle>();
+        return;
+    }
+
+    let (Some(asset), Some(heightmap)) = (assets.get(&handle.0), &config.heightmap) else {
+        return;
+    };
+    commands.remove_resource::<HeightmapHandle>();
+
+    let source = TerrainSource::Heightmap {
+        image: asset.0.clone(),
+        world_bounds: heightmap.world_bounds,
+        scale: heightmap.scale,
+    };
+    for action in terrain.set_source(source) {
+        if let TerrainAction::DespawnChunk(coord) = action
+            && let Some(entity) = loaded.entities.remove(&coord)
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
 pub fn finish_tile_types_load(
     mut commands: Commands,
     handle: Option<Res<TileTypesHandle>>,
     assets: Res<Assets<TileTypesAsset>>,
+    config: Res<TerrainConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
     mut images: ResMut<Assets<Image>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    pending_config: Option<Res<TerrainConfigHandle>>,
 ) {
     let Some(handle) = handle else {
         return;
     };
+    // The atlas and water quad depend on the config, so wait for terrain.ron to settle.
+    if pending_config.is_some() {
+        return;
+    }
 
     let Some(asset) = assets.get(&handle.0) else {
         return;
     };
-    let tile_types = asset.0.clone();
+    let biomes = asset.0.clone();
 
     commands.remove_resource::<TileTypesHandle>();
 
-    let atlas_colors: Vec<Color> = tile_types
-        .tiles
-        .iter()
-        .map(|t| {
-            let (r, g, b) = t.color_srgb;
-            Color::srgb(r, g, b)
-        })
-        .collect();
-
-    commands.insert_resource(tile_types);
+    if biomes.is_textured() && config.shading != Shading::Flat {
+        warn!("tile textures need flat shading; smooth terrain will show them smeared");
+    }
+    let atlas_tex = images.add(if biomes.is_textured() {
+        make_textured_atlas_image(&biomes, &asset.1)
+    } else {
+        make_atlas_image(&biomes, config.tile_blend != TileBlend::Off)
+    });
+    commands.insert_resource(biomes);
 
-    let atlas_tex = images.add(make_atlas_1x_n_image(&atlas_colors));
     let material = materials.add(StandardMaterial {
         base_color_texture: Some(atlas_tex),
         perceptual_roughness: 1.0,
         ..default()
     });
 
-    commands.insert_resource(TerrainAtlas { material });
+    let chunk_world_size = config.chunk_size as f32 * config.tile_size;
+    let debug_material = materials.add(StandardMaterial {
+        perceptual_roughness: 1.0,
+        ..default()
+    });
+
+    let water_mesh = meshes.add(Plane3d::new(Vec3::Y, Vec2::splat(chunk_world_size * 0.5)));
+    let water_material = materials.add(StandardMaterial {
+        base_color: Color::srgba(0.15, 0.35, 0.75, 0.6),
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 0.1,
+        ..default()
+    });
+
+    commands.insert_resource(TerrainAtlas {
+        material,
+        debug_material,
+        water_mesh,
+        water_material,
+    });
+}
+
+/// One row per biome, one column per height band; see [`BiomeConfig`]. `blend` samples it
+/// linearly even with one biome, for [`TileBlend`].
+fn make_atlas_image(biomes: &BiomeConfig, blend: bool) -> Image {
+    let (width, height) = (biomes.column_count(), biomes.row_count());
+    let mut data = Vec::with_capacity(width * height * 4);
+    for biome in &biomes.biomes {
+        for column in 0..width {
+            let tiles = &biome.tiles;
+            let tile = &tiles.tiles[tiles.pick_tile_index(biomes.column_height(column)) as usize];
+            let (r, g, b) = tile.color_srgb;
+            let [r, g, b, a] = Color::srgb(r, g, b).to_srgba().to_u8_array();
+            data.extend_from_slice(&[r, g, b, a]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // Biomes blend by sampling between rows. UVs sit on texel centres otherwise, so flat tiles
+    // still get exactly one colour; smooth shading also blends across height bands then.
+    image.sampler = if height > 1 || blend {
+        bevy::image::ImageSampler::linear()
+    } else {
+        bevy::image::ImageSampler::nearest()
+    };
+    image
 }
 
-fn make_atlas_1x_n_image(colors: &[Color]) -> Image {
-    let mut data = Vec::with_capacity(colors.len() * 4);
-    for c in colors {
-        let [r, g, b, a] = c.to_srgba().to_u8_array();
-        data.extend_from_slice(&[r, g, b, a]);
+/// Like [`make_atlas_image`], but every cell is [`TILE_TEXTURE_SIZE`] square: the tile's
+/// texture if it has a loaded one, otherwise its colour.
+fn make_textured_atlas_image(biomes: &BiomeConfig, textures: &HashMap<String, Vec<u8>>) -> Image {
+    let cell = TILE_TEXTURE_SIZE as usize;
+    let (columns, rows) = (biomes.column_count(), biomes.row_count());
+    let (width, height) = (columns * cell, rows * cell);
+    let mut data = vec![0u8; width * height * 4];
+    for (row, biome) in biomes.biomes.iter().enumerate() {
+        for column in 0..columns {
+            let tiles = &biome.tiles;
+            let tile = &tiles.tiles[tiles.pick_tile_index(biomes.column_height(column)) as usize];
+            let texture = tile.texture.as_ref().and_then(|path| textures.get(path));
+            let (r, g, b) = tile.color_srgb;
+            let flat = Color::srgb(r, g, b).to_srgba().to_u8_array();
+            for y in 0..cell {
+                let start = ((row * cell + y) * width + column * cell) * 4;
+                let dest = &mut data[start..start + cell * 4];
+                match texture {
+                    Some(pixels) => dest.copy_from_slice(&pixels[y * cell * 4..(y + 1) * cell * 4]),
+                    None => dest
+                        .chunks_exact_mut(4)
+                        .for_each(|px| px.copy_from_slice(&flat)),
+                }
+            }
+        }
     }
 
     let mut image = Image::new(
         Extent3d {
-            width: colors.len() as u32,
-            height: 1,
+            width: width as u32,
+            height: height as u32,
             depth_or_array_layers: 1,
         },
         TextureDimension::D2,
@@ -84,7 +264,8 @@ fn make_atlas_1x_n_image(colors: &[Color]) -> Image {
         TextureFormat::Rgba8UnormSrgb,
         RenderAssetUsages::default(),
     );
-    image.sampler = bevy::image::ImageSampler::nearest();
+    // Mesh UVs stay half a texel inside each cell, so filtering never reaches a neighbour.
+    image.sampler = bevy::image::ImageSampler::linear();
     image
 }
 
@@ -92,16 +273,49 @@ pub fn stream_chunks(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     atlas: Option<Res<TerrainAtlas>>,
-    tiles: Option<Res<TileTypes>>,
+    biomes: Option<Res<BiomeConfig>>,
     mut terrain: ResMut<TerrainWorld>,
     mut loaded: ResMut<LoadedChunkEntities>,
     viewer: Res<TerrainViewerWorldXz>,
+    facing: Res<TerrainViewerFacing>,
+    mut config: ResMut<TerrainConfig>,
+    mut reconfigure: MessageReader<ReconfigureTerrain>,
+    mut reconfigured: MessageWriter<TerrainReconfigured>,
+    mut unload: MessageReader<UnloadTerrain>,
+    pending_config: Option<Res<TerrainConfigHandle>>,
 ) {
-    let (Some(atlas), Some(tiles)) = (atlas, tiles) else {
+    let (Some(atlas), Some(biomes)) = (atlas, biomes) else {
         return;
     };
+    if pending_config.is_some() {
+        return;
+    }
+
+    if unload.read().last().is_some() {
+        for action in terrain.unload_all() {
+            if let TerrainAction::DespawnChunk(coord) = action
+                && let Some(entity) = loaded.entities.remove(&coord)
+            {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    // Only the latest request matters if several arrived this frame.
+    if let Some(ReconfigureTerrain(new_config)) = reconfigure.read().last() {
+        for action in terrain.reconfigure(new_config.clone()) {
+            if let TerrainAction::DespawnChunk(coord) = action
+                && let Some(entity) = loaded.entities.remove(&coord)
+            {
+                commands.entity(entity).despawn();
+            }
+        }
+        *config = new_config.clone();
+        reconfigured.write(TerrainReconfigured);
+    }
 
     terrain.set_viewer_world_xz(viewer.0);
+    terrain.set_viewer_facing(facing.0);
     let actions = terrain.tick();
 
     for action in actions {
@@ -117,11 +331,21 @@ pub fn stream_chunks(
                 }
 
                 let chunk_entity =
-                    spawn_chunk(&mut commands, &mut meshes, &terrain, &atlas, &tiles, coord);
+                    spawn_chunk(&mut commands, &mut meshes, &terrain, &atlas, &biomes, coord);
                 loaded.entities.insert(coord, chunk_entity);
             }
         }
     }
+
+    // Rebuild chunks whose heights were edited.
+    for coord in terrain.take_dirty_chunks() {
+        if let Some(entity) = loaded.entities.remove(&coord) {
+            commands.entity(entity).despawn();
+        }
+        let chunk_entity =
+            spawn_chunk(&mut commands, &mut meshes, &terrain, &atlas, &biomes, coord);
+        loaded.entities.insert(coord, chunk_entity);
+    }
 }
 
 fn spawn_chunk(
@@ -129,22 +353,39 @@ fn spawn_chunk(
     meshes: &mut Assets<Mesh>,
     terrain: &TerrainWorld,
     atlas: &TerrainAtlas,
-    tiles: &TileTypes,
+    biomes: &BiomeConfig,
     coord: IVec2,
 ) -> Entity {
     let origin = terrain.chunk_origin_world(coord);
-    let mesh_data = terrain.build_chunk_mesh_data(coord, tiles);
+    let mesh_data = terrain.build_chunk_mesh_data(coord, biomes);
+    let has_water = mesh_data.min_height < terrain.config.sea_level;
+    let material = if mesh_data.colors.is_some() {
+        atlas.debug_material.clone()
+    } else {
+        atlas.material.clone()
+    };
     let mesh = mesh_from_chunk_mesh_data(mesh_data);
     let mesh_handle = meshes.add(mesh);
 
-    commands
-        .spawn((
-            Chunk,
-            Mesh3d(mesh_handle),
-            MeshMaterial3d(atlas.material.clone()),
-            Transform::from_translation(Vec3::new(origin.x, origin.y, origin.z)),
-        ))
-        .id()
+    let mut chunk = commands.spawn((
+        Chunk,
+        ChunkCoord(coord),
+        Mesh3d(mesh_handle),
+        MeshMaterial3d(material),
+        Transform::from_translation(Vec3::new(origin.x, origin.y, origin.z)),
+    ));
+
+    // The water quad is a child, so it streams and despawns along with the chunk.
+    if has_water {
+        let half = terrain.config.chunk_size as f32 * terrain.config.tile_size * 0.5;
+        chunk.with_child((
+            Mesh3d(atlas.water_mesh.clone()),
+            MeshMaterial3d(atlas.water_material.clone()),
+            Transform::from_xyz(half, terrain.config.sea_level - origin.y, half),
+        ));
+    }
+
+    chunk.id()
 }
 
 fn mesh_from_chunk_mesh_data(data: ChunkMeshData) -> Mesh {
@@ -155,6 +396,9 @@ fn mesh_from_chunk_mesh_data(data: ChunkMeshData) -> Mesh {
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, data.positions);
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, data.normals);
     mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, data.uvs);
+    if let Some(colors) = data.colors {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
     mesh.insert_indices(Indices::U32(data.indices));
     mesh
 }
@@ -1,39 +1,338 @@
 use bevy::prelude::*;
 use glam::IVec2;
 use glam::Vec2;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 // --- Config ---
 
-#[derive(Resource, Clone, Debug)]
+/// Which chunks around the viewer count as within the view distance.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ViewShape {
+    /// Every chunk in the `(2 * view_distance_chunks + 1)`-wide square.
+    #[default]
+    Square,
+    /// Only chunks whose centre is within `view_distance_chunks` chunk widths, which skips the
+    /// square's corners.
+    Circle,
+}
+
+/// How each noise octave is shaped before the octaves are summed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum NoiseMode {
+    /// Plain fBm.
+    #[default]
+    Fbm,
+    /// `(1 - |n|)^2`: sharp crests where the noise crosses zero, for mountain ridges.
+    Ridged,
+    /// `|n|`: creases in the valleys and rounded tops, for rolling hills.
+    Billow,
+}
+
+/// How chunk meshes are built.
+///
+/// `Flat` gives every tile its own four vertices so each tile is one solid colour with hard
+/// edges: `4 * n^2` vertices for an `n`-tile chunk. `Smooth` shares one `(n + 1)^2` vertex grid
+/// and picks the colour per vertex, so colours blend across tiles but the mesh is about a quarter
+/// of the size (1089 instead of 4096 vertices at `chunk_size` 32).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Shading {
+    #[default]
+    Flat,
+    Smooth,
+}
+
+/// Whether flat-shaded tiles of different types meet at a hard edge.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TileBlend {
+    #[default]
+    Off,
+    /// Each tile's colour ramps towards its neighbours' at its corners, so bands (e.g. a sand
+    /// shoreline) fade into each other instead of stair-stepping. Colour-only tile sets; a
+    /// textured atlas can't be blended this way and stays hard-edged.
+    Corners,
+}
+
+/// How a tile texture is laid over flat-shaded tiles. Makes no difference to colour-only tile
+/// sets, where each tile is a single atlas texel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum TileUvMode {
+    /// The whole texture on every tile, so its scale follows `tile_size`.
+    #[default]
+    PerTile,
+    /// One texture covers this many world units (rounded to whole tiles, at least one) and
+    /// tiles show their part of it, keeping the texture's scale whatever `tile_size` is.
+    World { texture_size: f32 },
+}
+
+impl TileUvMode {
+    /// How many tiles one texture spans along each axis.
+    pub fn tiles_per_texture(self, tile_size: f32) -> i32 {
+        match self {
+            TileUvMode::PerTile => 1,
+            TileUvMode::World { texture_size } => {
+                ((texture_size / tile_size.max(0.001)).round() as i32).max(1)
+            }
+        }
+    }
+}
+
+/// Vertex colours drawn over the terrain instead of the atlas, for debugging.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DebugColorMode {
+    /// The normal atlas texture; chunk meshes get no colour attribute.
+    #[default]
+    None,
+    /// Green on flat ground through yellow to red at 45 degrees and steeper.
+    Slope,
+    /// Alternating colours per chunk, to show chunk boundaries.
+    ChunkChecker,
+    /// One colour per height band (atlas column), to preview height-based texturing.
+    TileIndex,
+}
+
+/// Also read from `terrain.ron` at startup; fields left out of the file take their
+/// [`Default`] values.
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct TerrainConfig {
     pub seed: u64,
+    /// Text `seed` was made from with [`TerrainConfig::seed_from_str`], kept so it can be shown
+    /// and shared. When given in `terrain.ron` it replaces `seed`.
+    pub seed_text: Option<String>,
     pub chunk_size: i32,
     pub tile_size: f32,
     pub view_distance_chunks: i32,
+    /// Loaded chunks are only despawned once they're this many chunks beyond
+    /// `view_distance_chunks`, so a viewer hovering on a chunk border doesn't keep unloading and
+    /// reloading the outermost ring. 0 despawns as soon as a chunk leaves the view.
+    pub despawn_margin_chunks: i32,
+    pub view_shape: ViewShape,
+    pub shading: Shading,
+    pub tile_uv_mode: TileUvMode,
+    pub tile_blend: TileBlend,
+    pub debug_colors: DebugColorMode,
+    /// Hang a strip of wall down from every chunk's edges, so float or LOD mismatches between
+    /// neighbours show the skirt instead of a hairline gap to the sky.
+    pub chunk_skirts: bool,
+    /// How far below the edge heights the skirts reach, in world units.
+    pub chunk_skirt_depth: f32,
     pub chunk_spawn_budget_per_frame: usize,
+    /// Scale the spawn budget with frame time instead; `None` keeps it fixed at
+    /// `chunk_spawn_budget_per_frame`.
+    pub adaptive_spawn_budget: Option<AdaptiveSpawnBudget>,
     pub noise_base_frequency: f64,
     pub noise_octaves: u32,
     pub noise_persistence: f64,
+    pub noise_mode: NoiseMode,
     pub height_scale: f32,
+    /// How far, in world units, sample points are pushed around by low-frequency noise before
+    /// the octaves are summed. Breaks up the blobby fBm look into ridges and valleys; 0 is off.
+    pub warp_strength: f32,
+    /// Frequency of the warp noise; lower than `noise_base_frequency` looks best.
+    pub warp_frequency: f64,
+    /// Let cursor raycasts use a direct ray-plane intersection when the terrain is flat.
+    pub raycast_flat_fast_path: bool,
+    /// Height of the water surface. Chunks with terrain below it get a water quad.
+    pub sea_level: f32,
+    /// Treat cursor hits below `sea_level` as hitting the water surface, and disallow building
+    /// there.
+    pub water_blocks_placement: bool,
+    /// Take heights from an image instead of noise. Read once at startup; `None` is procedural.
+    pub heightmap: Option<HeightmapConfig>,
+}
+
+impl Default for TerrainConfig {
+    fn default() -> Self {
+        Self {
+            seed: 12345,
+            seed_text: None,
+            chunk_size: 32,
+            tile_size: 2.0,
+            view_distance_chunks: 8,
+            despawn_margin_chunks: 0,
+            view_shape: ViewShape::Square,
+            shading: Shading::Flat,
+            tile_uv_mode: TileUvMode::PerTile,
+            tile_blend: TileBlend::Off,
+            debug_colors: DebugColorMode::None,
+            chunk_skirts: false,
+            chunk_skirt_depth: 1.0,
+            chunk_spawn_budget_per_frame: 32,
+            adaptive_spawn_budget: None,
+            noise_base_frequency: 0.02,
+            noise_octaves: 4,
+            noise_persistence: 0.5,
+            noise_mode: NoiseMode::Fbm,
+            height_scale: 8.0,
+            warp_strength: 0.0,
+            warp_frequency: 0.005,
+            raycast_flat_fast_path: true,
+            sea_level: -3.0,
+            water_blocks_placement: false,
+            heightmap: None,
+        }
+    }
+}
+
+impl TerrainConfig {
+    /// Turns text a player typed into a seed, the same on every run and platform. Text that is
+    /// already a whole number is used as is, so numeric seeds can be typed too.
+    pub fn seed_from_str(text: &str) -> u64 {
+        let text = text.trim();
+        if let Ok(seed) = text.parse() {
+            return seed;
+        }
+        // 64-bit FNV-1a: fixed constants, unlike std's randomly keyed `DefaultHasher`.
+        text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.tile_size.is_finite() || self.tile_size <= 0.0 {
+            return Err(format!("invalid tile_size={}", self.tile_size));
+        }
+        if self.chunk_size < 1 {
+            return Err(format!("invalid chunk_size={}", self.chunk_size));
+        }
+        if self.view_distance_chunks < 0 || self.despawn_margin_chunks < 0 {
+            return Err(format!(
+                "invalid view_distance_chunks={} / despawn_margin_chunks={}",
+                self.view_distance_chunks, self.despawn_margin_chunks
+            ));
+        }
+        if self.noise_octaves == 0 {
+            return Err("noise_octaves must be at least 1".to_string());
+        }
+        let noise = [
+            ("noise_base_frequency", self.noise_base_frequency),
+            ("noise_persistence", self.noise_persistence),
+            ("warp_frequency", self.warp_frequency),
+            ("warp_strength", self.warp_strength as f64),
+            ("height_scale", self.height_scale as f64),
+            ("sea_level", self.sea_level as f64),
+            ("chunk_skirt_depth", self.chunk_skirt_depth as f64),
+        ];
+        for (name, value) in noise {
+            if !value.is_finite() {
+                return Err(format!("{name} must be finite, got {value}"));
+            }
+        }
+        if let Some(heightmap) = &self.heightmap {
+            let (min, max) = heightmap.world_bounds;
+            let valid = min.x < max.x && min.y < max.y && heightmap.scale.is_finite();
+            if !valid {
+                return Err(format!(
+                    "heightmap '{}' has invalid world_bounds or scale",
+                    heightmap.path
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bounds for the frame-time driven spawn budget; see [`crate::budget::SpawnBudgetController`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AdaptiveSpawnBudget {
+    /// Frames slower than this shrink the budget; comfortably faster ones grow it.
+    pub target_frame_ms: f32,
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Default for AdaptiveSpawnBudget {
+    fn default() -> Self {
+        Self {
+            target_frame_ms: 16.6,
+            min: 2,
+            max: 64,
+        }
+    }
+}
+
+/// A grayscale image stretched over a rectangle of the world; see
+/// [`crate::TerrainSource::Heightmap`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HeightmapConfig {
+    /// Asset path of a `*.heightmap.png` (8- or 16-bit, colour is converted to luma).
+    pub path: String,
+    /// Min and max world XZ the image covers, edge to edge; pixel row 0 is at the min Z edge.
+    /// Written `((min_x, min_z), (max_x, max_z))` in RON.
+    #[serde(
+        deserialize_with = "deserialize_bounds",
+        serialize_with = "serialize_bounds"
+    )]
+    pub world_bounds: (Vec2, Vec2),
+    /// Height of a white pixel; black is 0.
+    pub scale: f32,
+}
+
+fn serialize_bounds<S: serde::Serializer>(bounds: &(Vec2, Vec2), s: S) -> Result<S::Ok, S::Error> {
+    let (min, max) = bounds;
+    ((min.x, min.y), (max.x, max.y)).serialize(s)
+}
+
+fn deserialize_bounds<'de, D: serde::Deserializer<'de>>(d: D) -> Result<(Vec2, Vec2), D::Error> {
+    let ((min_x, min_z), (max_x, max_z)): ((f32, f32), (f32, f32)) = Deserialize::deserialize(d)?;
+    Ok((Vec2::new(min_x, min_z), Vec2::new(max_x, max_z)))
 }
 
 // --- Tiles ---
 
+/// `tiles.ron`: either `tiles` for a single biome, or `biomes` plus optional `biome_field`.
 #[derive(Clone, Debug, Deserialize)]
 pub struct TileTypesFile {
+    #[serde(default)]
     pub tiles: Vec<TileType>,
+    #[serde(default)]
+    pub biomes: Vec<BiomeFile>,
+    #[serde(default)]
+    pub biome_field: BiomeField,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BiomeFile {
+    pub name: String,
+    #[serde(default = "BiomeFile::default_weight")]
+    pub weight: f32,
+    pub tiles: Vec<TileType>,
+}
+
+impl BiomeFile {
+    fn default_weight() -> f32 {
+        1.0
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct TileType {
     pub name: String,
+    /// Flat colour, used when there's no `texture` (and by the minimap either way).
     pub color_srgb: (f32, f32, f32),
     /// Select this tile if height < height_lt.
     pub height_lt: f32,
+    /// PNG drawn across each tile of this type instead of the flat colour, relative to the
+    /// asset root. Resized to [`TILE_TEXTURE_SIZE`] square, so author it square and ideally at
+    /// that size. Only takes effect with [`Shading::Flat`].
+    #[serde(default)]
+    pub texture: Option<String>,
+    /// Whether objects may be placed on this tile; e.g. `false` for water.
+    #[serde(default = "TileType::default_buildable")]
+    pub buildable: bool,
 }
 
+impl TileType {
+    fn default_buildable() -> bool {
+        true
+    }
+}
+
+/// Side length, in pixels, of each terrain atlas cell once any tile has a texture.
+pub const TILE_TEXTURE_SIZE: u32 = 64;
+
 #[derive(Resource, Clone, Debug)]
 pub struct TileTypes {
     pub tiles: Vec<TileType>,
@@ -77,11 +376,214 @@ impl TileTypes {
     }
 }
 
+// --- Biomes ---
+
+/// The low-frequency noise that picks a biome, independent of height.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BiomeField {
+    pub frequency: f64,
+    pub octaves: u32,
+    /// Width of the blend between neighbouring biomes, as a fraction of the field's range.
+    pub blend: f32,
+}
+
+impl Default for BiomeField {
+    fn default() -> Self {
+        Self {
+            frequency: 0.003,
+            octaves: 3,
+            blend: 0.08,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Biome {
+    pub name: String,
+    /// Relative share of the biome field. The field clusters around its middle, so the middle
+    /// biomes come out somewhat more common than their weight alone suggests.
+    pub weight: f32,
+    pub tiles: TileTypes,
+}
+
+/// Biomes in field order: each takes the next slice of the field, so only neighbours in this
+/// list ever meet and blend.
+///
+/// The terrain atlas has one row per biome and one column per height band of all biomes
+/// combined, so a height maps to the same column in every row and blending between two rows
+/// blends the two biomes' tiles for that height. A single biome is exactly the old height-only
+/// tile table.
+#[derive(Resource, Clone, Debug)]
+pub struct BiomeConfig {
+    pub biomes: Vec<Biome>,
+    pub field: BiomeField,
+    /// Every biome's `height_lt` thresholds, sorted and deduplicated.
+    columns: Vec<f32>,
+    /// Some tile has a texture, so the atlas is built from [`TILE_TEXTURE_SIZE`] cells.
+    textured: bool,
+}
+
+impl BiomeConfig {
+    pub fn new(biomes: Vec<Biome>, field: BiomeField) -> Result<Self, String> {
+        if biomes.is_empty() {
+            return Err("tile types file must define at least one biome".to_string());
+        }
+        for biome in &biomes {
+            if !biome.weight.is_finite() || biome.weight <= 0.0 {
+                return Err(format!(
+                    "biome '{}' has invalid weight={}",
+                    biome.name, biome.weight
+                ));
+            }
+            biome
+                .tiles
+                .validate()
+                .map_err(|e| format!("biome '{}': {e}", biome.name))?;
+        }
+        if !field.frequency.is_finite() || field.frequency <= 0.0 {
+            return Err(format!(
+                "biome field has invalid frequency={}",
+                field.frequency
+            ));
+        }
+        if !field.blend.is_finite() || field.blend < 0.0 {
+            return Err(format!("biome field has invalid blend={}", field.blend));
+        }
+
+        let mut columns: Vec<f32> = biomes
+            .iter()
+            .flat_map(|b| b.tiles.tiles.iter().map(|t| t.height_lt))
+            .collect();
+        columns.sort_by(f32::total_cmp);
+        columns.dedup();
+        let textured = biomes
+            .iter()
+            .any(|b| b.tiles.tiles.iter().any(|t| t.texture.is_some()));
+
+        Ok(Self {
+            biomes,
+            field,
+            columns,
+            textured,
+        })
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.biomes.len()
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Whether the atlas is made of texture cells rather than one texel per tile type.
+    pub fn is_textured(&self) -> bool {
+        self.textured
+    }
+
+    /// Half an atlas cell in UV units, less half a texel so sampling never bleeds into the
+    /// neighbouring cell. Flat shading spreads a cell across each tile with it; zero without
+    /// textures, where every tile is a single texel.
+    pub fn cell_half_uv(&self) -> [f32; 2] {
+        if !self.textured {
+            return [0.0, 0.0];
+        }
+        let inset = 0.5 / TILE_TEXTURE_SIZE as f32;
+        [
+            (0.5 - inset) / self.column_count() as f32,
+            (0.5 - inset) / self.row_count() as f32,
+        ]
+    }
+
+    /// Atlas column for a height; with one biome this is its tile index.
+    pub fn column(&self, height: f32) -> u32 {
+        let above = self.columns.partition_point(|&t| t <= height);
+        above.min(self.columns.len() - 1) as u32
+    }
+
+    /// A height that falls in `column`, for looking up each biome's tile there.
+    pub fn column_height(&self, column: usize) -> f32 {
+        match column {
+            0 => f32::NEG_INFINITY,
+            c => self.columns[c - 1],
+        }
+    }
+
+    /// Fractional biome row for a biome field value in `[0, 1]`: whole numbers inside a biome,
+    /// ramping across `field.blend` at each boundary.
+    pub fn row_coord(&self, field: f32) -> f32 {
+        let total: f32 = self.biomes.iter().map(|b| b.weight).sum();
+        let half = self.field.blend * 0.5;
+        let mut edge = 0.0;
+        for (i, biome) in self.biomes.iter().enumerate() {
+            edge += biome.weight / total;
+            if i + 1 == self.biomes.len() {
+                break;
+            }
+            if field < edge - half {
+                return i as f32;
+            }
+            if field < edge + half {
+                return i as f32 + (field - (edge - half)) / self.field.blend;
+            }
+        }
+        (self.biomes.len() - 1) as f32
+    }
+
+    /// Atlas UV for a height and biome field value.
+    pub fn atlas_uv(&self, height: f32, field: f32) -> [f32; 2] {
+        self.column_uv(self.column(height), field)
+    }
+
+    /// Like [`Self::atlas_uv`], but for a given atlas column instead of the one a height falls
+    /// in; used for painted tiles. Out of range columns clamp to the last.
+    pub fn column_uv(&self, column: u32, field: f32) -> [f32; 2] {
+        let column = column.min(self.column_count() as u32 - 1);
+        let u = (column as f32 + 0.5) / self.column_count() as f32;
+        // Texture cells can't be blended between, so biomes meet at a hard edge then.
+        let row = if self.textured {
+            self.row_coord(field).round()
+        } else {
+            self.row_coord(field)
+        };
+        let v = (row + 0.5) / self.row_count() as f32;
+        [u, v]
+    }
+
+    /// The tile type shown in `column` at a biome field value. Between two biomes this is the
+    /// nearer one's tile.
+    pub fn tile(&self, column: u32, field: f32) -> &TileType {
+        let row = (self.row_coord(field).round() as usize).min(self.biomes.len() - 1);
+        let column = (column as usize).min(self.column_count() - 1);
+        let tiles = &self.biomes[row].tiles;
+        &tiles.tiles[tiles.pick_tile_index(self.column_height(column)) as usize]
+    }
+
+    /// The colour the atlas shows for a height and biome field value, blended like the mesh.
+    pub fn color_srgb(&self, height: f32, field: f32) -> (f32, f32, f32) {
+        let row = self.row_coord(field);
+        let lo = row.floor() as usize;
+        let hi = (lo + 1).min(self.biomes.len() - 1);
+        let pick = |b: usize| {
+            let tiles = &self.biomes[b].tiles;
+            Vec3::from(tiles.tiles[tiles.pick_tile_index(height) as usize].color_srgb)
+        };
+        let c = pick(lo).lerp(pick(hi), row - lo as f32);
+        (c.x, c.y, c.z)
+    }
+}
+
 // --- Resources ---
 
 #[derive(Resource)]
 pub struct TerrainAtlas {
     pub material: Handle<StandardMaterial>,
+    /// Untextured, for chunks drawn with [`DebugColorMode`] vertex colours.
+    pub debug_material: Handle<StandardMaterial>,
+    /// One chunk-sized quad, shared by every chunk's water surface.
+    pub water_mesh: Handle<Mesh>,
+    pub water_material: Handle<StandardMaterial>,
 }
 
 #[derive(Resource, Default)]
@@ -92,3 +594,127 @@ pub struct LoadedChunkEntities {
 /// Set by the root game crate to indicate where the viewer is (XZ plane).
 #[derive(Resource, Default, Clone, Copy, Debug)]
 pub struct TerrainViewerWorldXz(pub Vec2);
+
+/// Set by the root game crate to the direction the viewer looks in (XZ plane, any length).
+/// Chunks in front of it are streamed in first; zero means no preference.
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct TerrainViewerFacing(pub Vec2);
+
+// --- Messages ---
+
+/// Ask the terrain to regenerate with a new config; see [`crate::TerrainWorld::reconfigure`].
+#[derive(Message, Clone, Debug)]
+pub struct ReconfigureTerrain(pub TerrainConfig);
+
+/// Despawn every loaded chunk, e.g. when leaving the game for a menu. Streaming picks up again
+/// on the next frame, so pause it by other means if nothing should be shown.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct UnloadTerrain;
+
+/// Sent after the terrain has switched to a new config, once heights reflect it.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct TerrainReconfigured;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::parse_terrain_config;
+
+    fn to_ron(config: &TerrainConfig) -> String {
+        ron::ser::to_string_pretty(config, ron::ser::PrettyConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn config_round_trips_through_the_loader() {
+        let config = TerrainConfig {
+            seed: TerrainConfig::seed_from_str("round trip"),
+            seed_text: Some("round trip".to_string()),
+            chunk_size: 24,
+            tile_size: 1.5,
+            view_distance_chunks: 5,
+            despawn_margin_chunks: 2,
+            view_shape: ViewShape::Circle,
+            shading: Shading::Smooth,
+            tile_uv_mode: TileUvMode::World { texture_size: 12.0 },
+            tile_blend: TileBlend::Corners,
+            debug_colors: DebugColorMode::Slope,
+            chunk_skirts: true,
+            chunk_skirt_depth: 2.5,
+            chunk_spawn_budget_per_frame: 7,
+            adaptive_spawn_budget: Some(AdaptiveSpawnBudget {
+                target_frame_ms: 8.3,
+                min: 1,
+                max: 20,
+            }),
+            noise_base_frequency: 0.031,
+            noise_octaves: 6,
+            noise_persistence: 0.45,
+            noise_mode: NoiseMode::Ridged,
+            height_scale: 22.0,
+            warp_strength: 3.25,
+            warp_frequency: 0.0042,
+            raycast_flat_fast_path: false,
+            sea_level: -1.75,
+            water_blocks_placement: false,
+            heightmap: Some(HeightmapConfig {
+                path: "maps/island.heightmap.png".to_string(),
+                world_bounds: (Vec2::new(-256.0, -128.0), Vec2::new(256.0, 384.0)),
+                scale: 40.0,
+            }),
+        };
+        config.validate().unwrap();
+
+        let text = to_ron(&config);
+        let parsed = parse_terrain_config(&text).unwrap();
+        assert_eq!(to_ron(&parsed), text);
+    }
+
+    #[test]
+    fn omitted_fields_take_their_defaults() {
+        let parsed = parse_terrain_config("(chunk_size: 16, noise_mode: Billow)").unwrap();
+        let expected = TerrainConfig {
+            chunk_size: 16,
+            noise_mode: NoiseMode::Billow,
+            ..TerrainConfig::default()
+        };
+        assert_eq!(to_ron(&parsed), to_ron(&expected));
+
+        let budget = parse_terrain_config("(adaptive_spawn_budget: Some((max: 9)))").unwrap();
+        let budget = budget.adaptive_spawn_budget.unwrap();
+        assert_eq!(budget.max, 9);
+        assert_eq!(budget.min, AdaptiveSpawnBudget::default().min);
+        assert_eq!(
+            budget.target_frame_ms,
+            AdaptiveSpawnBudget::default().target_frame_ms
+        );
+    }
+
+    #[test]
+    fn seed_text_is_stable() {
+        assert_eq!(
+            TerrainConfig::seed_from_str("Mountain Pass"),
+            TerrainConfig::seed_from_str("Mountain Pass")
+        );
+        assert_eq!(
+            TerrainConfig::seed_from_str("  Mountain Pass "),
+            TerrainConfig::seed_from_str("Mountain Pass")
+        );
+        assert_ne!(
+            TerrainConfig::seed_from_str("Mountain Pass"),
+            TerrainConfig::seed_from_str("Mountain pass")
+        );
+        // Published FNV-1a test vectors, so a changed hash can't slip through.
+        assert_eq!(TerrainConfig::seed_from_str("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(
+            TerrainConfig::seed_from_str("foobar"),
+            0x8594_4171_f739_67e8
+        );
+        assert_eq!(TerrainConfig::seed_from_str("12345"), 12345);
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected() {
+        assert!(parse_terrain_config("(tile_size: 0.0)").is_err());
+        assert!(parse_terrain_config("(noise_octaves: 0)").is_err());
+    }
+}
@@ -1,8 +1,11 @@
-use crate::types::{TerrainConfig, TileTypes};
+use crate::types::{
+    BiomeConfig, BiomeField, DebugColorMode, NoiseMode, Shading, TerrainConfig, TileBlend,
+    TileType, ViewShape,
+};
 use bevy::prelude::Resource;
-use glam::{IVec2, Vec2, Vec3};
+use glam::{IVec2, UVec2, Vec2, Vec3};
 use parrot::Perlin;
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum TerrainAction {
@@ -10,63 +13,315 @@ pub enum TerrainAction {
     DespawnChunk(IVec2),
 }
 
+/// One vertex's height edit before and after a change, so the change can be undone with
+/// [`TerrainWorld::set_height_deltas`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeightDeltaChange {
+    pub vertex: IVec2,
+    pub before: f32,
+    pub after: f32,
+}
+
 #[derive(Clone, Debug)]
 pub struct ChunkMeshData {
     pub positions: Vec<[f32; 3]>,
     pub normals: Vec<[f32; 3]>,
     pub uvs: Vec<[f32; 2]>,
     pub indices: Vec<u32>,
+    /// Lowest vertex height in the chunk.
+    pub min_height: f32,
+    /// Per-vertex colours for [`TerrainConfig::debug_colors`]; `None` (and not allocated)
+    /// unless a debug mode is on.
+    pub colors: Option<Vec<[f32; 4]>>,
 }
 
 #[derive(Default)]
 struct ChunkStreamingState {
     last_viewer_chunk: Option<IVec2>,
     desired: HashSet<IVec2>,
+    /// Ordered nearest-and-in-front first; see [`spawn_priority`].
     pending_spawn: VecDeque<IVec2>,
     pending_despawn: VecDeque<IVec2>,
+    /// Viewer facing when `pending_spawn` was last sorted.
+    sorted_facing: Vec2,
+    /// Centre chunk of the current prefetch, if any.
+    prefetch_center: Option<IVec2>,
+    /// Chunks kept loaded for the prefetch, even outside the view distance.
+    prefetched: HashSet<IVec2>,
+    pending_prefetch: VecDeque<IVec2>,
+}
+
+/// Where the base heights (before player edits) come from.
+pub enum TerrainSource {
+    /// Infinite noise, shaped by the noise settings in [`TerrainConfig`].
+    Procedural(Perlin),
+    /// An authored map: `image` stretched over `world_bounds` (min, max XZ), a white pixel being
+    /// `scale` high. Heights are 0 outside the bounds.
+    Heightmap {
+        image: HeightmapImage,
+        world_bounds: (Vec2, Vec2),
+        scale: f32,
+    },
+    /// Heights from outside the crate, e.g. Worley or ridged hybrids; see [`HeightSource`].
+    Custom(Box<dyn HeightSource>),
+}
+
+/// A base height generator that can be plugged in with [`TerrainSource::Custom`].
+///
+/// Install it with [`TerrainWorld::set_source`] before any chunks have streamed in, e.g. from a
+/// `Startup` system; the noise settings in [`TerrainConfig`] only apply to the built-in source.
+pub trait HeightSource: Send + Sync {
+    /// Height at a world position, before player edits.
+    fn sample(&self, x: f64, z: f64) -> f64;
+
+    /// Lower and upper bound of anything [`sample`](Self::sample) returns. Chunk bounds and the
+    /// water checks rely on it, so it must not be too tight.
+    fn height_range(&self) -> (f32, f32);
+}
+
+/// Decoded grayscale heights in `[0, 1]`, row-major.
+#[derive(Clone, Debug)]
+pub struct HeightmapImage {
+    pub size: UVec2,
+    pub values: Vec<f32>,
+}
+
+impl HeightmapImage {
+    /// Bilinear sample at `uv` in `[0, 1]^2`, treating pixels as cells so the outer half-pixel
+    /// holds the edge value.
+    pub fn sample(&self, uv: Vec2) -> f32 {
+        if self.size.x == 0 || self.size.y == 0 {
+            return 0.0;
+        }
+        let max = (self.size - UVec2::ONE).as_vec2();
+        let p = (uv * self.size.as_vec2() - Vec2::splat(0.5)).clamp(Vec2::ZERO, max);
+        let base = p.floor();
+        let f = p - base;
+        let x0 = base.x as u32;
+        let y0 = base.y as u32;
+        let x1 = (x0 + 1).min(self.size.x - 1);
+        let y1 = (y0 + 1).min(self.size.y - 1);
+
+        let at = |x: u32, y: u32| self.values[(y * self.size.x + x) as usize];
+        let top = at(x0, y0) + (at(x1, y0) - at(x0, y0)) * f.x;
+        let bottom = at(x0, y1) + (at(x1, y1) - at(x0, y1)) * f.x;
+        top + (bottom - top) * f.y
+    }
 }
 
 #[derive(Resource)]
 pub struct TerrainWorld {
     pub config: TerrainConfig,
-    perlin: Perlin,
+    source: TerrainSource,
+    /// Drives the biome field; seeded apart from the height noise so the two don't correlate.
+    biome_perlin: Perlin,
     loaded: HashSet<IVec2>,
     streaming: ChunkStreamingState,
     viewer_world_xz: Vec2,
+    /// Unit XZ direction the viewer faces, or zero.
+    viewer_facing: Vec2,
+    /// Player edits on top of the procedural heights, keyed by global vertex coordinate.
+    height_deltas: HashMap<IVec2, f32>,
+    /// Painted atlas columns, keyed by global tile coordinate; see [`Self::paint_tiles`].
+    tile_overrides: HashMap<IVec2, u32>,
+    /// Loaded chunks whose mesh is stale because of height or paint edits.
+    dirty_chunks: HashSet<IVec2>,
+    /// Conservative bounds of `height_deltas` (they only ever widen).
+    delta_range: (f32, f32),
+    /// Replaces `config.chunk_spawn_budget_per_frame` while set, e.g. by the adaptive budget.
+    spawn_budget: Option<usize>,
 }
 
 impl TerrainWorld {
     pub fn new(config: TerrainConfig) -> Self {
         Self {
-            perlin: Perlin::new(config.seed),
+            source: TerrainSource::Procedural(Perlin::new(config.seed)),
+            biome_perlin: Perlin::new(biome_seed(config.seed)),
             config,
             loaded: HashSet::new(),
             streaming: ChunkStreamingState::default(),
             viewer_world_xz: Vec2::ZERO,
+            viewer_facing: Vec2::ZERO,
+            height_deltas: HashMap::new(),
+            tile_overrides: HashMap::new(),
+            dirty_chunks: HashSet::new(),
+            delta_range: (0.0, 0.0),
+            spawn_budget: None,
+        }
+    }
+
+    /// Swaps in a new config, e.g. different noise settings.
+    ///
+    /// Every loaded chunk is returned as a despawn so the following `tick`s stream the view back
+    /// in with the new settings. Height and paint edits are dropped, see
+    /// [`unload_all`](Self::unload_all).
+    pub fn reconfigure(&mut self, new_config: TerrainConfig) -> Vec<TerrainAction> {
+        if new_config.seed != self.config.seed {
+            self.biome_perlin = Perlin::new(biome_seed(new_config.seed));
+            if let TerrainSource::Procedural(perlin) = &mut self.source {
+                *perlin = Perlin::new(new_config.seed);
+            }
         }
+        self.config = new_config;
+        self.unload_all()
+    }
+
+    /// Replaces where base heights come from, e.g. once a heightmap image has loaded.
+    ///
+    /// Like [`reconfigure`](Self::reconfigure), every loaded chunk is returned as a despawn.
+    pub fn set_source(&mut self, source: TerrainSource) -> Vec<TerrainAction> {
+        self.source = source;
+        self.unload_all()
+    }
+
+    /// Forgets every loaded chunk, returning a despawn for each, and resets streaming (including
+    /// any prefetch) so the next `tick` starts from scratch. For leaving the game or switching
+    /// worlds.
+    ///
+    /// Height and paint edits go too: height edits are offsets from base heights that are about
+    /// to change, so kept ones would leave pads and trenches at the wrong height.
+    pub fn unload_all(&mut self) -> Vec<TerrainAction> {
+        self.streaming = ChunkStreamingState::default();
+        self.dirty_chunks.clear();
+        self.height_deltas.clear();
+        self.tile_overrides.clear();
+        self.delta_range = (0.0, 0.0);
+        self.loaded
+            .drain()
+            .map(TerrainAction::DespawnChunk)
+            .collect()
     }
 
     pub fn set_viewer_world_xz(&mut self, world_xz: Vec2) {
         self.viewer_world_xz = world_xz;
     }
 
-    pub fn tick(&mut self) -> Vec<TerrainAction> {
+    /// Sets the direction the viewer faces, so chunks in view are spawned first.
+    ///
+    /// Only the order chunks are spawned in changes; the queue is re-sorted once the facing has
+    /// turned by more than about 30 degrees.
+    pub fn set_viewer_facing(&mut self, facing_xz: Vec2) {
+        self.viewer_facing = facing_xz.normalize_or_zero();
+        if self.viewer_facing.dot(self.streaming.sorted_facing) < 0.87 {
+            self.sort_pending_spawn();
+        }
+    }
+
+    fn sort_pending_spawn(&mut self) {
+        let facing = self.viewer_facing;
+        self.streaming.sorted_facing = facing;
+        let Some(viewer_chunk) = self.streaming.last_viewer_chunk else {
+            return;
+        };
+        self.streaming
+            .pending_spawn
+            .make_contiguous()
+            .sort_by(|a, b| {
+                let pa = spawn_priority((*a - viewer_chunk).as_vec2(), facing);
+                let pb = spawn_priority((*b - viewer_chunk).as_vec2(), facing);
+                pa.total_cmp(&pb)
+            });
+    }
+
+    /// Starts loading the chunks within `radius` of `center`, e.g. ahead of a camera flight.
+    ///
+    /// They share the per-frame spawn budget, after the viewer's own chunks, and aren't culled
+    /// while the prefetch lasts. It ends when the viewer reaches `center`, when
+    /// [`cancel_prefetch`](Self::cancel_prefetch) is called, or when another prefetch replaces
+    /// it; from then on the chunks are culled by view distance like any other.
+    pub fn prefetch(&mut self, center: IVec2, radius: i32) {
+        self.cancel_prefetch();
+
+        let radius = radius.max(0);
+        let streaming = &mut self.streaming;
+        streaming.prefetch_center = Some(center);
+        for dz in -radius..=radius {
+            for dx in -radius..=radius {
+                let coord = center + IVec2::new(dx, dz);
+                streaming.prefetched.insert(coord);
+                if !self.loaded.contains(&coord) {
+                    streaming.pending_prefetch.push_back(coord);
+                }
+            }
+        }
+    }
+
+    /// Drops the current prefetch so chunks outside the view distance are culled again.
+    pub fn cancel_prefetch(&mut self) {
+        let streaming = &mut self.streaming;
+        if streaming.prefetch_center.take().is_none() {
+            return;
+        }
+        streaming.prefetched.clear();
+        streaming.pending_prefetch.clear();
+        // Recompute the despawn queue, which skipped the pinned chunks.
+        streaming.last_viewer_chunk = None;
+    }
+
+    /// Chunks currently loaded, in no particular order. Their meshes may still be building.
+    pub fn loaded_chunk_coords(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.loaded.iter().copied()
+    }
+
+    pub fn is_chunk_loaded(&self, coord: IVec2) -> bool {
+        self.loaded.contains(&coord)
+    }
+
+    /// How many chunks the view distance wants around the viewer, for diagnostics. Zero
+    /// before the first `tick`.
+    pub fn desired_chunk_count(&self) -> usize {
+        self.streaming.desired.len()
+    }
+
+    /// Chunks spawned (and despawned) per `tick`.
+    pub fn spawn_budget(&self) -> usize {
+        self.spawn_budget
+            .unwrap_or(self.config.chunk_spawn_budget_per_frame)
+    }
+
+    /// Overrides the configured spawn budget; `None` goes back to the config value.
+    pub fn set_spawn_budget(&mut self, budget: Option<usize>) {
+        self.spawn_budget = budget;
+    }
+
+    /// Chunks waiting to be spawned, for the viewer and for a prefetch.
+    pub fn pending_spawn_len(&self) -> usize {
+        self.streaming.pending_spawn.len() + self.streaming.pending_prefetch.len()
+    }
+
+    pub fn pending_despawn_len(&self) -> usize {
+        self.streaming.pending_despawn.len()
+    }
+
+    /// Chunks whose mesh is waiting to be rebuilt after a height edit.
+    pub fn dirty_chunk_count(&self) -> usize {
+        self.dirty_chunks.len()
+    }
+
+    pub fn world_to_chunk_coord(&self, world_xz: Vec2) -> IVec2 {
         let chunk_world_size = self.config.chunk_size as f32 * self.config.tile_size;
-        let viewer_chunk = IVec2::new(
-            (self.viewer_world_xz.x / chunk_world_size).floor() as i32,
-            (self.viewer_world_xz.y / chunk_world_size).floor() as i32,
-        );
+        (world_xz / chunk_world_size).floor().as_ivec2()
+    }
+
+    pub fn tick(&mut self) -> Vec<TerrainAction> {
+        let viewer_chunk = self.world_to_chunk_coord(self.viewer_world_xz);
+
+        if self.streaming.prefetch_center == Some(viewer_chunk) {
+            self.cancel_prefetch();
+        }
 
         // Recompute streaming targets only when entering a new chunk.
         if self.streaming.last_viewer_chunk != Some(viewer_chunk) {
             self.streaming.last_viewer_chunk = Some(viewer_chunk);
 
             self.streaming.desired.clear();
-            for dz in -self.config.view_distance_chunks..=self.config.view_distance_chunks {
-                for dx in -self.config.view_distance_chunks..=self.config.view_distance_chunks {
-                    self.streaming
-                        .desired
-                        .insert(viewer_chunk + IVec2::new(dx, dz));
+            let r = self.config.view_distance_chunks;
+            for dz in -r..=r {
+                for dx in -r..=r {
+                    let offset = IVec2::new(dx, dz);
+                    if self.in_view_shape(offset, r) {
+                        self.streaming.desired.insert(viewer_chunk + offset);
+                    }
                 }
             }
 
@@ -76,10 +331,16 @@ impl TerrainWorld {
                     self.streaming.pending_spawn.push_back(coord);
                 }
             }
+            self.sort_pending_spawn();
 
+            // Chunks in the margin band stay loaded without being wanted, so crossing a border
+            // and back despawns nothing.
             self.streaming.pending_despawn.clear();
+            let keep = r + self.config.despawn_margin_chunks.max(0);
             for coord in self.loaded.iter().copied() {
-                if !self.streaming.desired.contains(&coord) {
+                if !self.in_view_shape(coord - viewer_chunk, keep)
+                    && !self.streaming.prefetched.contains(&coord)
+                {
                     self.streaming.pending_despawn.push_back(coord);
                 }
             }
@@ -88,18 +349,22 @@ impl TerrainWorld {
         let mut actions = Vec::new();
 
         // Incremental despawn/spawn to avoid massive spikes at large view distances.
-        let mut budget = self.config.chunk_spawn_budget_per_frame;
+        let mut budget = self.spawn_budget();
         while budget > 0 {
             let Some(coord) = self.streaming.pending_despawn.pop_front() else {
                 break;
             };
+            // Queued before a prefetch pinned it.
+            if self.streaming.prefetched.contains(&coord) {
+                continue;
+            }
             if self.loaded.remove(&coord) {
                 actions.push(TerrainAction::DespawnChunk(coord));
             }
             budget -= 1;
         }
 
-        let mut budget = self.config.chunk_spawn_budget_per_frame;
+        let mut budget = self.spawn_budget();
         while budget > 0 {
             let Some(coord) = self.streaming.pending_spawn.pop_front() else {
                 break;
@@ -113,9 +378,30 @@ impl TerrainWorld {
             budget -= 1;
         }
 
+        // Prefetching gets whatever budget the viewer's chunks left over.
+        while budget > 0 {
+            let Some(coord) = self.streaming.pending_prefetch.pop_front() else {
+                break;
+            };
+            if !self.loaded.insert(coord) {
+                continue;
+            }
+            actions.push(TerrainAction::SpawnChunk(coord));
+            budget -= 1;
+        }
+
         actions
     }
 
+    /// Whether a chunk `offset` chunks from the viewer's (between centres, in chunk widths) is
+    /// within `radius` for the configured [`ViewShape`].
+    fn in_view_shape(&self, offset: IVec2, radius: i32) -> bool {
+        match self.config.view_shape {
+            ViewShape::Square => offset.x.abs() <= radius && offset.y.abs() <= radius,
+            ViewShape::Circle => offset.length_squared() <= radius * radius,
+        }
+    }
+
     pub fn chunk_origin_world(&self, coord: IVec2) -> Vec3 {
         let chunk_world_size = self.config.chunk_size as f32 * self.config.tile_size;
         Vec3::new(
@@ -125,27 +411,411 @@ impl TerrainWorld {
         )
     }
 
+    /// Whether the terrain at this position lies below `sea_level`.
+    pub fn is_underwater(&self, world_x: f32, world_z: f32) -> bool {
+        self.sample_height_at(world_x, world_z) < self.config.sea_level
+    }
+
     /// Get the height at a specific world position.
     pub fn sample_height_at(&self, world_x: f32, world_z: f32) -> f32 {
-        sample_height(&self.config, &self.perlin, world_x, world_z)
+        sample_height(&self.config, &self.source, world_x, world_z)
+            + self.sample_delta_at(world_x, world_z)
     }
 
-    pub fn build_chunk_mesh_data(&self, coord: IVec2, tiles: &TileTypes) -> ChunkMeshData {
-        let chunk_world_size = self.config.chunk_size as f32 * self.config.tile_size;
-        let chunk_origin_x = coord.x as f32 * chunk_world_size;
-        let chunk_origin_z = coord.y as f32 * chunk_world_size;
+    /// [`sample_height_at`](Self::sample_height_at) for many world XZ points at once.
+    ///
+    /// `out` is cleared and refilled in the same order as `points`, so callers can reuse one
+    /// buffer across queries.
+    pub fn sample_heights(&self, points: &[Vec2], out: &mut Vec<f32>) {
+        out.clear();
+        out.extend(points.iter().map(|p| self.sample_height_at(p.x, p.y)));
+    }
+
+    /// Heights on a regular grid of `dims.x` by `dims.y` points, `step` apart, starting at
+    /// `origin` (world XZ). Row-major: the height at grid `(x, z)` is at `z * dims.x + x`.
+    pub fn sample_height_grid(&self, origin: Vec2, step: f32, dims: UVec2) -> Vec<f32> {
+        let mut out = Vec::with_capacity(dims.x as usize * dims.y as usize);
+        for z in 0..dims.y {
+            for x in 0..dims.x {
+                let p = origin + Vec2::new(x as f32, z as f32) * step;
+                out.push(self.sample_height_at(p.x, p.y));
+            }
+        }
+        out
+    }
+
+    /// Lower and upper bound for any height this terrain can produce, including edits.
+    ///
+    /// The noise is normalised to [-1, 1] before scaling, so the procedural part is bounded by
+    /// `height_scale` either way. A heightmap spans 0 to its `scale`, and a custom source
+    /// reports its own range.
+    pub fn height_range(&self) -> (f32, f32) {
+        let (lo, hi) = match &self.source {
+            TerrainSource::Procedural(_) => {
+                let scale = self.config.height_scale.abs();
+                (-scale, scale)
+            }
+            TerrainSource::Heightmap { scale, .. } => (scale.min(0.0), scale.max(0.0)),
+            TerrainSource::Custom(custom) => custom.height_range(),
+        };
+        (lo + self.delta_range.0, hi + self.delta_range.1)
+    }
+
+    /// Samples the region `min_xz..max_xz` as a 16-bit heightmap of `resolution.x` by
+    /// `resolution.y` pixels, row-major with row 0 at `min_xz.y`.
+    ///
+    /// Each pixel samples the centre of its cell. Heights map linearly from
+    /// [`height_range`](Self::height_range) `(lo, hi)` onto the full `u16` range, so a pixel
+    /// value `v` re-imports as `lo + v / 65535 * (hi - lo)`; keep `lo` and `hi` alongside the
+    /// export. Flat terrain exports as all zeros. A zero-area region or resolution yields an
+    /// empty buffer.
+    pub fn export_heightmap(&self, min_xz: Vec2, max_xz: Vec2, resolution: UVec2) -> Vec<u16> {
+        let size = max_xz - min_xz;
+        if resolution.x == 0 || resolution.y == 0 || size.x <= 0.0 || size.y <= 0.0 {
+            return Vec::new();
+        }
+
+        let (lo, hi) = self.height_range();
+        let span = hi - lo;
+        let step = size / resolution.as_vec2();
+
+        let mut out = Vec::with_capacity(resolution.x as usize * resolution.y as usize);
+        for z in 0..resolution.y {
+            for x in 0..resolution.x {
+                let p = min_xz + (Vec2::new(x as f32, z as f32) + Vec2::splat(0.5)) * step;
+                let t = if span > 0.0 {
+                    (self.sample_height_at(p.x, p.y) - lo) / span
+                } else {
+                    0.0
+                };
+                out.push((t.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16);
+            }
+        }
+        out
+    }
+
+    /// True if every point of the terrain is at the same height.
+    pub fn is_flat(&self) -> bool {
+        let (lo, hi) = self.height_range();
+        lo == hi
+    }
+
+    /// The biome field at a world position, in `[0, 1]`; see [`BiomeConfig::row_coord`].
+    pub fn biome_field(&self, world_x: f32, world_z: f32, field: &BiomeField) -> f32 {
+        let mut amplitude = 1.0f64;
+        let mut frequency = field.frequency;
+        let mut sum = 0.0f64;
+        let mut norm = 0.0f64;
+        for _ in 0..field.octaves.max(1) {
+            sum += self
+                .biome_perlin
+                .noise2d(world_x as f64 * frequency, world_z as f64 * frequency)
+                * amplitude;
+            norm += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        ((sum / norm) as f32 * 0.5 + 0.5).clamp(0.0, 1.0)
+    }
+
+    /// Global vertex coordinate nearest to a world position.
+    pub fn world_to_vertex_coord(&self, world_x: f32, world_z: f32) -> IVec2 {
+        let ts = self.config.tile_size;
+        IVec2::new((world_x / ts).round() as i32, (world_z / ts).round() as i32)
+    }
+
+    /// Global coordinate of the tile containing a world position.
+    pub fn world_to_tile_coord(&self, world_x: f32, world_z: f32) -> IVec2 {
+        let ts = self.config.tile_size;
+        IVec2::new((world_x / ts).floor() as i32, (world_z / ts).floor() as i32)
+    }
+
+    /// World XZ of a tile's centre; the inverse of [`Self::world_to_tile_coord`].
+    pub fn tile_center(&self, coord: IVec2) -> Vec2 {
+        let ts = self.config.tile_size;
+        (coord.as_vec2() + Vec2::splat(0.5)) * ts
+    }
+
+    /// Height of a grid vertex, including edits.
+    pub fn vertex_height(&self, vertex: IVec2) -> f32 {
+        let ts = self.config.tile_size;
+        sample_height(
+            &self.config,
+            &self.source,
+            vertex.x as f32 * ts,
+            vertex.y as f32 * ts,
+        ) + self.height_deltas.get(&vertex).copied().unwrap_or(0.0)
+    }
+
+    /// Steepness of the terrain at a world position, as the length of the height gradient
+    /// (rise over run; 1.0 is 45 degrees).
+    ///
+    /// Uses the same central differences as the chunk mesh normals, blended across the tile the
+    /// way the normals are, so it agrees with the rendered surface.
+    pub fn slope_at(&self, world_x: f32, world_z: f32) -> f32 {
+        let ts = self.config.tile_size.max(0.001);
+        let g = Vec2::new(world_x, world_z) / ts;
+        let base = g.floor();
+        let f = g - base;
+        let v = base.as_ivec2();
+
+        let g00 = self.vertex_gradient(v);
+        let g10 = self.vertex_gradient(v + IVec2::new(1, 0));
+        let g01 = self.vertex_gradient(v + IVec2::new(0, 1));
+        let g11 = self.vertex_gradient(v + IVec2::new(1, 1));
+        let gradient = g00.lerp(g10, f.x).lerp(g01.lerp(g11, f.x), f.y);
+        gradient.length()
+    }
+
+    /// Whether units can cross the terrain here; `max_slope` is in [`slope_at`](Self::slope_at)
+    /// units.
+    pub fn is_walkable(&self, world_x: f32, world_z: f32, max_slope: f32) -> bool {
+        self.slope_at(world_x, world_z) <= max_slope
+    }
+
+    /// Height gradient (d/dx, d/dz) at a grid vertex, as used for the mesh normals.
+    fn vertex_gradient(&self, vertex: IVec2) -> Vec2 {
+        let ts = self.config.tile_size;
+        let h_l = self.vertex_height(vertex - IVec2::new(1, 0));
+        let h_r = self.vertex_height(vertex + IVec2::new(1, 0));
+        let h_d = self.vertex_height(vertex - IVec2::new(0, 1));
+        let h_u = self.vertex_height(vertex + IVec2::new(0, 1));
+        Vec2::new((h_r - h_l) / (2.0 * ts), (h_u - h_d) / (2.0 * ts))
+    }
+
+    /// Raises (or, with a negative `amount`, lowers) every vertex within `radius` of `center_xz`.
+    ///
+    /// The change fades smoothly from `amount` at the centre to zero at the edge.
+    pub fn raise_vertices(&mut self, center_xz: Vec2, radius: f32, amount: f32) {
+        for (vertex, weight) in self.brush_vertices(center_xz, radius) {
+            let delta = self.height_deltas.entry(vertex).or_insert(0.0);
+            *delta += amount * weight;
+            let delta = *delta;
+            self.widen_delta_range(delta);
+            self.mark_vertex_dirty(vertex);
+        }
+    }
+
+    /// Sets every vertex within `radius` of `center_xz` to `target_height`.
+    pub fn flatten_vertices(&mut self, center_xz: Vec2, radius: f32, target_height: f32) {
+        for (vertex, _) in self.brush_vertices(center_xz, radius) {
+            let ts = self.config.tile_size;
+            let base = sample_height(
+                &self.config,
+                &self.source,
+                vertex.x as f32 * ts,
+                vertex.y as f32 * ts,
+            );
+            self.height_deltas.insert(vertex, target_height - base);
+            self.widen_delta_range(target_height - base);
+            self.mark_vertex_dirty(vertex);
+        }
+    }
+
+    /// Levels a round pad at `target_height` out to `radius` from `center_xz`. Vertices in the
+    /// `blend`-wide ring around it ease from the pad back to the ground already there, so the
+    /// pad doesn't end in a cliff.
+    ///
+    /// Returns the edits it changed, for undo.
+    pub fn flatten_pad(
+        &mut self,
+        center_xz: Vec2,
+        radius: f32,
+        blend: f32,
+        target_height: f32,
+    ) -> Vec<HeightDeltaChange> {
+        let ts = self.config.tile_size;
+        let blend = blend.max(0.001);
+        let mut changes = Vec::new();
+        for (vertex, _) in self.brush_vertices(center_xz, radius + blend) {
+            let p = Vec2::new(vertex.x as f32 * ts, vertex.y as f32 * ts);
+            let d = p.distance(center_xz);
+            let weight = if d <= radius {
+                1.0
+            } else {
+                let t = 1.0 - (d - radius) / blend;
+                t * t * (3.0 - 2.0 * t)
+            };
+
+            let current = self.vertex_height(vertex);
+            let height = current + (target_height - current) * weight;
+            let base = sample_height(&self.config, &self.source, p.x, p.y);
+            let before = self.height_deltas.insert(vertex, height - base);
+            self.widen_delta_range(height - base);
+            self.mark_vertex_dirty(vertex);
+            changes.push(HeightDeltaChange {
+                vertex,
+                before: before.unwrap_or(0.0),
+                after: height - base,
+            });
+        }
+        changes
+    }
+
+    /// Overwrites the height edit of each vertex, e.g. to put back the `before` of some
+    /// [`HeightDeltaChange`]s.
+    pub fn set_height_deltas(&mut self, deltas: impl IntoIterator<Item = (IVec2, f32)>) {
+        for (vertex, delta) in deltas {
+            if delta == 0.0 {
+                self.height_deltas.remove(&vertex);
+            } else {
+                self.height_deltas.insert(vertex, delta);
+                self.widen_delta_range(delta);
+            }
+            self.mark_vertex_dirty(vertex);
+        }
+    }
+
+    /// Paints every tile whose centre is within `radius` of `center_xz` with atlas `column`
+    /// (with one biome, its tile index), overriding the height-based pick. `None` erases the
+    /// paint so the tiles follow their height again.
+    pub fn paint_tiles(&mut self, center_xz: Vec2, radius: f32, column: Option<u32>) {
+        let ts = self.config.tile_size;
+        let radius = radius.max(ts * 0.5);
+        let lo = self.world_to_tile_coord(center_xz.x - radius, center_xz.y - radius);
+        let hi = self.world_to_tile_coord(center_xz.x + radius, center_xz.y + radius);
+        for tz in lo.y..=hi.y {
+            for tx in lo.x..=hi.x {
+                let tile = IVec2::new(tx, tz);
+                if self.tile_center(tile).distance(center_xz) > radius {
+                    continue;
+                }
+                let previous = match column {
+                    Some(c) => self.tile_overrides.insert(tile, c),
+                    None => self.tile_overrides.remove(&tile),
+                };
+                if previous != column {
+                    // Smooth shading reads a tile's paint at its min-corner vertex, which the
+                    // chunks to the -X/-Z may share.
+                    self.mark_vertex_dirty(tile);
+                }
+            }
+        }
+    }
+
+    /// The atlas column painted on a tile, if any.
+    pub fn tile_override(&self, tile: IVec2) -> Option<u32> {
+        self.tile_overrides.get(&tile).copied()
+    }
+
+    /// The tile type under a world position, taking painted tiles into account.
+    pub fn tile_type_at<'a>(
+        &self,
+        world_x: f32,
+        world_z: f32,
+        biomes: &'a BiomeConfig,
+    ) -> &'a TileType {
+        let tile = self.world_to_tile_coord(world_x, world_z);
+        let column = self
+            .tile_override(tile)
+            .unwrap_or_else(|| biomes.column(self.sample_height_at(world_x, world_z)));
+        let field = if biomes.row_count() == 1 {
+            0.0
+        } else {
+            self.biome_field(world_x, world_z, &biomes.field)
+        };
+        biomes.tile(column, field)
+    }
+
+    /// Returns (and clears) the loaded chunks that need their mesh rebuilt.
+    pub fn take_dirty_chunks(&mut self) -> Vec<IVec2> {
+        self.dirty_chunks
+            .drain()
+            .filter(|c| self.loaded.contains(c))
+            .collect()
+    }
+
+    fn widen_delta_range(&mut self, delta: f32) {
+        self.delta_range.0 = self.delta_range.0.min(delta);
+        self.delta_range.1 = self.delta_range.1.max(delta);
+    }
+
+    /// Vertices inside the brush circle, paired with a smooth 1 -> 0 falloff weight.
+    fn brush_vertices(&self, center_xz: Vec2, radius: f32) -> Vec<(IVec2, f32)> {
+        let ts = self.config.tile_size;
+        let radius = radius.max(ts * 0.5);
+        let lo = self.world_to_vertex_coord(center_xz.x - radius, center_xz.y - radius);
+        let hi = self.world_to_vertex_coord(center_xz.x + radius, center_xz.y + radius);
+
+        let mut out = Vec::new();
+        for vz in lo.y..=hi.y {
+            for vx in lo.x..=hi.x {
+                let p = Vec2::new(vx as f32 * ts, vz as f32 * ts);
+                let d = p.distance(center_xz);
+                if d > radius {
+                    continue;
+                }
+                let t = 1.0 - d / radius;
+                out.push((IVec2::new(vx, vz), t * t * (3.0 - 2.0 * t)));
+            }
+        }
+        out
+    }
+
+    /// Marks every chunk whose mesh reads this vertex as dirty.
+    ///
+    /// That includes chunks where it's only in the one-vertex ring used for edge normals.
+    fn mark_vertex_dirty(&mut self, vertex: IVec2) {
+        let n = self.config.chunk_size.max(1);
+        let range = |v: i32| (v - n - 1).div_euclid(n)..=(v + 1).div_euclid(n);
+        for cz in range(vertex.y) {
+            for cx in range(vertex.x) {
+                let local = vertex - IVec2::new(cx, cz) * n;
+                if (-1..=n + 1).contains(&local.x) && (-1..=n + 1).contains(&local.y) {
+                    self.dirty_chunks.insert(IVec2::new(cx, cz));
+                }
+            }
+        }
+    }
+
+    /// Bilinearly interpolated height edit at a world position.
+    fn sample_delta_at(&self, world_x: f32, world_z: f32) -> f32 {
+        if self.height_deltas.is_empty() {
+            return 0.0;
+        }
+
+        let ts = self.config.tile_size;
+        let fx = world_x / ts;
+        let fz = world_z / ts;
+        let x0 = fx.floor() as i32;
+        let z0 = fz.floor() as i32;
+        let tx = fx - x0 as f32;
+        let tz = fz - z0 as f32;
+
+        let d = |x: i32, z: i32| {
+            self.height_deltas
+                .get(&IVec2::new(x, z))
+                .copied()
+                .unwrap_or(0.0)
+        };
+        let top = d(x0, z0) * (1.0 - tx) + d(x0 + 1, z0) * tx;
+        let bottom = d(x0, z0 + 1) * (1.0 - tx) + d(x0 + 1, z0 + 1) * tx;
+        top * (1.0 - tz) + bottom * tz
+    }
 
+    pub fn build_chunk_mesh_data(&self, coord: IVec2, biomes: &BiomeConfig) -> ChunkMeshData {
         let n = self.config.chunk_size.max(1) as usize;
         let stride = n + 1;
         let tile_size = self.config.tile_size;
 
-        // Pre-sample heights once per grid vertex (huge perf win vs per-tile sampling).
+        // Pre-sample heights once per grid vertex (huge perf win vs per-tile sampling), plus one
+        // ring beyond the chunk so edge normals can use the same central difference the
+        // neighbouring chunk computes. Heights are sampled from global vertex coordinates so
+        // both chunks see bit-identical values.
+        let pstride = n + 3;
+        let base_vertex = coord * self.config.chunk_size;
+        let mut padded: Vec<f32> = vec![0.0; pstride * pstride];
+        for pz in 0..pstride {
+            for px in 0..pstride {
+                let vertex = base_vertex + IVec2::new(px as i32 - 1, pz as i32 - 1);
+                padded[pz * pstride + px] = self.vertex_height(vertex);
+            }
+        }
+
         let mut heights: Vec<f32> = vec![0.0; stride * stride];
         for gz in 0..=n {
             for gx in 0..=n {
-                let wx = chunk_origin_x + gx as f32 * tile_size;
-                let wz = chunk_origin_z + gz as f32 * tile_size;
-                heights[gz * stride + gx] = sample_height(&self.config, &self.perlin, wx, wz);
+                heights[gz * stride + gx] = padded[(gz + 1) * pstride + (gx + 1)];
             }
         }
 
@@ -153,93 +823,436 @@ impl TerrainWorld {
         let mut normals_grid: Vec<[f32; 3]> = vec![[0.0, 1.0, 0.0]; stride * stride];
         for gz in 0..=n {
             for gx in 0..=n {
-                let gx_l = (gx as usize).saturating_sub(1);
-                let gx_r = (gx + 1).min(n as usize);
-                let gz_d = (gz as usize).saturating_sub(1);
-                let gz_u = (gz + 1).min(n as usize);
-
-                let h_l = heights[gz * stride + gx_l];
-                let h_r = heights[gz * stride + gx_r];
-                let h_d = heights[gz_d * stride + gx];
-                let h_u = heights[gz_u * stride + gx];
-
-                let dx = ((gx_r as i32 - gx_l as i32).max(1) as f32) * tile_size;
-                let dz = ((gz_u as i32 - gz_d as i32).max(1) as f32) * tile_size;
+                // Padded indices are offset by one, so (gx, gz) in padded space is the left/down
+                // neighbour and (gx + 2, gz + 2) the right/up one.
+                let h_l = padded[(gz + 1) * pstride + gx];
+                let h_r = padded[(gz + 1) * pstride + gx + 2];
+                let h_d = padded[gz * pstride + gx + 1];
+                let h_u = padded[(gz + 2) * pstride + gx + 1];
 
-                let dhdx = (h_r - h_l) / dx;
-                let dhdz = (h_u - h_d) / dz;
+                let dhdx = (h_r - h_l) / (2.0 * tile_size);
+                let dhdz = (h_u - h_d) / (2.0 * tile_size);
 
                 let normal = Vec3::new(-dhdx, 1.0, -dhdz).normalize_or_zero();
                 normals_grid[gz * stride + gx] = [normal.x, normal.y, normal.z];
             }
         }
 
-        let tile_count = (n * n) as usize;
-        let mut positions: Vec<[f32; 3]> = Vec::with_capacity(tile_count * 4);
-        let mut normals: Vec<[f32; 3]> = Vec::with_capacity(tile_count * 4);
-        let mut uvs: Vec<[f32; 2]> = Vec::with_capacity(tile_count * 4);
-        let mut indices: Vec<u32> = Vec::with_capacity(tile_count * 6);
-
-        for z in 0..n {
-            for x in 0..n {
-                let x0 = x as f32 * tile_size;
-                let z0 = z as f32 * tile_size;
-                let x1 = x0 + tile_size;
-                let z1 = z0 + tile_size;
-
-                let h00 = heights[z * stride + x];
-                let h10 = heights[z * stride + (x + 1)];
-                let h01 = heights[(z + 1) * stride + x];
-                let h11 = heights[(z + 1) * stride + (x + 1)];
-
-                let n00 = normals_grid[z * stride + x];
-                let n10 = normals_grid[z * stride + (x + 1)];
-                let n01 = normals_grid[(z + 1) * stride + x];
-                let n11 = normals_grid[(z + 1) * stride + (x + 1)];
-
-                let avg_h = (h00 + h10 + h01 + h11) * 0.25;
-                let tile_index = tiles.pick_tile_index(avg_h);
-                let atlas_tile_count = tiles.tile_count_f32().max(1.0);
-                let uv_u = (tile_index as f32 + 0.5) / atlas_tile_count;
-                let uv = [uv_u, 0.5];
-
-                let v0 = Vec3::new(x0, h00, z0);
-                let v1 = Vec3::new(x1, h10, z0);
-                let v2 = Vec3::new(x0, h01, z1);
-                let v3 = Vec3::new(x1, h11, z1);
-
-                let base = positions.len() as u32;
-                positions.extend_from_slice(&[
-                    [v0.x, v0.y, v0.z],
-                    [v1.x, v1.y, v1.z],
-                    [v2.x, v2.y, v2.z],
-                    [v3.x, v3.y, v3.z],
-                ]);
-                normals.extend_from_slice(&[n00, n10, n01, n11]);
-                uvs.extend_from_slice(&[uv, uv, uv, uv]);
-
-                // Winding chosen so the "top" faces upward (CCW when viewed from above).
-                indices.extend_from_slice(&[
-                    base,
-                    base + 2,
-                    base + 1,
-                    base + 1,
-                    base + 2,
-                    base + 3,
-                ]);
+        // Atlas UV for a height at a chunk-local XZ. One biome needs no field samples. Painted
+        // tiles keep their column; flat shading asks at tile centres and smooth shading at
+        // vertices, which both floor to the tile (the nudge keeps vertices off the boundary).
+        let origin = self.chunk_origin_world(coord);
+        let single_biome = biomes.row_count() == 1;
+        let atlas_uv = |h: f32, x: f32, z: f32| {
+            let field = if single_biome {
+                0.0
+            } else {
+                self.biome_field(origin.x + x, origin.z + z, &biomes.field)
+            };
+            let tile = base_vertex
+                + IVec2::new(
+                    (x / tile_size + 1e-3).floor() as i32,
+                    (z / tile_size + 1e-3).floor() as i32,
+                );
+            match self.tile_overrides.get(&tile) {
+                Some(&column) => biomes.column_uv(column, field),
+                None => biomes.atlas_uv(h, field),
             }
+        };
+
+        // Smooth shading already blends per vertex.
+        let blended = (self.config.shading == Shading::Flat
+            && self.config.tile_blend == TileBlend::Corners
+            && !biomes.is_textured())
+        .then(|| blended_corner_uvs(n, tile_size, &padded, atlas_uv));
+        let mut buffers = match self.config.shading {
+            Shading::Flat => flat_chunk_mesh(
+                n,
+                tile_size,
+                &heights,
+                &normals_grid,
+                atlas_uv,
+                CellUvs {
+                    half: biomes.cell_half_uv(),
+                    tiles_per_cell: self.config.tile_uv_mode.tiles_per_texture(tile_size),
+                    base_tile: base_vertex,
+                },
+                blended.as_deref(),
+            ),
+            Shading::Smooth => smooth_chunk_mesh(n, tile_size, &heights, &normals_grid, atlas_uv),
+        };
+        if self.config.chunk_skirts {
+            append_chunk_skirts(
+                n,
+                tile_size,
+                &heights,
+                &normals_grid,
+                self.config.chunk_skirt_depth,
+                atlas_uv,
+                &mut buffers,
+            );
         }
+        let (positions, normals, uvs, indices) = buffers;
+
+        let min_height = heights.iter().copied().fold(f32::INFINITY, f32::min);
+        let colors = debug_vertex_colors(
+            self.config.debug_colors,
+            coord,
+            &positions,
+            &normals,
+            biomes,
+        );
 
         ChunkMeshData {
             positions,
             normals,
             uvs,
             indices,
+            min_height,
+            colors,
         }
     }
 }
 
-fn sample_height(config: &TerrainConfig, perlin: &Perlin, world_x: f32, world_z: f32) -> f32 {
+/// Vertex colours for a debug mode, or `None` when it's off.
+fn debug_vertex_colors(
+    mode: DebugColorMode,
+    coord: IVec2,
+    positions: &[[f32; 3]],
+    normals: &[[f32; 3]],
+    biomes: &BiomeConfig,
+) -> Option<Vec<[f32; 4]>> {
+    const PALETTE: [[f32; 4]; 6] = [
+        [0.90, 0.30, 0.25, 1.0],
+        [0.95, 0.75, 0.20, 1.0],
+        [0.35, 0.80, 0.35, 1.0],
+        [0.25, 0.70, 0.90, 1.0],
+        [0.45, 0.35, 0.90, 1.0],
+        [0.90, 0.40, 0.80, 1.0],
+    ];
+
+    let colors = match mode {
+        DebugColorMode::None => return None,
+        DebugColorMode::Slope => normals
+            .iter()
+            .map(|n| {
+                // Rise over run, saturating at 45 degrees.
+                let ny = n[1].clamp(0.001, 1.0);
+                let slope = ((1.0 - ny * ny).sqrt() / ny).min(1.0);
+                let flat = Vec3::new(0.2, 0.8, 0.2);
+                let mid = Vec3::new(0.95, 0.85, 0.2);
+                let steep = Vec3::new(0.9, 0.15, 0.1);
+                let c = if slope < 0.5 {
+                    flat.lerp(mid, slope * 2.0)
+                } else {
+                    mid.lerp(steep, (slope - 0.5) * 2.0)
+                };
+                [c.x, c.y, c.z, 1.0]
+            })
+            .collect(),
+        DebugColorMode::ChunkChecker => {
+            let c = if (coord.x + coord.y).rem_euclid(2) == 0 {
+                [0.85, 0.85, 0.85, 1.0]
+            } else {
+                [0.35, 0.35, 0.45, 1.0]
+            };
+            vec![c; positions.len()]
+        }
+        DebugColorMode::TileIndex => positions
+            .iter()
+            .map(|p| PALETTE[biomes.column(p[1]) as usize % PALETTE.len()])
+            .collect(),
+    };
+    Some(colors)
+}
+
+type ChunkMeshBuffers = (Vec<[f32; 3]>, Vec<[f32; 3]>, Vec<[f32; 2]>, Vec<u32>);
+
+/// Where a flat tile's corners land inside its atlas cell.
+struct CellUvs {
+    /// Half the usable cell size in UV units; zero for the one-texel colour atlas.
+    half: [f32; 2],
+    /// Tiles one cell is spread over along each axis; see [`crate::TileUvMode`].
+    tiles_per_cell: i32,
+    /// Global coordinate of the chunk's first tile, so the spread lines up across chunks.
+    base_tile: IVec2,
+}
+
+impl CellUvs {
+    /// UVs of the tile's corners in mesh order: (x0, z0), (x1, z0), (x0, z1), (x1, z1).
+    fn corners(&self, center: [f32; 2], x: usize, z: usize) -> [[f32; 2]; 4] {
+        let k = self.tiles_per_cell;
+        let sub = (self.base_tile + IVec2::new(x as i32, z as i32)).rem_euclid(IVec2::splat(k));
+        let [hu, hv] = self.half;
+        // Cell-local fraction in 0..1 to UV.
+        let at = |fx: i32, fz: i32| {
+            [
+                center[0] - hu + 2.0 * hu * fx as f32 / k as f32,
+                center[1] - hv + 2.0 * hv * fz as f32 / k as f32,
+            ]
+        };
+        [
+            at(sub.x, sub.y),
+            at(sub.x + 1, sub.y),
+            at(sub.x, sub.y + 1),
+            at(sub.x + 1, sub.y + 1),
+        ]
+    }
+}
+
+/// For [`TileBlend::Corners`]: the UV at every grid vertex, averaged over the (up to four)
+/// tiles around it, `(n + 1)^2` in grid order.
+///
+/// Tiles one ring beyond the chunk come from the padded heights, so a corner on the border gets
+/// the same UV from both chunks. Averaging atlas coordinates blends colours because
+/// neighbouring columns are neighbouring height bands, and the atlas is sampled linearly.
+fn blended_corner_uvs(
+    n: usize,
+    tile_size: f32,
+    padded: &[f32],
+    atlas_uv: impl Fn(f32, f32, f32) -> [f32; 2],
+) -> Vec<[f32; 2]> {
+    let pstride = n + 3;
+    // Tiles -1..=n on each axis, stored shifted by one.
+    let ring = n + 2;
+    let mut tile_uvs: Vec<[f32; 2]> = Vec::with_capacity(ring * ring);
+    for tz in 0..ring {
+        for tx in 0..ring {
+            let h = |px: usize, pz: usize| padded[pz * pstride + px];
+            let avg_h = (h(tx, tz) + h(tx + 1, tz) + h(tx, tz + 1) + h(tx + 1, tz + 1)) * 0.25;
+            let x = (tx as f32 - 0.5) * tile_size;
+            let z = (tz as f32 - 0.5) * tile_size;
+            tile_uvs.push(atlas_uv(avg_h, x, z));
+        }
+    }
+
+    let mut corners = Vec::with_capacity((n + 1) * (n + 1));
+    for gz in 0..=n {
+        for gx in 0..=n {
+            let [a, b, c, d] = [
+                tile_uvs[gz * ring + gx],
+                tile_uvs[gz * ring + gx + 1],
+                tile_uvs[(gz + 1) * ring + gx],
+                tile_uvs[(gz + 1) * ring + gx + 1],
+            ];
+            corners.push([
+                (a[0] + b[0] + c[0] + d[0]) * 0.25,
+                (a[1] + b[1] + c[1] + d[1]) * 0.25,
+            ]);
+        }
+    }
+    corners
+}
+
+/// Four vertices per tile, all using the tile's colour.
+///
+/// `atlas_uv` maps a height and chunk-local XZ to the atlas; it's sampled at the tile centre.
+/// With a textured atlas `cell_uvs` spreads the tile's corners over (part of) that cell;
+/// `blended` (from [`blended_corner_uvs`]) replaces the per-tile UV altogether.
+fn flat_chunk_mesh(
+    n: usize,
+    tile_size: f32,
+    heights: &[f32],
+    normals_grid: &[[f32; 3]],
+    atlas_uv: impl Fn(f32, f32, f32) -> [f32; 2],
+    cell_uvs: CellUvs,
+    blended: Option<&[[f32; 2]]>,
+) -> ChunkMeshBuffers {
+    let stride = n + 1;
+    let tile_count = (n * n) as usize;
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(tile_count * 4);
+    let mut normals: Vec<[f32; 3]> = Vec::with_capacity(tile_count * 4);
+    let mut uvs: Vec<[f32; 2]> = Vec::with_capacity(tile_count * 4);
+    let mut indices: Vec<u32> = Vec::with_capacity(tile_count * 6);
+
+    for z in 0..n {
+        for x in 0..n {
+            let x0 = x as f32 * tile_size;
+            let z0 = z as f32 * tile_size;
+            let x1 = x0 + tile_size;
+            let z1 = z0 + tile_size;
+
+            let h00 = heights[z * stride + x];
+            let h10 = heights[z * stride + (x + 1)];
+            let h01 = heights[(z + 1) * stride + x];
+            let h11 = heights[(z + 1) * stride + (x + 1)];
+
+            let n00 = normals_grid[z * stride + x];
+            let n10 = normals_grid[z * stride + (x + 1)];
+            let n01 = normals_grid[(z + 1) * stride + x];
+            let n11 = normals_grid[(z + 1) * stride + (x + 1)];
+
+            let avg_h = (h00 + h10 + h01 + h11) * 0.25;
+            let uv = atlas_uv(avg_h, x0 + tile_size * 0.5, z0 + tile_size * 0.5);
+
+            let v0 = Vec3::new(x0, h00, z0);
+            let v1 = Vec3::new(x1, h10, z0);
+            let v2 = Vec3::new(x0, h01, z1);
+            let v3 = Vec3::new(x1, h11, z1);
+
+            let base = positions.len() as u32;
+            positions.extend_from_slice(&[
+                [v0.x, v0.y, v0.z],
+                [v1.x, v1.y, v1.z],
+                [v2.x, v2.y, v2.z],
+                [v3.x, v3.y, v3.z],
+            ]);
+            normals.extend_from_slice(&[n00, n10, n01, n11]);
+            match blended {
+                Some(corners) => uvs.extend_from_slice(&[
+                    corners[z * stride + x],
+                    corners[z * stride + x + 1],
+                    corners[(z + 1) * stride + x],
+                    corners[(z + 1) * stride + x + 1],
+                ]),
+                None => uvs.extend_from_slice(&cell_uvs.corners(uv, x, z)),
+            }
+
+            // Winding chosen so the "top" faces upward (CCW when viewed from above).
+            indices.extend_from_slice(&[base, base + 2, base + 1, base + 1, base + 2, base + 3]);
+        }
+    }
+
+    (positions, normals, uvs, indices)
+}
+
+/// One vertex per grid point, shared by the tiles around it, coloured by its own height.
+fn smooth_chunk_mesh(
+    n: usize,
+    tile_size: f32,
+    heights: &[f32],
+    normals_grid: &[[f32; 3]],
+    atlas_uv: impl Fn(f32, f32, f32) -> [f32; 2],
+) -> ChunkMeshBuffers {
+    let stride = n + 1;
+
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(stride * stride);
+    let mut uvs: Vec<[f32; 2]> = Vec::with_capacity(stride * stride);
+    for gz in 0..=n {
+        for gx in 0..=n {
+            let h = heights[gz * stride + gx];
+            let (x, z) = (gx as f32 * tile_size, gz as f32 * tile_size);
+            positions.push([x, h, z]);
+            uvs.push(atlas_uv(h, x, z));
+        }
+    }
+
+    let mut indices: Vec<u32> = Vec::with_capacity(n * n * 6);
+    for z in 0..n {
+        for x in 0..n {
+            let i00 = (z * stride + x) as u32;
+            let i10 = i00 + 1;
+            let i01 = i00 + stride as u32;
+            let i11 = i01 + 1;
+            // Same winding as the flat mesh.
+            indices.extend_from_slice(&[i00, i01, i10, i10, i01, i11]);
+        }
+    }
+
+    (positions, normals_grid.to_vec(), uvs, indices)
+}
+
+/// Adds a skirt below the chunk's perimeter: one quad per edge segment, hanging `depth` below
+/// the edge heights and facing outwards.
+///
+/// Skirt vertices copy the normal and atlas UV of the edge vertex above them, so where one shows
+/// through a crack it's lit and coloured like the ground around it.
+fn append_chunk_skirts(
+    n: usize,
+    tile_size: f32,
+    heights: &[f32],
+    normals_grid: &[[f32; 3]],
+    depth: f32,
+    atlas_uv: impl Fn(f32, f32, f32) -> [f32; 2],
+    (positions, normals, uvs, indices): &mut ChunkMeshBuffers,
+) {
+    let stride = n + 1;
+    let depth = depth.max(0.0);
+
+    // Grid points around the perimeter, walked so that each segment's outside is on its
+    // right-hand side seen from above (+X along the -Z edge, then +Z, -X, -Z).
+    let mut ring: Vec<(usize, usize)> = Vec::with_capacity(n * 4 + 1);
+    ring.extend((0..n).map(|x| (x, 0)));
+    ring.extend((0..n).map(|z| (n, z)));
+    ring.extend((1..=n).rev().map(|x| (x, n)));
+    ring.extend((1..=n).rev().map(|z| (0, z)));
+    ring.push((0, 0));
+
+    positions.reserve(n * 16);
+    normals.reserve(n * 16);
+    uvs.reserve(n * 16);
+    indices.reserve(n * 24);
+    for pair in ring.windows(2) {
+        let base = positions.len() as u32;
+        for &(gx, gz) in pair {
+            let h = heights[gz * stride + gx];
+            let (x, z) = (gx as f32 * tile_size, gz as f32 * tile_size);
+            let uv = atlas_uv(h, x, z);
+            let normal = normals_grid[gz * stride + gx];
+            positions.extend_from_slice(&[[x, h, z], [x, h - depth, z]]);
+            normals.extend_from_slice(&[normal, normal]);
+            uvs.extend_from_slice(&[uv, uv]);
+        }
+        // top a, bottom a, top b, bottom b; CCW seen from outside.
+        indices.extend_from_slice(&[base, base + 2, base + 1, base + 2, base + 3, base + 1]);
+    }
+}
+
+fn biome_seed(seed: u64) -> u64 {
+    seed ^ 0x5EED_B10E_0000_0001
+}
+
+/// Spawn order key for a chunk `offset` chunks from the viewer's; lower spawns first.
+///
+/// Plain distance straight ahead, growing to three times the distance directly behind.
+fn spawn_priority(offset: Vec2, facing: Vec2) -> f32 {
+    let dist = offset.length();
+    if dist == 0.0 {
+        return 0.0;
+    }
+    dist * (2.0 - offset.dot(facing) / dist)
+}
+
+fn sample_height(
+    config: &TerrainConfig,
+    source: &TerrainSource,
+    world_x: f32,
+    world_z: f32,
+) -> f32 {
+    match source {
+        TerrainSource::Procedural(perlin) => sample_noise_height(config, perlin, world_x, world_z),
+        TerrainSource::Heightmap {
+            image,
+            world_bounds: (min, max),
+            scale,
+        } => {
+            let uv = (Vec2::new(world_x, world_z) - *min) / (*max - *min);
+            // Also catches NaN from empty bounds.
+            if !(uv.cmpge(Vec2::ZERO).all() && uv.cmple(Vec2::ONE).all()) {
+                return 0.0;
+            }
+            image.sample(uv) * scale
+        }
+        TerrainSource::Custom(custom) => custom.sample(world_x as f64, world_z as f64) as f32,
+    }
+}
+
+fn sample_noise_height(config: &TerrainConfig, perlin: &Perlin, world_x: f32, world_z: f32) -> f32 {
+    // Skipped entirely when off so heights match unwarped terrain exactly.
+    let (world_x, world_z) = if config.warp_strength != 0.0 {
+        let wx = world_x as f64 * config.warp_frequency;
+        let wz = world_z as f64 * config.warp_frequency;
+        // Offset the second lookup so the two axes don't warp in lockstep.
+        let dx = perlin.noise2d(wx, wz);
+        let dz = perlin.noise2d(wx + 31.7, wz + 17.3);
+        (
+            world_x + config.warp_strength * dx as f32,
+            world_z + config.warp_strength * dz as f32,
+        )
+    } else {
+        (world_x, world_z)
+    };
+
     let mut amplitude = 1.0f64;
     let mut frequency = config.noise_base_frequency;
     let mut sum = 0.0f64;
@@ -247,6 +1260,13 @@ fn sample_height(config: &TerrainConfig, perlin: &Perlin, world_x: f32, world_z:
 
     for _ in 0..config.noise_octaves {
         let n = perlin.noise2d(world_x as f64 * frequency, world_z as f64 * frequency);
+        // Ridged and billow fold the noise into 0..1; stretch it back to -1..1 so heights keep
+        // the same range (and tile bands) as fBm.
+        let n = match config.noise_mode {
+            NoiseMode::Fbm => n,
+            NoiseMode::Ridged => (1.0 - n.abs()).powi(2) * 2.0 - 1.0,
+            NoiseMode::Billow => n.abs() * 2.0 - 1.0,
+        };
         sum += n * amplitude;
         norm += amplitude;
         amplitude *= config.noise_persistence;
@@ -256,3 +1276,501 @@ fn sample_height(config: &TerrainConfig, perlin: &Perlin, world_x: f32, world_z:
     let value = if norm > 0.0 { sum / norm } else { 0.0 };
     (value as f32) * config.height_scale
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Biome, TileTypes};
+
+    fn config() -> TerrainConfig {
+        TerrainConfig {
+            chunk_size: 8,
+            view_distance_chunks: 2,
+            ..TerrainConfig::default()
+        }
+    }
+
+    fn biomes() -> BiomeConfig {
+        let tile = |name: &str, height_lt| TileType {
+            name: name.to_string(),
+            color_srgb: (0.5, 0.5, 0.5),
+            height_lt,
+            texture: None,
+            buildable: true,
+        };
+        let biome = Biome {
+            name: "test".to_string(),
+            weight: 1.0,
+            tiles: TileTypes {
+                tiles: vec![tile("low", 0.0), tile("high", 100.0)],
+            },
+        };
+        BiomeConfig::new(vec![biome], BiomeField::default()).unwrap()
+    }
+
+    #[test]
+    fn shared_edge_normals_match_across_chunks() {
+        let mut world = TerrainWorld::new(TerrainConfig {
+            shading: Shading::Smooth,
+            warp_strength: 6.0,
+            ..config()
+        });
+        // An edit straddling the border, so the normals there aren't just the noise's.
+        world.raise_vertices(Vec2::new(16.0, 5.0), 6.0, 3.0);
+
+        let n = world.config.chunk_size as usize;
+        let stride = n + 1;
+        let biomes = biomes();
+        let west = world.build_chunk_mesh_data(IVec2::new(0, 0), &biomes);
+        let east = world.build_chunk_mesh_data(IVec2::new(1, 0), &biomes);
+        let south = world.build_chunk_mesh_data(IVec2::new(0, 1), &biomes);
+        let bits = |v: [f32; 3]| v.map(f32::to_bits);
+        for i in 0..=n {
+            // West's last column is east's first.
+            let (a, b) = (i * stride + n, i * stride);
+            assert_eq!(bits(west.normals[a]), bits(east.normals[b]), "row {i}");
+            assert_eq!(
+                west.positions[a][1].to_bits(),
+                east.positions[b][1].to_bits()
+            );
+            // West's last row is south's first.
+            let (a, b) = (n * stride + i, i);
+            assert_eq!(bits(west.normals[a]), bits(south.normals[b]), "column {i}");
+        }
+    }
+
+    #[test]
+    fn tile_coords_round_trip() {
+        for tile_size in [2.0, 0.75] {
+            let world = TerrainWorld::new(TerrainConfig {
+                tile_size,
+                ..TerrainConfig::default()
+            });
+            for z in -5..=5 {
+                for x in -5..=5 {
+                    let tile = IVec2::new(x * 7, z * 3);
+                    let center = world.tile_center(tile);
+                    assert_eq!(world.world_to_tile_coord(center.x, center.y), tile);
+
+                    // Anywhere inside the tile, not just its centre.
+                    let min = center - Vec2::splat(tile_size * 0.5);
+                    let inside = min + Vec2::splat(tile_size * 0.999);
+                    assert_eq!(world.world_to_tile_coord(min.x, min.y), tile);
+                    assert_eq!(world.world_to_tile_coord(inside.x, inside.y), tile);
+                }
+            }
+        }
+
+        // Negative coordinates floor, rather than rounding towards zero.
+        let world = TerrainWorld::new(TerrainConfig::default());
+        assert_eq!(world.world_to_tile_coord(-0.1, -0.1), IVec2::new(-1, -1));
+        assert_eq!(world.world_to_tile_coord(0.1, -2.1), IVec2::new(0, -2));
+    }
+
+    #[test]
+    fn desired_chunks_follow_the_view_shape() {
+        // Chunk offsets with dx^2 + dz^2 <= r^2, for r = 0..=3.
+        let circle = [1, 5, 13, 29];
+        for r in 0..=3 {
+            for (shape, expected) in [
+                (ViewShape::Square, ((2 * r + 1) * (2 * r + 1)) as usize),
+                (ViewShape::Circle, circle[r as usize]),
+            ] {
+                let mut world = TerrainWorld::new(TerrainConfig {
+                    view_distance_chunks: r,
+                    view_shape: shape,
+                    chunk_spawn_budget_per_frame: 1000,
+                    ..config()
+                });
+                assert_eq!(world.desired_chunk_count(), 0);
+                world.set_viewer_world_xz(Vec2::new(100.0, -50.0));
+                world.tick();
+                assert_eq!(world.desired_chunk_count(), expected, "{shape:?} r={r}");
+                assert_eq!(world.loaded_chunk_coords().count(), expected);
+            }
+        }
+    }
+
+    /// Plain fBm as it was before warping and noise modes, to compare against.
+    fn baseline_height(config: &TerrainConfig, x: f32, z: f32) -> f32 {
+        let perlin = Perlin::new(config.seed);
+        let mut amplitude = 1.0f64;
+        let mut frequency = config.noise_base_frequency;
+        let mut sum = 0.0f64;
+        let mut norm = 0.0f64;
+        for _ in 0..config.noise_octaves {
+            sum += perlin.noise2d(x as f64 * frequency, z as f64 * frequency) * amplitude;
+            norm += amplitude;
+            amplitude *= config.noise_persistence;
+            frequency *= 2.0;
+        }
+        (sum / norm) as f32 * config.height_scale
+    }
+
+    fn sample_points() -> impl Iterator<Item = Vec2> {
+        (-20..20).flat_map(|z| (-20..20).map(move |x| Vec2::new(x as f32 * 3.7, z as f32 * 5.3)))
+    }
+
+    #[test]
+    fn zero_warp_matches_the_unwarped_heights() {
+        let config = TerrainConfig {
+            warp_strength: 0.0,
+            ..TerrainConfig::default()
+        };
+        let world = TerrainWorld::new(config.clone());
+        for p in sample_points() {
+            assert_eq!(
+                world.sample_height_at(p.x, p.y).to_bits(),
+                baseline_height(&config, p.x, p.y).to_bits()
+            );
+        }
+
+        // And warping does move things.
+        let warped = TerrainWorld::new(TerrainConfig {
+            warp_strength: 25.0,
+            ..config.clone()
+        });
+        assert!(
+            sample_points().any(|p| {
+                warped.sample_height_at(p.x, p.y) != baseline_height(&config, p.x, p.y)
+            })
+        );
+    }
+
+    #[test]
+    fn noise_modes_stay_within_height_scale() {
+        for mode in [NoiseMode::Fbm, NoiseMode::Ridged, NoiseMode::Billow] {
+            let world = TerrainWorld::new(TerrainConfig {
+                noise_mode: mode,
+                height_scale: 12.0,
+                ..TerrainConfig::default()
+            });
+            let (lo, hi) = world.height_range();
+            assert_eq!((lo, hi), (-12.0, 12.0));
+            for p in sample_points() {
+                let h = world.sample_height_at(p.x, p.y);
+                assert!((lo..=hi).contains(&h), "{mode:?} gave {h} at {p}");
+            }
+        }
+    }
+
+    #[test]
+    fn fbm_mode_is_unchanged() {
+        let config = TerrainConfig {
+            noise_mode: NoiseMode::Fbm,
+            ..TerrainConfig::default()
+        };
+        let world = TerrainWorld::new(config.clone());
+        for p in sample_points() {
+            assert_eq!(
+                world.sample_height_at(p.x, p.y).to_bits(),
+                baseline_height(&config, p.x, p.y).to_bits()
+            );
+        }
+    }
+
+    #[test]
+    fn flat_and_smooth_vertex_counts() {
+        let biomes = biomes();
+        for n in [1, 8, 32] {
+            let mesh = |shading| {
+                TerrainWorld::new(TerrainConfig {
+                    chunk_size: n,
+                    shading,
+                    ..TerrainConfig::default()
+                })
+                .build_chunk_mesh_data(IVec2::new(1, -1), &biomes)
+            };
+            let n = n as usize;
+            let flat = mesh(Shading::Flat);
+            assert_eq!(flat.positions.len(), 4 * n * n);
+            assert_eq!(flat.indices.len(), 6 * n * n);
+            let smooth = mesh(Shading::Smooth);
+            assert_eq!(smooth.positions.len(), (n + 1) * (n + 1));
+            assert_eq!(smooth.normals.len(), smooth.positions.len());
+            assert_eq!(smooth.uvs.len(), smooth.positions.len());
+            assert_eq!(smooth.indices.len(), 6 * n * n);
+        }
+    }
+
+    #[test]
+    fn batch_sampling_matches_single_samples() {
+        let mut world = TerrainWorld::new(TerrainConfig::default());
+        // Include edits, which are interpolated separately from the base heights.
+        world.raise_vertices(Vec2::new(3.0, -4.0), 9.0, 2.5);
+
+        let points: Vec<Vec2> = sample_points().collect();
+        let mut heights = vec![123.0; 3];
+        world.sample_heights(&points, &mut heights);
+        assert_eq!(heights.len(), points.len());
+        for (p, h) in points.iter().zip(&heights) {
+            assert_eq!(h.to_bits(), world.sample_height_at(p.x, p.y).to_bits());
+        }
+
+        let origin = Vec2::new(-10.5, -7.25);
+        let (step, dims) = (0.8, UVec2::new(23, 17));
+        let grid = world.sample_height_grid(origin, step, dims);
+        assert_eq!(grid.len(), (dims.x * dims.y) as usize);
+        for z in 0..dims.y {
+            for x in 0..dims.x {
+                let p = origin + Vec2::new(x as f32, z as f32) * step;
+                let h = grid[(z * dims.x + x) as usize];
+                assert_eq!(h.to_bits(), world.sample_height_at(p.x, p.y).to_bits());
+            }
+        }
+    }
+
+    /// `height = slope * x`.
+    struct Ramp(f64);
+
+    impl HeightSource for Ramp {
+        fn sample(&self, x: f64, _z: f64) -> f64 {
+            self.0 * x
+        }
+
+        fn height_range(&self) -> (f32, f32) {
+            (-1e6, 1e6)
+        }
+    }
+
+    #[test]
+    fn walkability_flips_at_the_slope_limit() {
+        let mut world = TerrainWorld::new(TerrainConfig::default());
+        world.set_source(TerrainSource::Custom(Box::new(Ramp(0.5))));
+
+        for p in [Vec2::ZERO, Vec2::new(13.3, -7.9), Vec2::new(-40.0, 21.5)] {
+            let slope = world.slope_at(p.x, p.y);
+            assert!((slope - 0.5).abs() < 1e-3, "slope {slope} at {p}");
+            assert!(world.is_walkable(p.x, p.y, 0.51));
+            assert!(!world.is_walkable(p.x, p.y, 0.49));
+        }
+
+        // Flat ground is walkable with no slope allowance at all.
+        world.set_source(TerrainSource::Custom(Box::new(Ramp(0.0))));
+        assert_eq!(world.slope_at(5.0, 5.0), 0.0);
+        assert!(world.is_walkable(5.0, 5.0, 0.0));
+    }
+
+    #[test]
+    fn loaded_coords_match_the_applied_actions() {
+        let mut world = TerrainWorld::new(TerrainConfig {
+            chunk_spawn_budget_per_frame: 3,
+            ..config()
+        });
+        let mut loaded = HashSet::new();
+        // A small budget, so the view is still streaming in as the viewer moves on.
+        for step in 0..60 {
+            world.set_viewer_world_xz(Vec2::new(step as f32 * 9.0, step as f32 * -4.0));
+            for action in world.tick() {
+                match action {
+                    TerrainAction::SpawnChunk(c) => assert!(loaded.insert(c)),
+                    TerrainAction::DespawnChunk(c) => assert!(loaded.remove(&c)),
+                }
+            }
+            let coords: HashSet<IVec2> = world.loaded_chunk_coords().collect();
+            assert_eq!(coords, loaded, "after step {step}");
+            assert!(loaded.iter().all(|c| world.is_chunk_loaded(*c)));
+        }
+    }
+
+    /// Ticks until streaming has nothing left to do, keeping `loaded` in step with the actions
+    /// the way the render side does. Returns every action.
+    fn settle(world: &mut TerrainWorld, loaded: &mut HashSet<IVec2>) -> Vec<TerrainAction> {
+        let mut all = Vec::new();
+        for _ in 0..1000 {
+            let actions = world.tick();
+            if actions.is_empty() && world.pending_spawn_len() == 0 {
+                break;
+            }
+            for action in &actions {
+                match *action {
+                    TerrainAction::SpawnChunk(c) => assert!(loaded.insert(c), "{c} spawned twice"),
+                    TerrainAction::DespawnChunk(c) => assert!(loaded.remove(&c), "{c} not loaded"),
+                }
+            }
+            all.extend(actions);
+        }
+        all
+    }
+
+    #[test]
+    fn unload_all_despawns_every_loaded_chunk() {
+        let mut world = TerrainWorld::new(config());
+        let mut loaded = HashSet::new();
+        world.set_viewer_world_xz(Vec2::new(-30.0, 70.0));
+        settle(&mut world, &mut loaded);
+        world.prefetch(IVec2::new(20, 20), 1);
+        world.tick();
+        let before: HashSet<IVec2> = world.loaded_chunk_coords().collect();
+        assert!(before.len() > loaded.len());
+
+        let actions = world.unload_all();
+        let despawned: HashSet<IVec2> = actions
+            .iter()
+            .map(|a| match a {
+                TerrainAction::DespawnChunk(c) => *c,
+                TerrainAction::SpawnChunk(c) => panic!("unload_all spawned {c}"),
+            })
+            .collect();
+        assert_eq!(despawned.len(), actions.len());
+        assert_eq!(despawned, before);
+        assert_eq!(world.loaded_chunk_coords().count(), 0);
+        assert_eq!(world.pending_spawn_len(), 0);
+        assert_eq!(world.pending_despawn_len(), 0);
+
+        // Streaming starts over on the next tick.
+        assert!(!world.tick().is_empty());
+    }
+
+    #[test]
+    fn reconfigure_drops_height_and_paint_edits() {
+        let mut world = TerrainWorld::new(config());
+        let pad = Vec2::ZERO;
+        let base = world.sample_height_at(pad.x, pad.y);
+        world.flatten_pad(pad, 2.0, 1.0, base + 4.0);
+        world.paint_tiles(pad, 2.0, Some(1));
+        let tile = world.world_to_tile_coord(pad.x, pad.y);
+        assert!((world.sample_height_at(pad.x, pad.y) - (base + 4.0)).abs() < 1e-4);
+        assert_eq!(world.tile_override(tile), Some(1));
+
+        world.reconfigure(TerrainConfig {
+            height_scale: config().height_scale * 2.0,
+            ..config()
+        });
+        let fresh = TerrainWorld::new(world.config.clone());
+        assert_eq!(
+            world.sample_height_at(pad.x, pad.y),
+            fresh.sample_height_at(pad.x, pad.y)
+        );
+        assert_eq!(world.tile_override(tile), None);
+        assert_eq!(world.height_range(), fresh.height_range());
+    }
+
+    #[test]
+    fn flatten_pad_can_be_put_back() {
+        let mut world = TerrainWorld::new(config());
+        let pad = Vec2::ZERO;
+        let before: Vec<f32> = (-4..=4)
+            .map(|x| world.sample_height_at(x as f32, 0.0))
+            .collect();
+        world.raise_vertices(Vec2::new(2.0, 0.0), 3.0, 1.5);
+        let raised: Vec<f32> = (-4..=4)
+            .map(|x| world.sample_height_at(x as f32, 0.0))
+            .collect();
+        assert_ne!(raised, before);
+
+        let changes = world.flatten_pad(pad, 2.0, 1.0, raised[4] + 3.0);
+        assert!(!changes.is_empty());
+        world.set_height_deltas(changes.iter().map(|c| (c.vertex, c.before)));
+        let restored: Vec<f32> = (-4..=4)
+            .map(|x| world.sample_height_at(x as f32, 0.0))
+            .collect();
+        assert_eq!(restored, raised);
+
+        world.set_height_deltas(changes.iter().map(|c| (c.vertex, c.after)));
+        assert!((world.sample_height_at(pad.x, pad.y) - (raised[4] + 3.0)).abs() < 1e-4);
+    }
+
+    /// The same height everywhere.
+    struct Level(f32);
+
+    impl HeightSource for Level {
+        fn sample(&self, _x: f64, _z: f64) -> f64 {
+            self.0 as f64
+        }
+
+        fn height_range(&self) -> (f32, f32) {
+            (self.0, self.0)
+        }
+    }
+
+    #[test]
+    fn custom_source_replaces_the_noise() {
+        let mut world = TerrainWorld::new(TerrainConfig::default());
+        world.set_source(TerrainSource::Custom(Box::new(Level(4.5))));
+
+        for p in sample_points() {
+            assert_eq!(world.sample_height_at(p.x, p.y), 4.5);
+        }
+        assert_eq!(world.height_range(), (4.5, 4.5));
+        assert!(world.is_flat());
+        let mesh = world.build_chunk_mesh_data(IVec2::new(-2, 3), &biomes());
+        assert_eq!(mesh.min_height, 4.5);
+        assert!(mesh.positions.iter().all(|p| p[1] == 4.5));
+
+        // Edits still go on top.
+        world.raise_vertices(Vec2::ZERO, 4.0, 1.0);
+        assert_eq!(world.sample_height_at(0.0, 0.0), 5.5);
+        assert!(!world.is_flat());
+    }
+
+    #[test]
+    fn skirts_hang_below_the_chunk_edges() {
+        let biomes = biomes();
+        for shading in [Shading::Flat, Shading::Smooth] {
+            let config = TerrainConfig {
+                shading,
+                chunk_skirt_depth: 1.5,
+                ..config()
+            };
+            let plain =
+                TerrainWorld::new(config.clone()).build_chunk_mesh_data(IVec2::ONE, &biomes);
+            let world = TerrainWorld::new(TerrainConfig {
+                chunk_skirts: true,
+                ..config
+            });
+            let skirted = world.build_chunk_mesh_data(IVec2::ONE, &biomes);
+
+            // Four vertices for each of the 4n perimeter segments.
+            let n = world.config.chunk_size as usize;
+            let base = plain.positions.len();
+            assert_eq!(skirted.positions.len(), base + 16 * n);
+            assert_eq!(skirted.positions[..base], plain.positions[..]);
+
+            let ts = world.config.tile_size;
+            let edge = n as f32 * ts;
+            let origin = world.chunk_origin_world(IVec2::ONE);
+            for pair in skirted.positions[base..].chunks(2) {
+                let (top, bottom) = (pair[0], pair[1]);
+                // Tops sit on the ground along the perimeter...
+                assert!([top[0], top[2]].iter().any(|&c| c == 0.0 || c == edge));
+                let ground = world.sample_height_at(origin.x + top[0], origin.z + top[2]);
+                assert!((top[1] - ground).abs() < 1e-4);
+                // ...and bottoms hang straight down from them.
+                assert_eq!((bottom[0], bottom[2]), (top[0], top[2]));
+                assert_eq!(bottom[1], top[1] - 1.5);
+            }
+        }
+    }
+
+    #[test]
+    fn despawn_margin_stops_churn_at_a_border() {
+        // Chunks are 16 wide; the viewer rocks across the x = 16 border.
+        let churn = |margin: i32| {
+            let mut world = TerrainWorld::new(TerrainConfig {
+                despawn_margin_chunks: margin,
+                ..config()
+            });
+            let mut loaded = HashSet::new();
+            world.set_viewer_world_xz(Vec2::new(15.9, 0.0));
+            settle(&mut world, &mut loaded);
+            world.set_viewer_world_xz(Vec2::new(16.1, 0.0));
+            settle(&mut world, &mut loaded);
+
+            let mut actions = Vec::new();
+            for x in [15.9, 16.1, 15.9, 16.1, 15.9] {
+                world.set_viewer_world_xz(Vec2::new(x, 0.0));
+                actions.extend(settle(&mut world, &mut loaded));
+            }
+            actions
+        };
+
+        assert!(churn(1).is_empty());
+        // Without a margin the outermost ring goes and comes back every time.
+        assert!(
+            churn(0)
+                .iter()
+                .any(|a| matches!(a, TerrainAction::DespawnChunk(_)))
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use bevy::window::{CursorIcon, PrimaryWindow};
+
+use crate::toolbar::{ToolbarRegistry, ToolbarState, UiInputCapture};
+
+/// Set by the active tool to replace its usual cursor for now, e.g. "not allowed" while a
+/// placement is blocked. Ignored when no tool is active.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct ToolCursorOverride(pub Option<CursorIcon>);
+
+/// Shows the active tool's [`ToolbarTool::cursor`](crate::ToolbarTool::cursor), or the default
+/// arrow with no tool. Over the UI egui picks the cursor, so it's left alone there.
+pub fn update_tool_cursor(
+    mut commands: Commands,
+    toolbar: Res<ToolbarState>,
+    registry: Res<ToolbarRegistry>,
+    cursor_override: Res<ToolCursorOverride>,
+    ui_capture: Res<UiInputCapture>,
+    q_window: Query<(Entity, Option<&CursorIcon>), With<PrimaryWindow>>,
+) {
+    if ui_capture.pointer {
+        return;
+    }
+    let Ok((window, current)) = q_window.single() else {
+        return;
+    };
+
+    let wanted = toolbar
+        .active_tool
+        .and_then(|id| {
+            cursor_override
+                .0
+                .clone()
+                .or_else(|| registry.tools.iter().find(|t| t.id == id)?.cursor.clone())
+        })
+        .unwrap_or_default();
+    if current != Some(&wanted) {
+        commands.entity(window).insert(wanted);
+    }
+}
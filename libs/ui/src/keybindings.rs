@@ -0,0 +1,339 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::collections::HashMap;
+
+use crate::toolbar::{ToolId, ToolbarRegistry, format_key};
+
+/// A logical input that can be bound to a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    ToolSelect(ToolId),
+    PanForward,
+    PanBackward,
+    PanLeft,
+    PanRight,
+    OrbitLeft,
+    OrbitRight,
+    /// Tilt the camera towards looking straight down.
+    TiltSteeper,
+    /// Tilt the camera towards the horizon.
+    TiltFlatter,
+    /// Put the camera's yaw and tilt back to their defaults.
+    ResetCameraAngle,
+    /// Rotate the object being placed.
+    RotateCW,
+    RotateCCW,
+    ToggleMinimap,
+    ToggleTileGrid,
+    ToggleContours,
+    ToggleTerrainSettings,
+    ExportHeightmap,
+    /// Fly the camera to the object under the cursor.
+    FocusHovered,
+    ToggleDebugGizmos,
+    ToggleDiagnostics,
+    /// Switch the camera between perspective and orthographic.
+    ToggleProjection,
+}
+
+impl InputAction {
+    /// Every action: one per tool, then the rest in the order the rebinding panel lists them.
+    pub const ALL: [InputAction; 26] = [
+        InputAction::ToolSelect(ToolId::Construct),
+        InputAction::ToolSelect(ToolId::Destroy),
+        InputAction::ToolSelect(ToolId::Select),
+        InputAction::ToolSelect(ToolId::Terraform),
+        InputAction::ToolSelect(ToolId::Edit),
+        InputAction::ToolSelect(ToolId::Paint),
+        InputAction::PanForward,
+        InputAction::PanBackward,
+        InputAction::PanLeft,
+        InputAction::PanRight,
+        InputAction::OrbitLeft,
+        InputAction::OrbitRight,
+        InputAction::TiltSteeper,
+        InputAction::TiltFlatter,
+        InputAction::ResetCameraAngle,
+        InputAction::RotateCW,
+        InputAction::RotateCCW,
+        InputAction::ToggleMinimap,
+        InputAction::ToggleTileGrid,
+        InputAction::ToggleContours,
+        InputAction::ToggleTerrainSettings,
+        InputAction::ExportHeightmap,
+        InputAction::FocusHovered,
+        InputAction::ToggleDebugGizmos,
+        InputAction::ToggleDiagnostics,
+        InputAction::ToggleProjection,
+    ];
+
+    /// Everything except the tool actions.
+    pub const FIXED: &'static [InputAction] = Self::ALL.split_at(ToolId::ALL.len()).1;
+
+    fn label(self) -> String {
+        match self {
+            InputAction::ToolSelect(id) => format!("Tool: {:?}", id),
+            InputAction::PanForward => "Pan forward".to_string(),
+            InputAction::PanBackward => "Pan backward".to_string(),
+            InputAction::PanLeft => "Pan left".to_string(),
+            InputAction::PanRight => "Pan right".to_string(),
+            InputAction::OrbitLeft => "Orbit camera left".to_string(),
+            InputAction::OrbitRight => "Orbit camera right".to_string(),
+            InputAction::TiltSteeper => "Tilt camera steeper".to_string(),
+            InputAction::TiltFlatter => "Tilt camera flatter".to_string(),
+            InputAction::ResetCameraAngle => "Reset camera angle".to_string(),
+            InputAction::RotateCW => "Rotate placement CW".to_string(),
+            InputAction::RotateCCW => "Rotate placement CCW".to_string(),
+            InputAction::ToggleMinimap => "Toggle minimap".to_string(),
+            InputAction::ToggleTileGrid => "Toggle tile grid".to_string(),
+            InputAction::ToggleContours => "Toggle contour lines".to_string(),
+            InputAction::ToggleTerrainSettings => "Terrain settings".to_string(),
+            InputAction::ExportHeightmap => "Export heightmap".to_string(),
+            InputAction::FocusHovered => "Focus hovered object (Shift: follow)".to_string(),
+            InputAction::ToggleDebugGizmos => "Debug gizmos".to_string(),
+            InputAction::ToggleDiagnostics => "Diagnostics overlay".to_string(),
+            InputAction::ToggleProjection => "Toggle orthographic camera".to_string(),
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct KeyBindings {
+    bindings: HashMap<InputAction, KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let bindings = HashMap::from([
+            (InputAction::ToolSelect(ToolId::Construct), KeyCode::Digit1),
+            (InputAction::ToolSelect(ToolId::Destroy), KeyCode::Digit2),
+            (InputAction::ToolSelect(ToolId::Select), KeyCode::Digit3),
+            (InputAction::ToolSelect(ToolId::Terraform), KeyCode::Digit4),
+            (InputAction::ToolSelect(ToolId::Edit), KeyCode::Digit5),
+            (InputAction::ToolSelect(ToolId::Paint), KeyCode::Digit6),
+            (InputAction::PanForward, KeyCode::KeyW),
+            (InputAction::PanBackward, KeyCode::KeyS),
+            (InputAction::PanLeft, KeyCode::KeyA),
+            (InputAction::PanRight, KeyCode::KeyD),
+            (InputAction::OrbitLeft, KeyCode::KeyQ),
+            (InputAction::OrbitRight, KeyCode::KeyE),
+            (InputAction::TiltSteeper, KeyCode::PageUp),
+            (InputAction::TiltFlatter, KeyCode::PageDown),
+            (InputAction::ResetCameraAngle, KeyCode::Home),
+            (InputAction::RotateCW, KeyCode::KeyR),
+            (InputAction::RotateCCW, KeyCode::KeyF),
+            (InputAction::ToggleMinimap, KeyCode::KeyM),
+            (InputAction::ToggleTileGrid, KeyCode::KeyG),
+            (InputAction::ToggleContours, KeyCode::KeyH),
+            (InputAction::ToggleTerrainSettings, KeyCode::KeyT),
+            (InputAction::ExportHeightmap, KeyCode::F12),
+            (InputAction::FocusHovered, KeyCode::KeyC),
+            (InputAction::ToggleDebugGizmos, KeyCode::F4),
+            (InputAction::ToggleDiagnostics, KeyCode::F3),
+            (InputAction::ToggleProjection, KeyCode::KeyP),
+        ]);
+        Self { bindings }
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, action: InputAction) -> Option<KeyCode> {
+        self.bindings.get(&action).copied()
+    }
+
+    /// Whether any action is bound to `key`.
+    pub fn is_bound(&self, key: KeyCode) -> bool {
+        self.bindings.values().any(|k| *k == key)
+    }
+
+    pub fn pressed(&self, keys: &ButtonInput<KeyCode>, action: InputAction) -> bool {
+        self.key(action).is_some_and(|k| keys.pressed(k))
+    }
+
+    pub fn just_pressed(&self, keys: &ButtonInput<KeyCode>, action: InputAction) -> bool {
+        self.key(action).is_some_and(|k| keys.just_pressed(k))
+    }
+
+    /// Binds `key` to `action`.
+    ///
+    /// If another action already used `key` it takes `action`'s previous key instead (or is
+    /// left unbound), so a key never drives two actions at once.
+    pub fn rebind(&mut self, action: InputAction, key: KeyCode) {
+        let previous = self.bindings.insert(action, key);
+
+        let conflicting: Vec<InputAction> = self
+            .bindings
+            .iter()
+            .filter(|(a, k)| **a != action && **k == key)
+            .map(|(a, _)| *a)
+            .collect();
+        for other in conflicting {
+            match previous {
+                Some(prev) if prev != key => {
+                    self.bindings.insert(other, prev);
+                }
+                _ => {
+                    self.bindings.remove(&other);
+                }
+            }
+        }
+    }
+
+    /// Actions sharing a key with some other action. Empty unless the map was edited by hand,
+    /// since [`Self::rebind`] resolves conflicts as they happen.
+    pub fn conflicts(&self) -> Vec<InputAction> {
+        self.bindings
+            .iter()
+            .filter(|(a, k)| self.bindings.iter().any(|(b, l)| a != &b && k == &l))
+            .map(|(a, _)| *a)
+            .collect()
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct KeyBindingsPanel {
+    pub open: bool,
+    /// The action waiting for its new key, if any.
+    pub listening: Option<InputAction>,
+}
+
+/// Keep each toolbar button's key hint in step with the bindings.
+pub fn sync_toolbar_keys(bindings: Res<KeyBindings>, mut registry: ResMut<ToolbarRegistry>) {
+    if !bindings.is_changed() {
+        return;
+    }
+    for tool in &mut registry.tools {
+        tool.key = bindings.key(InputAction::ToolSelect(tool.id));
+    }
+}
+
+/// Assign the next key pressed to the action the panel is listening for. Escape cancels.
+pub fn capture_rebind_key(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut panel: ResMut<KeyBindingsPanel>,
+    mut bindings: ResMut<KeyBindings>,
+) {
+    let Some(action) = panel.listening else {
+        return;
+    };
+    let Some(&key) = keys.get_just_pressed().next() else {
+        return;
+    };
+
+    panel.listening = None;
+    if key != KeyCode::Escape {
+        bindings.rebind(action, key);
+    }
+}
+
+pub fn key_bindings_panel_system(
+    mut contexts: EguiContexts,
+    mut panel: ResMut<KeyBindingsPanel>,
+    bindings: Res<KeyBindings>,
+    registry: Res<ToolbarRegistry>,
+) {
+    if !panel.open {
+        return;
+    }
+
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+
+    let mut sorted_tools: Vec<_> = registry.tools.iter().collect();
+    sorted_tools.sort_by_key(|t| t.order);
+    let rows: Vec<(InputAction, String)> = sorted_tools
+        .iter()
+        .map(|t| (InputAction::ToolSelect(t.id), format!("Tool: {}", t.label)))
+        .chain(InputAction::FIXED.iter().map(|a| (*a, a.label())))
+        .collect();
+    let conflicts = bindings.conflicts();
+
+    let mut open = panel.open;
+    egui::Window::new("Key Bindings")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("key_bindings_grid")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (action, label) in rows {
+                        ui.label(label);
+
+                        let text = if panel.listening == Some(action) {
+                            "Press a key...".to_string()
+                        } else {
+                            bindings
+                                .key(action)
+                                .map(format_key)
+                                .unwrap_or_else(|| "Unbound".to_string())
+                        };
+                        let mut button = egui::Button::new(text);
+                        if conflicts.contains(&action) {
+                            button = button.fill(egui::Color32::from_rgb(120, 40, 40));
+                        }
+                        if ui.add(button).clicked() {
+                            panel.listening = Some(action);
+                        }
+                        ui.end_row();
+                    }
+                });
+            ui.label("Esc cancels a rebind.");
+        });
+
+    if !open {
+        panel.open = false;
+        panel.listening = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_action_is_listed_and_bound() {
+        // No wildcard arm, so a new variant doesn't compile until it's been added to `ALL`
+        // (and given a default key) too.
+        let listed = |action: InputAction| match action {
+            InputAction::ToolSelect(id) => {
+                ToolId::ALL.contains(&id) && InputAction::ALL.contains(&action)
+            }
+            InputAction::PanForward
+            | InputAction::PanBackward
+            | InputAction::PanLeft
+            | InputAction::PanRight
+            | InputAction::OrbitLeft
+            | InputAction::OrbitRight
+            | InputAction::TiltSteeper
+            | InputAction::TiltFlatter
+            | InputAction::ResetCameraAngle
+            | InputAction::RotateCW
+            | InputAction::RotateCCW
+            | InputAction::ToggleMinimap
+            | InputAction::ToggleTileGrid
+            | InputAction::ToggleContours
+            | InputAction::ToggleTerrainSettings
+            | InputAction::ExportHeightmap
+            | InputAction::FocusHovered
+            | InputAction::ToggleDebugGizmos
+            | InputAction::ToggleDiagnostics
+            | InputAction::ToggleProjection => InputAction::FIXED.contains(&action),
+        };
+
+        let bindings = KeyBindings::default();
+        for action in InputAction::ALL {
+            assert!(listed(action), "{action:?} is in the wrong part of ALL");
+            assert!(bindings.key(action).is_some(), "{action:?} has no key");
+        }
+        for id in ToolId::ALL {
+            assert!(InputAction::ALL.contains(&InputAction::ToolSelect(id)));
+        }
+        let unique: std::collections::HashSet<_> = InputAction::ALL.into_iter().collect();
+        assert_eq!(unique.len(), InputAction::ALL.len());
+        assert!(bindings.conflicts().is_empty());
+    }
+}
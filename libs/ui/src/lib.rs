@@ -1,8 +1,15 @@
+pub mod cursor;
+pub mod keybindings;
 pub mod toolbar;
 
+pub use cursor::{ToolCursorOverride, update_tool_cursor};
+pub use keybindings::{
+    InputAction, KeyBindings, KeyBindingsPanel, capture_rebind_key, key_bindings_panel_system,
+    sync_toolbar_keys,
+};
 pub use toolbar::{
-    ToolId, ToolbarActionText, ToolbarRegistry, ToolbarState, ToolbarTool, UiInputCapture,
-    bottom_toolbar_system, update_toolbar_state_from_hotkeys,
+    KeyChord, ToolChanged, ToolId, ToolbarActionText, ToolbarRegistry, ToolbarState, ToolbarTool,
+    UiInputCapture, bottom_toolbar_system, emit_tool_changed, update_toolbar_state_from_hotkeys,
 };
 
 use bevy::prelude::*;
@@ -16,7 +23,24 @@ impl Plugin for UiPlugin {
             .init_resource::<ToolbarActionText>()
             .insert_resource(ToolbarState::default())
             .insert_resource(UiInputCapture::default())
-            .add_systems(Update, update_toolbar_state_from_hotkeys)
-            .add_systems(EguiPrimaryContextPass, bottom_toolbar_system);
+            .init_resource::<KeyBindings>()
+            .init_resource::<KeyBindingsPanel>()
+            .init_resource::<ToolCursorOverride>()
+            .add_message::<ToolChanged>()
+            .add_systems(
+                Update,
+                (
+                    sync_toolbar_keys,
+                    update_toolbar_state_from_hotkeys,
+                    emit_tool_changed,
+                    capture_rebind_key,
+                )
+                    .chain(),
+            )
+            .add_systems(PostUpdate, update_tool_cursor)
+            .add_systems(
+                EguiPrimaryContextPass,
+                (bottom_toolbar_system, key_bindings_panel_system),
+            );
     }
 }
@@ -1,10 +1,29 @@
 use bevy::prelude::*;
+use bevy::window::CursorIcon;
 use bevy_egui::{EguiContexts, egui};
 
+use crate::keybindings::{InputAction, KeyBindings, KeyBindingsPanel};
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ToolId {
     Construct,
     Destroy,
+    Select,
+    Terraform,
+    Edit,
+    Paint,
+}
+
+impl ToolId {
+    /// Every tool, in declaration order.
+    pub const ALL: [ToolId; 6] = [
+        ToolId::Construct,
+        ToolId::Destroy,
+        ToolId::Select,
+        ToolId::Terraform,
+        ToolId::Edit,
+        ToolId::Paint,
+    ];
 }
 
 #[derive(Resource, Default, Clone, Copy, Debug)]
@@ -18,11 +37,133 @@ pub struct ToolbarRegistry {
     pub tools: Vec<ToolbarTool>,
 }
 
+impl ToolbarRegistry {
+    /// Adds `tool`, or replaces the tool already registered with the same id (with a warning),
+    /// so adding a plugin twice doesn't give it two buttons.
+    pub fn register(&mut self, tool: ToolbarTool) {
+        if let Some(existing) = self.tools.iter_mut().find(|t| t.id == tool.id) {
+            warn!(
+                "toolbar tool {:?} registered twice; keeping the latest",
+                tool.id
+            );
+            *existing = tool;
+        } else {
+            self.tools.push(tool);
+        }
+    }
+
+    /// Tools split into their groups, each sorted by `order`. Groups are ordered by their
+    /// lowest `order`; ungrouped tools each form a group of their own.
+    pub fn grouped(&self) -> Vec<Vec<&ToolbarTool>> {
+        let mut sorted: Vec<&ToolbarTool> = self.tools.iter().collect();
+        sorted.sort_by_key(|t| t.order);
+
+        let mut groups: Vec<Vec<&ToolbarTool>> = Vec::new();
+        for tool in sorted {
+            let existing = tool.group.as_ref().and_then(|name| {
+                groups
+                    .iter_mut()
+                    .find(|g| g[0].group.as_ref() == Some(name))
+            });
+            match existing {
+                Some(group) => group.push(tool),
+                None => groups.push(vec![tool]),
+            }
+        }
+        groups
+    }
+}
+
 pub struct ToolbarTool {
     pub id: ToolId,
     pub label: String,
     pub order: u32,
+    /// Tools sharing a group sit together on the toolbar, with separators between groups.
+    pub group: Option<String>,
+    /// Filled in from [`KeyBindings`]; tools don't need to set this themselves.
     pub key: Option<KeyCode>,
+    /// An extra shortcut set by the tool itself, usually a modifier chord like Shift+1 so tools
+    /// can share the number row.
+    pub secondary_key: Option<KeyChord>,
+    /// Mouse cursor while the tool is active; `None` keeps the default arrow.
+    pub cursor: Option<CursorIcon>,
+}
+
+impl ToolbarTool {
+    /// Both shortcuts for display, e.g. "1 / Shift+1". Empty if there are none.
+    pub fn key_hint(&self) -> String {
+        let keys: Vec<String> = self
+            .key
+            .map(format_key)
+            .into_iter()
+            .chain(self.secondary_key.map(|c| c.to_string()))
+            .collect();
+        keys.join(" / ")
+    }
+}
+
+/// A key plus the modifiers that must be held with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub key: KeyCode,
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            shift: false,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    pub fn shift(key: KeyCode) -> Self {
+        Self {
+            shift: true,
+            ..Self::new(key)
+        }
+    }
+
+    pub fn ctrl(key: KeyCode) -> Self {
+        Self {
+            ctrl: true,
+            ..Self::new(key)
+        }
+    }
+
+    pub fn alt(key: KeyCode) -> Self {
+        Self {
+            alt: true,
+            ..Self::new(key)
+        }
+    }
+
+    /// The key went down this frame with exactly these modifiers held.
+    pub fn just_pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+        let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        keys.just_pressed(self.key) && shift == self.shift && ctrl == self.ctrl && alt == self.alt
+    }
+}
+
+impl std::fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            f.write_str("Ctrl+")?;
+        }
+        if self.alt {
+            f.write_str("Alt+")?;
+        }
+        if self.shift {
+            f.write_str("Shift+")?;
+        }
+        f.write_str(&format_key(self.key))
+    }
 }
 
 #[derive(Resource, Debug, Default)]
@@ -33,7 +174,15 @@ pub struct ToolbarState {
 #[derive(Resource, Default)]
 pub struct ToolbarActionText(pub String);
 
-fn format_key(key: KeyCode) -> String {
+/// The active tool changed, by hotkey, button or code. Sent once per change, from
+/// [`emit_tool_changed`]; order mode enter/exit systems after it.
+#[derive(Message, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ToolChanged {
+    pub from: Option<ToolId>,
+    pub to: Option<ToolId>,
+}
+
+pub(crate) fn format_key(key: KeyCode) -> String {
     let s = format!("{:?}", key);
     if let Some(d) = s.strip_prefix("Digit") {
         return d.to_string();
@@ -48,28 +197,68 @@ pub fn update_toolbar_state_from_hotkeys(
     keys: Res<ButtonInput<KeyCode>>,
     mut toolbar: ResMut<ToolbarState>,
     registry: Res<ToolbarRegistry>,
+    bindings: Res<KeyBindings>,
+    panel: Res<KeyBindingsPanel>,
     ui_capture: Res<UiInputCapture>,
 ) {
-    if ui_capture.keyboard {
+    // A key pressed while rebinding is the new binding, not a hotkey.
+    if ui_capture.keyboard || panel.listening.is_some() {
         return;
     }
 
-    for tool in &registry.tools {
-        if let Some(key) = tool.key {
-            if keys.just_pressed(key) {
-                if toolbar.active_tool == Some(tool.id) {
-                    toolbar.active_tool = None;
-                } else {
-                    toolbar.active_tool = Some(tool.id);
-                }
-            }
+    // A chord wins over a plain binding on the same key, so Shift+1 doesn't also press 1.
+    let chord = registry
+        .tools
+        .iter()
+        .find(|t| t.secondary_key.is_some_and(|c| c.just_pressed(&keys)));
+    // Plain bindings with Ctrl or Alt held are left for other shortcuts, e.g. camera bookmarks.
+    let modified = keys.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::AltLeft,
+        KeyCode::AltRight,
+    ]);
+    let pressed: Vec<ToolId> = match chord {
+        Some(tool) => vec![tool.id],
+        None if modified => Vec::new(),
+        None => registry
+            .tools
+            .iter()
+            .filter(|t| bindings.just_pressed(&keys, InputAction::ToolSelect(t.id)))
+            .map(|t| t.id)
+            .collect(),
+    };
+
+    for id in pressed {
+        if toolbar.active_tool == Some(id) {
+            toolbar.active_tool = None;
+        } else {
+            toolbar.active_tool = Some(id);
         }
     }
 }
 
+/// Compares against the last seen tool rather than trusting change detection, so writes that
+/// leave `active_tool` as it was don't count.
+pub fn emit_tool_changed(
+    toolbar: Res<ToolbarState>,
+    mut last: Local<Option<ToolId>>,
+    mut changed: MessageWriter<ToolChanged>,
+) {
+    if toolbar.active_tool == *last {
+        return;
+    }
+    changed.write(ToolChanged {
+        from: *last,
+        to: toolbar.active_tool,
+    });
+    *last = toolbar.active_tool;
+}
+
 pub fn bottom_toolbar_system(
     mut contexts: EguiContexts,
     mut toolbar: ResMut<ToolbarState>,
+    mut key_panel: ResMut<KeyBindingsPanel>,
     registry: Res<ToolbarRegistry>,
     action_text: Res<ToolbarActionText>,
 ) {
@@ -102,11 +291,8 @@ pub fn bottom_toolbar_system(
                     if toolbar.active_tool.is_none() {
                         ui.label("Mode: None");
 
-                        let mut sorted_tools: Vec<&ToolbarTool> = registry.tools.iter().collect();
-                        sorted_tools.sort_by_key(|t| t.order);
-
-                        for tool in sorted_tools.into_iter() {
-                            let key_help = tool.key.map(format_key).unwrap_or_default();
+                        for tool in registry.grouped().into_iter().flatten() {
+                            let key_help = tool.key_hint();
                             let prefix = if key_help.is_empty() {
                                 "".to_string()
                             } else {
@@ -139,28 +325,134 @@ pub fn bottom_toolbar_system(
                     ui.set_min_size(egui::vec2(toolbar_width, toolbar_height));
 
                     ui.horizontal_centered(|ui| {
-                        let mut sorted_tools: Vec<_> = registry.tools.iter().collect();
-                        sorted_tools.sort_by_key(|t| t.order);
-
-                        for tool in sorted_tools.iter() {
-                            let is_active = toolbar.active_tool.as_ref() == Some(&tool.id);
-                            let key_hint = tool
-                                .key
-                                .map(|k| format!(" ({})", format_key(k)))
-                                .unwrap_or_default();
-                            let label = format!("{}{}", tool.label, key_hint);
-                            if ui
-                                .add(egui::Button::new(label).selected(is_active))
-                                .clicked()
-                            {
-                                if is_active {
-                                    toolbar.active_tool = None;
+                        for (i, group) in registry.grouped().into_iter().enumerate() {
+                            if i > 0 {
+                                ui.separator();
+                            }
+                            for tool in group {
+                                let is_active = toolbar.active_tool.as_ref() == Some(&tool.id);
+                                let key_hint = tool.key_hint();
+                                let label = if key_hint.is_empty() {
+                                    tool.label.clone()
                                 } else {
-                                    toolbar.active_tool = Some(tool.id.clone());
+                                    format!("{} ({})", tool.label, key_hint)
+                                };
+                                if ui
+                                    .add(egui::Button::new(label).selected(is_active))
+                                    .clicked()
+                                {
+                                    if is_active {
+                                        toolbar.active_tool = None;
+                                    } else {
+                                        toolbar.active_tool = Some(tool.id.clone());
+                                    }
                                 }
                             }
                         }
+
+                        ui.separator();
+                        if ui
+                            .add(egui::Button::new("Keys").selected(key_panel.open))
+                            .clicked()
+                        {
+                            key_panel.open = !key_panel.open;
+                            key_panel.listening = None;
+                        }
                     });
                 });
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn held(keys: &[KeyCode]) -> ButtonInput<KeyCode> {
+        let mut input = ButtonInput::default();
+        for key in keys {
+            input.press(*key);
+        }
+        input
+    }
+
+    #[test]
+    fn chords_need_exactly_their_modifiers() {
+        let shift_1 = KeyChord::shift(KeyCode::Digit1);
+        assert!(shift_1.just_pressed(&held(&[KeyCode::ShiftLeft, KeyCode::Digit1])));
+        assert!(shift_1.just_pressed(&held(&[KeyCode::ShiftRight, KeyCode::Digit1])));
+        assert!(!shift_1.just_pressed(&held(&[KeyCode::Digit1])));
+        assert!(!shift_1.just_pressed(&held(&[
+            KeyCode::ShiftLeft,
+            KeyCode::ControlLeft,
+            KeyCode::Digit1
+        ])));
+        assert!(!shift_1.just_pressed(&held(&[KeyCode::ShiftLeft, KeyCode::Digit2])));
+
+        let plain = KeyChord::new(KeyCode::Digit1);
+        assert!(plain.just_pressed(&held(&[KeyCode::Digit1])));
+        assert!(!plain.just_pressed(&held(&[KeyCode::AltLeft, KeyCode::Digit1])));
+
+        // Held from an earlier frame isn't a press.
+        let mut input = held(&[KeyCode::ShiftLeft, KeyCode::Digit1]);
+        input.clear();
+        assert!(!shift_1.just_pressed(&input));
+    }
+
+    fn tool(id: ToolId, secondary_key: Option<KeyChord>) -> ToolbarTool {
+        ToolbarTool {
+            id,
+            label: format!("{id:?}"),
+            order: 0,
+            group: None,
+            key: None,
+            secondary_key,
+            cursor: None,
+        }
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ToolbarState>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<KeyBindingsPanel>()
+            .init_resource::<UiInputCapture>()
+            .insert_resource(ToolbarRegistry {
+                tools: vec![
+                    tool(ToolId::Construct, None),
+                    tool(ToolId::Edit, Some(KeyChord::shift(KeyCode::Digit1))),
+                ],
+            })
+            .add_systems(Update, update_toolbar_state_from_hotkeys);
+        app
+    }
+
+    fn press(app: &mut App, keys: &[KeyCode]) -> Option<ToolId> {
+        *app.world_mut().resource_mut::<ButtonInput<KeyCode>>() = held(keys);
+        app.update();
+        app.world().resource::<ToolbarState>().active_tool
+    }
+
+    #[test]
+    fn plain_bindings_ignore_presses_with_ctrl_or_alt() {
+        let mut app = app();
+        assert_eq!(
+            press(&mut app, &[KeyCode::ControlLeft, KeyCode::Digit1]),
+            None
+        );
+        assert_eq!(press(&mut app, &[KeyCode::AltRight, KeyCode::Digit1]), None);
+        assert_eq!(press(&mut app, &[KeyCode::Digit1]), Some(ToolId::Construct));
+        // Pressing it again puts the tool away.
+        assert_eq!(press(&mut app, &[KeyCode::Digit1]), None);
+    }
+
+    #[test]
+    fn a_chord_wins_over_the_plain_key() {
+        let mut app = app();
+        assert_eq!(
+            press(&mut app, &[KeyCode::ShiftLeft, KeyCode::Digit1]),
+            Some(ToolId::Edit)
+        );
+        assert_eq!(press(&mut app, &[KeyCode::Digit1]), Some(ToolId::Construct));
+    }
+}